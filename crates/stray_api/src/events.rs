@@ -24,17 +24,11 @@ pub enum InputEvent{
 
 impl InputEvent{
     pub fn is_pressed(&self, vk: Key) -> bool{
-        match self{
-            Self::PRESSED(key) if key == &vk =>  true,
-            _ => false
-        }
+        matches!(self, Self::PRESSED(key) if key == &vk)
     }
 
     pub fn is_released(&self, vk: Key) -> bool{
-        match self{
-            Self::RELEASED(key) if key == &vk =>  true,
-            _ => false
-        }
+        matches!(self, Self::RELEASED(key) if key == &vk)
     }
 }
 
@@ -50,17 +44,11 @@ impl From<&KeyboardInput> for InputEvent{
 
 impl LastState{
     pub fn was_pressed(&self, vk: Key) -> bool{
-        match self{
-            Self::PRESSED(key) if key == &vk =>  true,
-            _ => false
-        }
+        matches!(self, Self::PRESSED(key) if key == &vk)
     }
 
     pub fn was_released(&self, vk: Key) -> bool{
-        match self{
-            Self::RELEASED(key) if key == &vk =>  true,
-            _ => false
-        }
+        matches!(self, Self::RELEASED(key) if key == &vk)
     }
 }
 
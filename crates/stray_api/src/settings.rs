@@ -1,4 +1,4 @@
-use stray_render::StrayBackend;
+use stray_render::{StrayBackend, LayerSort, RenderSettings, AntiAliasing};
 use winit::dpi::PhysicalSize;
 
 
@@ -10,6 +10,9 @@ pub struct Settings{
     pub backend: StrayBackend,
     pub width: u32,
     pub height: u32,
+    pub layer_sort: LayerSort,
+    pub depth_test: bool,
+    pub anti_aliasing: AntiAliasing,
 }
 
 impl Default for Settings{
@@ -20,7 +23,22 @@ impl Default for Settings{
             resizable: true, 
             backend: StrayBackend::All,
             width: 600,
-            height: 600
+            height: 600,
+            layer_sort: LayerSort::Layer,
+            depth_test: false,
+            anti_aliasing: AntiAliasing::None,
+        }
+    }
+}
+
+impl Settings{
+    /// Part of settings which is used by render
+    pub fn render_settings(&self) -> RenderSettings{
+        RenderSettings { 
+            layer_sort: self.layer_sort, 
+            depth_test: self.depth_test,
+            anti_aliasing: self.anti_aliasing,
+            requested_anti_aliasing: self.anti_aliasing,
         }
     }
 }
@@ -1,6 +1,8 @@
 use legion::*;
-use stray_scene::EngineData;
+use stray_scene::{EngineData, Background, AmbientLight, Time, AnimationEvents};
+use stray_material::Material2D;
 use wgpu::{Surface, Device, SurfaceConfiguration};
+use smaa::SmaaTarget;
 use winit::{
     event::*,
     event_loop::{ControlFlow, EventLoop},
@@ -25,7 +27,7 @@ use crate::plugin::*;
 /// 
 /// Basic example that open the window
 /// 
-/// ```
+/// ```ignore
 /// use stray_api::*;
 /// 
 /// fn main(){
@@ -41,7 +43,6 @@ pub struct Stray{
     render_schedule: Option<Schedule>,
     global_resources: Resources,
     render_resources: Resources,
-    plugins: Vec<Box<dyn Plugin>>,
     event_loop: EventLoop<()>,
     world: World,
 }
@@ -49,6 +50,7 @@ pub struct Stray{
 impl Stray{
 
     /// Initialize Stray Builder For Configuration
+    #[allow(clippy::new_ret_no_self)]
     pub fn new() -> StrayBuilder{
         StrayBuilder::new()
     }
@@ -78,16 +80,19 @@ impl Stray{
         );
         self.global_resources.insert(InputEvent::NONE);
         self.global_resources.insert(LastState::NONE);
+        self.global_resources.insert(Time::new());
+        self.global_resources.insert(AnimationEvents::default());
         
         let mut r_schedule = self.render_schedule.unwrap();
         let mut g_schedule = self.global_schedule.unwrap();
-        match initialize_render(&mut self.render_resources, &self.global_resources.get::<Window>().unwrap(), StrayBackend::All){
-            Err(e) => {
-                eprintln!("Render Error: {}", e);
-                std::process::exit(1);
-            }
-            Ok(_) => {}
+        let render_settings = self.global_resources.get::<Settings>().unwrap().render_settings();
+        if let Err(e) = initialize_render(&mut self.render_resources, &self.global_resources.get::<Window>().unwrap(), StrayBackend::All, render_settings){
+            eprintln!("Render Error: {}", e);
+            std::process::exit(1);
         }
+        // Render settings after adapter fallbacks, so user systems can see what is actually used
+        let render_settings = *self.render_resources.get::<RenderSettings>().unwrap();
+        self.global_resources.insert(render_settings);
         self.event_loop.run(move |event, _, control_flow| 
             {
                 match event {
@@ -124,9 +129,23 @@ impl Stray{
         
                     },
                     Event::MainEventsCleared => {
+                        self.global_resources.get_mut::<Time>().unwrap().update();
+                        move_resource::<Time>(&mut self.global_resources, &mut self.render_resources);
+                        move_resource::<AnimationEvents>(&mut self.global_resources, &mut self.render_resources);
+                        // Background is set by user systems, but is drawn by render schedule
+                        move_resource::<Background>(&mut self.global_resources, &mut self.render_resources);
+                        move_resource::<PostProcess>(&mut self.global_resources, &mut self.render_resources);
+                        move_resource::<AmbientLight>(&mut self.global_resources, &mut self.render_resources);
                         r_schedule.execute(&mut self.world, &mut self.render_resources);
+                        move_resource::<Time>(&mut self.render_resources, &mut self.global_resources);
+                        move_resource::<AnimationEvents>(&mut self.render_resources, &mut self.global_resources);
+                        move_resource::<Background>(&mut self.render_resources, &mut self.global_resources);
+                        move_resource::<PostProcess>(&mut self.render_resources, &mut self.global_resources);
+                        move_resource::<AmbientLight>(&mut self.render_resources, &mut self.global_resources);
                         g_schedule.execute(&mut self.world, &mut self.global_resources);
                         self.global_resources.insert(InputEvent::NONE);
+                        // Events of animators are read by global schedule of the same frame
+                        self.global_resources.get_mut::<AnimationEvents>().unwrap().0.clear();
                     }
                     _ => {}
             }
@@ -145,7 +164,7 @@ impl Stray{
 /// 
 /// example which use all of methods in StrayBuilder
 /// 
-/// ```
+/// ```ignore
 /// let stray = StrayBuilder::new()
 ///     .with_title("example")
 ///     .with_size(400, 600)
@@ -162,6 +181,14 @@ pub struct StrayBuilder{
     once_schedule: systems::Builder,
     stray: Stray,
     settings: Settings,
+    // Adds render systems of registered materials
+    materials: Vec<fn(&mut systems::Builder)>,
+}
+
+impl Default for StrayBuilder{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl StrayBuilder{
@@ -178,8 +205,7 @@ impl StrayBuilder{
             render_schedule: None,
             global_resources: Resources::default(), 
             render_resources: Resources::default(),
-            plugins: vec![], 
-            event_loop: event_loop, 
+            event_loop, 
             world: World::default(),
         };
 
@@ -189,6 +215,7 @@ impl StrayBuilder{
             once_schedule,
             stray,
             settings,
+            materials: vec![],
         }
     }
     
@@ -205,13 +232,41 @@ impl StrayBuilder{
         self
     }
 
+    /// Sets order in which objects of the same layer are drawn
+    pub fn with_layer_sort(mut self, layer_sort: LayerSort) -> Self{
+        self.settings.layer_sort = layer_sort;
+        self
+    }
+
+    /// Enables depth buffer in render pipelines, 
+    /// useful when drawing a lot of opaque geometry.
+    /// Every object is tested against depth, but only materials with ``BlendState::REPLACE`` write it
+    pub fn with_depth_test(mut self, depth_test: bool) -> Self{
+        self.settings.depth_test = depth_test;
+        self
+    }
+
+    /// Sets smoothing of edges, MSAA sample count is lowered 
+    /// when it isn't supported by adapter. Used anti-aliasing can be read from ``RenderSettings`` resource
+    pub fn with_anti_aliasing(mut self, anti_aliasing: AntiAliasing) -> Self{
+        self.settings.anti_aliasing = anti_aliasing;
+        self
+    }
+
+    /// Sets background which is drawn behind all objects,
+    /// it can be changed later with ``Background`` resource
+    pub fn with_background(mut self, background: Background) -> Self{
+        self.stray.global_resources.insert(background);
+        self
+    }
+
     /// Adds system to run_once schedule.
     /// Is used for initalizating entites.
     /// Executed while build method is called
     /// 
     /// ### Example
     /// 
-    /// ```
+    /// ```ignore
     /// Stray::new()
     ///     .run_once()
     ///     .build();
@@ -234,7 +289,7 @@ impl StrayBuilder{
     /// 
     /// ### Example
     /// 
-    /// ```
+    /// ```ignore
     /// Stray::new()
     ///     .add_system(player_movement_system())
     ///     .build()
@@ -252,12 +307,58 @@ impl StrayBuilder{
         self
     }
 
+    /// Adds effect to the end of post process stack,
+    /// it can be changed later with ``PostProcess`` resource
+    pub fn with_post_effect(mut self, effect: PostEffect) -> Self{
+        if !self.stray.global_resources.contains::<PostProcess>(){
+            self.stray.global_resources.insert(PostProcess::default());
+        }
+        self.stray.global_resources.get_mut::<PostProcess>().unwrap().0.push(effect);
+        self
+    }
+
+    /// Registers custom material, so ``MaterialSprite<M>`` and ``MaterialDraw<M>`` are drawn
+    /// 
+    /// ### Example
+    /// 
+    /// ```no_run
+    /// use stray_api::*;
+    /// use stray_material::Material2D;
+    ///
+    /// struct Dissolve{
+    ///     amount: f32,
+    /// }
+    ///
+    /// impl Material2D for Dissolve{
+    ///     fn fragment_shader() -> &'static str{
+    ///         "@fragment fn fs_main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }"
+    ///     }
+    ///
+    ///     fn uniform(&self) -> Vec<u8>{
+    ///         self.amount.to_ne_bytes().to_vec()
+    ///     }
+    /// }
+    ///
+    /// Stray::new()
+    ///     .add_material::<Dissolve>()
+    ///     .build()
+    ///     .run();
+    /// ```
+    pub fn add_material<M: Material2D>(mut self) -> Self{
+        self.materials.push(|schedule| {
+            schedule.add_system(prepare_material_pipelines_system::<M>());
+            schedule.add_system(read_material_sprites_system::<M>());
+            schedule.add_system(read_material_draws_system::<M>());
+        });
+        self
+    }
+
     /// Creates entity from given components and pushes it to legion world in stray struct.
     /// Is used for storing data like velocity, hp, etc.
     /// 
     /// ## Example
     /// 
-    /// ```
+    /// ```ignore
     /// struct Player{
     ///     velocity: Vec2,
     ///     hp: i32,
@@ -290,7 +391,7 @@ impl StrayBuilder{
     /// Inserts resource to global resources
     /// 
     /// ## Example
-    /// ```
+    /// ```ignore
     /// let number = 100;
     /// let stray = Stray::new()
     ///     .insert(number)
@@ -309,7 +410,7 @@ impl StrayBuilder{
     /// Adds and **builds** plugin 
     /// ## Example
     /// 
-    /// ```
+    /// ```ignore
     /// struct MyPlugin;
     /// 
     /// impl Plugin for MyPlugin{
@@ -330,9 +431,30 @@ impl StrayBuilder{
     }
 
     fn init_systems(&mut self){
+        self.render_schedule.add_system(switch_ldtk_levels_system());
+        self.render_schedule.add_system(propagate_transforms_system());
+        // Global transforms added by propagation have to exist before objects are read
+        self.render_schedule.flush();
+        self.render_schedule.add_system(release_render_targets_system());
+        self.render_schedule.add_system(read_cameras_system());
+        self.render_schedule.add_system(read_point_lights_system());
+        self.render_schedule.add_system(read_spot_lights_system());
+        self.render_schedule.add_system(read_occluders_system());
         self.render_schedule.add_system(read_geometry_system());
-        self.render_schedule.add_system(redraw_system());
+        self.render_schedule.add_system(read_shapes_system());
+        self.render_schedule.add_system(read_sdf_shapes_system());
         self.render_schedule.add_system(read_sprites_system());
+        self.render_schedule.add_system(advance_animators_system());
+        self.render_schedule.add_system(read_atlas_sprites_system());
+        self.render_schedule.add_system(animate_tilemaps_system());
+        self.render_schedule.add_system(read_tilemaps_system());
+        self.render_schedule.add_system(read_text_system());
+        self.render_schedule.add_system(advance_typewriters_system());
+        self.render_schedule.add_system(read_rich_text_system());
+        for add_material_systems in self.materials.iter(){
+            add_material_systems(&mut self.render_schedule);
+        }
+        self.render_schedule.add_system(redraw_system());
     }
     
     /// Finalizes Stray app configuring
@@ -346,6 +468,15 @@ impl StrayBuilder{
         parse_settings(&self.settings,&window);
         self.stray.global_resources.insert(self.settings);
         self.stray.global_resources.insert(window);
+        if !self.stray.global_resources.contains::<Background>(){
+            self.stray.global_resources.insert(Background::default());
+        }
+        if !self.stray.global_resources.contains::<PostProcess>(){
+            self.stray.global_resources.insert(PostProcess::default());
+        }
+        if !self.stray.global_resources.contains::<AmbientLight>(){
+            self.stray.global_resources.insert(AmbientLight::default());
+        }
         self.once_schedule.build().execute(&mut self.stray.world, &mut self.stray.global_resources); 
         self.stray.global_schedule = Some(self.global_schedule.build());
         self.stray.render_schedule = Some(self.render_schedule.build());
@@ -358,6 +489,12 @@ impl StrayBuilder{
 
 // Other stuff
 
+fn move_resource<T: systems::Resource>(from: &mut Resources, to: &mut Resources){
+    if let Some(resource) = from.remove::<T>(){
+        to.insert(resource);
+    }
+}
+
 pub fn resize(render_res: &Resources, global_res: &Resources, new_size: winit::dpi::PhysicalSize<u32>) {
     if new_size.width > 0 && new_size.height > 0 {
         global_res.get_mut::<Settings>().unwrap().width = new_size.width;
@@ -365,6 +502,7 @@ pub fn resize(render_res: &Resources, global_res: &Resources, new_size: winit::d
         render_res.get_mut::<EngineData<SurfaceConfiguration>>().unwrap().0.width = new_size.width;
         render_res.get_mut::<EngineData<SurfaceConfiguration>>().unwrap().0.height = new_size.height;
         render_res.get::<EngineData<Surface>>().unwrap().0.configure(&render_res.get::<EngineData<Device>>().unwrap().0, &render_res.get::<EngineData<SurfaceConfiguration>>().unwrap().0);
+        render_res.get_mut::<EngineData<SmaaTarget>>().unwrap().0.resize(&render_res.get::<EngineData<Device>>().unwrap().0, new_size.width, new_size.height);
     }
 }
 
@@ -1,2 +1,4 @@
 mod material;
+mod material2d;
 pub use material::*;
+pub use material2d::*;
@@ -1,5 +1,4 @@
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct StandardMaterial{
     pub color: StrayColor
 }
@@ -10,10 +9,283 @@ impl StandardMaterial{
     }
 }
 
-#[derive(Default, Debug, Clone, Copy)]
+impl Default for StandardMaterial{
+    fn default() -> Self {
+        Self::new(StrayColor::WHITE)
+    }
+}
+
+/// ## Overview
+/// Color with channels in ``[0,1]`` range.
+/// Channels are stored in sRGB space, the same as in color pickers and image editors,
+/// render converts them to linear space before blending
+///
+/// ## Example
+///
+/// ```
+/// use stray_material::StrayColor;
+///
+/// let orange = StrayColor::srgb_u8(255, 128, 0);
+/// let sky = StrayColor::hex("#87ceeb").unwrap();
+/// let grass = StrayColor::hsv(120.0, 0.8, 0.6);
+/// let half_transparent = StrayColor::RED.with_alpha(0.5);
+/// let mixed = orange.lerp(sky, 0.5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct StrayColor{
-    pub r: i16,
-    pub g: i16,
-    pub b: i16,
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
     pub a: f32
 }
+
+impl StrayColor{
+    pub const WHITE: Self = Self::srgb(1.0, 1.0, 1.0);
+    pub const BLACK: Self = Self::srgb(0.0, 0.0, 0.0);
+    pub const GRAY: Self = Self::srgb(0.5, 0.5, 0.5);
+    pub const RED: Self = Self::srgb(1.0, 0.0, 0.0);
+    pub const GREEN: Self = Self::srgb(0.0, 1.0, 0.0);
+    pub const BLUE: Self = Self::srgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Self = Self::srgb(1.0, 1.0, 0.0);
+    pub const CYAN: Self = Self::srgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Self = Self::srgb(1.0, 0.0, 1.0);
+    pub const ORANGE: Self = Self::srgb(1.0, 0.65, 0.0);
+    pub const PURPLE: Self = Self::srgb(0.5, 0.0, 0.5);
+    pub const TRANSPARENT: Self = Self::srgba(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn srgb(r: f32, g: f32, b: f32) -> Self{
+        Self{r, g, b, a: 1.0}
+    }
+
+    pub const fn srgba(r: f32, g: f32, b: f32, a: f32) -> Self{
+        Self{r, g, b, a}
+    }
+
+    /// Color from 8-bit channels, like ``rgb(255, 128, 0)`` in css
+    pub fn srgb_u8(r: u8, g: u8, b: u8) -> Self{
+        Self::srgba_u8(r, g, b, 255)
+    }
+
+    pub fn srgba_u8(r: u8, g: u8, b: u8, a: u8) -> Self{
+        Self::srgba(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, a as f32 / 255.0)
+    }
+
+    /// Color from channels in linear space, e.g. computed by lighting
+    pub fn linear(r: f32, g: f32, b: f32) -> Self{
+        Self::linear_rgba(r, g, b, 1.0)
+    }
+
+    pub fn linear_rgba(r: f32, g: f32, b: f32, a: f32) -> Self{
+        Self::srgba(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+    }
+
+    /// Parses ``RGB``, ``RGBA``, ``RRGGBB`` and ``RRGGBBAA`` hex strings, ``#`` prefix is optional
+    pub fn hex(hex: &str) -> Result<Self, &'static str>{
+        let hex = hex.trim().trim_start_matches('#');
+        if !hex.is_ascii(){
+            return Err("Hex color contains non-ASCII characters");
+        }
+        let digit = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16)
+            .map_err(|_| "Hex color contains invalid digit");
+        match hex.len(){
+            3 | 4 => {
+                let a = if hex.len() == 4 { digit(3, 1)? * 17 } else { 255 };
+                Ok(Self::srgba_u8(digit(0, 1)? * 17, digit(1, 1)? * 17, digit(2, 1)? * 17, a))
+            }
+            6 | 8 => {
+                let a = if hex.len() == 8 { digit(3, 2)? } else { 255 };
+                Ok(Self::srgba_u8(digit(0, 2)?, digit(1, 2)?, digit(2, 2)?, a))
+            }
+            _ => Err("Hex color must have 3, 4, 6 or 8 digits")
+        }
+    }
+
+    /// Color from hue in degrees, saturation and value in ``[0,1]`` range
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Self{
+        Self::hsva(hue, saturation, value, 1.0)
+    }
+
+    pub fn hsva(hue: f32, saturation: f32, value: f32, alpha: f32) -> Self{
+        let chroma = value * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = value - chroma;
+        Self::srgba(r + m, g + m, b + m, alpha)
+    }
+
+    /// Color from hue in degrees, saturation and lightness in ``[0,1]`` range
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self{
+        Self::hsla(hue, saturation, lightness, 1.0)
+    }
+
+    pub fn hsla(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self{
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let (r, g, b) = hue_to_rgb(hue, chroma);
+        let m = lightness - chroma / 2.0;
+        Self::srgba(r + m, g + m, b + m, alpha)
+    }
+
+    /// Returns ``[hue, saturation, value]``, hue is in degrees
+    pub fn to_hsv(&self) -> [f32;3]{
+        let (max, min, hue) = self.hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        [hue, saturation, max]
+    }
+
+    /// Returns ``[hue, saturation, lightness]``, hue is in degrees
+    pub fn to_hsl(&self) -> [f32;3]{
+        let (max, min, hue) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        [hue, saturation, lightness]
+    }
+
+    pub fn with_alpha(mut self, alpha: f32) -> Self{
+        self.a = alpha;
+        self
+    }
+
+    /// Interpolates channels in sRGB space, ``t`` is in ``[0,1]`` range
+    pub fn lerp(&self, other: StrayColor, t: f32) -> Self{
+        Self::srgba(
+            self.r + (other.r - self.r) * t,
+            self.g + (other.g - self.g) * t,
+            self.b + (other.b - self.b) * t,
+            self.a + (other.a - self.a) * t
+        )
+    }
+
+    /// Interpolates channels in linear space, which is physically correct mix of lights
+    pub fn lerp_linear(&self, other: StrayColor, t: f32) -> Self{
+        let (from, to) = (self.to_linear(), other.to_linear());
+        let mix = |i: usize| from[i] + (to[i] - from[i]) * t;
+        Self::linear_rgba(mix(0), mix(1), mix(2), mix(3))
+    }
+
+    /// Channels in linear space with straight alpha, this is how colors are passed to shaders
+    pub fn to_linear(&self) -> [f32;4]{
+        [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b), self.a]
+    }
+
+    pub fn to_srgba_u8(&self) -> [u8;4]{
+        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [channel(self.r), channel(self.g), channel(self.b), channel(self.a)]
+    }
+
+    // Returns max channel, min channel and hue in degrees
+    fn hue(&self) -> (f32, f32, f32){
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == self.r {
+            60.0 * ((self.g - self.b) / delta).rem_euclid(6.0)
+        } else if max == self.g {
+            60.0 * ((self.b - self.r) / delta + 2.0)
+        } else {
+            60.0 * ((self.r - self.g) / delta + 4.0)
+        };
+        (max, min, hue)
+    }
+}
+
+impl Default for StrayColor{
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+fn hue_to_rgb(hue: f32, chroma: f32) -> (f32, f32, f32){
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    match hue as u32{
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+pub fn srgb_to_linear(channel: f32) -> f32{
+    if channel <= 0.04045 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(channel: f32) -> f32{
+    if channel <= 0.0031308 {
+        channel * 12.92
+    } else {
+        1.055 * channel.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn assert_color(color: StrayColor, expected: [f32;4]){
+        let channels = [color.r, color.g, color.b, color.a];
+        for (channel, expected) in channels.iter().zip(expected.iter()){
+            assert!((channel - expected).abs() < 1e-4, "{:?} != {:?}", channels, expected);
+        }
+    }
+
+    #[test]
+    fn hex_parses_all_lengths(){
+        assert_eq!(StrayColor::hex("#f80").unwrap().to_srgba_u8(), [255, 136, 0, 255]);
+        assert_eq!(StrayColor::hex("f808").unwrap().to_srgba_u8(), [255, 136, 0, 136]);
+        assert_eq!(StrayColor::hex("#87ceeb").unwrap().to_srgba_u8(), [135, 206, 235, 255]);
+        assert_eq!(StrayColor::hex(" 87CEEB80 ").unwrap().to_srgba_u8(), [135, 206, 235, 128]);
+    }
+
+    #[test]
+    fn hex_rejects_invalid_input(){
+        for hex in ["", "#", "#12", "#12345", "#1234567", "#123456789", "#ggg", "#12345z", "#+12", "#ééé"]{
+            assert!(StrayColor::hex(hex).is_err(), "{} was parsed", hex);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trips(){
+        assert_color(StrayColor::hsv(0.0, 1.0, 1.0), [1.0, 0.0, 0.0, 1.0]);
+        assert_color(StrayColor::hsv(120.0, 1.0, 1.0), [0.0, 1.0, 0.0, 1.0]);
+        assert_color(StrayColor::hsv(600.0, 1.0, 1.0), [0.0, 0.0, 1.0, 1.0]);
+        assert_color(StrayColor::hsva(30.0, 0.5, 0.8, 0.25), [0.8, 0.6, 0.4, 0.25]);
+        for hue in (0..360).step_by(15){
+            let [h, s, v] = StrayColor::hsv(hue as f32, 0.7, 0.9).to_hsv();
+            assert!((h - hue as f32).abs() < 1e-3, "{} != {}", h, hue);
+            assert!((s - 0.7).abs() < 1e-4 && (v - 0.9).abs() < 1e-4);
+        }
+        assert_eq!(StrayColor::GRAY.to_hsv(), [0.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn hsl_round_trips(){
+        assert_color(StrayColor::hsl(240.0, 1.0, 0.5), [0.0, 0.0, 1.0, 1.0]);
+        assert_color(StrayColor::hsl(0.0, 0.0, 1.0), [1.0, 1.0, 1.0, 1.0]);
+        assert_color(StrayColor::hsl(-60.0, 1.0, 0.25), [0.5, 0.0, 0.5, 1.0]);
+        for hue in (0..360).step_by(15){
+            let [h, s, l] = StrayColor::hsl(hue as f32, 0.6, 0.3).to_hsl();
+            assert!((h - hue as f32).abs() < 1e-3, "{} != {}", h, hue);
+            assert!((s - 0.6).abs() < 1e-4 && (l - 0.3).abs() < 1e-4);
+        }
+        assert_eq!(StrayColor::BLACK.to_hsl(), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn srgb_and_linear_are_inverse(){
+        for i in 0..=255{
+            let channel = i as f32 / 255.0;
+            assert!((linear_to_srgb(srgb_to_linear(channel)) - channel).abs() < 1e-5);
+            assert!((srgb_to_linear(linear_to_srgb(channel)) - channel).abs() < 1e-5);
+        }
+        assert!((srgb_to_linear(0.5) - 0.21404).abs() < 1e-4);
+        assert!((linear_to_srgb(0.5) - 0.73536).abs() < 1e-4);
+        assert_color(StrayColor::linear(0.0, 0.5, 1.0), [0.0, linear_to_srgb(0.5), 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,82 @@
+use wgpu::BlendState;
+
+use stray_texture::StrayTexture;
+
+/// ## Overview
+/// Material with user WGSL shader, it's drawn by ``MaterialSprite`` and ``MaterialDraw`` components.
+/// Pipeline is built once per material type and cached, material has to be registered with
+/// ``StrayBuilder::add_material``.
+///
+/// Bindings which are available in shaders:
+/// - ``@group(0)`` texture of sprite at ``@binding(0)`` and its sampler at ``@binding(1)``
+/// - ``@group(1) @binding(0)`` camera uniform with ``view_projection: mat4x4<f32>``
+/// - ``@group(2) @binding(0)`` uniform of material, see ``Material2D::uniform``
+/// - ``@group(2)`` extra textures, texture ``i`` at ``@binding(1 + 2 * i)`` and its sampler at ``@binding(2 + 2 * i)``
+///
+/// Default vertex shader passes this struct to fragment shader:
+///
+/// ```wgsl
+/// struct VertexOutput {
+///     @builtin(position) clip_position: vec4<f32>,
+///     @location(0) tex_coords: vec2<f32>,
+///     @location(1) color: vec4<f32>,
+///     @location(2) world_position: vec2<f32>,
+/// }
+/// ```
+///
+/// ## Example
+///
+/// ```ignore
+/// #[repr(C)]
+/// #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+/// struct Dissolve{
+///     amount: f32,
+///     _padding: [f32;3],
+/// }
+///
+/// impl Material2D for Dissolve{
+///     fn fragment_shader() -> &'static str{
+///         include_str!("dissolve.wgsl")
+///     }
+///
+///     fn uniform(&self) -> Vec<u8>{
+///         bytemuck::bytes_of(self).to_vec()
+///     }
+/// }
+///
+/// Stray::new()
+///     .add_material::<Dissolve>()
+///     .push((Transform2D::ZERO, MaterialSprite::new(Sprite::new(include_bytes!("cat.png"), 0), Dissolve{amount: 0.5, _padding: [0.0;3]})))
+///     .build()
+///     .run();
+/// ```
+pub trait Material2D: Send + Sync + 'static{
+    /// Number of extra textures, it has to be the same for all materials of this type
+    const TEXTURE_COUNT: u32 = 0;
+
+    /// WGSL source of fragment shader with ``fs_main`` entry point
+    fn fragment_shader() -> &'static str;
+
+    /// WGSL source of vertex shader with ``vs_main`` entry point,
+    /// ``None`` uses default vertex shader
+    fn vertex_shader() -> Option<&'static str>{
+        None
+    }
+
+    /// Blending of material with pixels behind it. Only materials with ``BlendState::REPLACE``
+    /// write to depth buffer when depth test is enabled
+    fn blend_state() -> BlendState{
+        BlendState::ALPHA_BLENDING
+    }
+
+    /// Bytes of uniform struct, it's padded to 16 bytes.
+    /// Struct has to follow WGSL alignment rules, so ``#[repr(C)]`` and explicit padding are recommended
+    fn uniform(&self) -> Vec<u8>{
+        vec![]
+    }
+
+    /// Extra textures, there has to be ``TEXTURE_COUNT`` of them
+    fn textures(&self) -> Vec<&StrayTexture>{
+        vec![]
+    }
+}
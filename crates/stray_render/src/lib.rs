@@ -1,5 +1,7 @@
 mod render;
 mod pipeline;
+mod post_process;
 
 pub use pipeline::*;
-pub use render::*;
\ No newline at end of file
+pub use render::*;
+pub use post_process::*;
\ No newline at end of file
@@ -0,0 +1,183 @@
+use std::any::{TypeId, type_name};
+
+use wgpu::{*, util::DeviceExt};
+
+use stray_scene::*;
+use stray_material::Material2D;
+use stray_texture::RenderTargets;
+
+use crate::{RenderSettings, depth_stencil_state, multisample_state};
+
+/// Layout of ``@group(2)``, uniform of material and its extra textures
+pub fn create_material_layout<M: Material2D>(device: &Device) -> BindGroupLayout{
+    let mut entries = vec![
+        BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::VERTEX_FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+    ];
+    for i in 0..M::TEXTURE_COUNT{
+        entries.push(BindGroupLayoutEntry {
+            binding: 1 + 2 * i,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Texture {
+                multisampled: false,
+                view_dimension: TextureViewDimension::D2,
+                sample_type: TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        });
+        entries.push(BindGroupLayoutEntry {
+            binding: 2 + 2 * i,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            count: None,
+        });
+    }
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        entries: &entries,
+        label: Some("material_bind_group_layout"),
+    })
+}
+
+pub fn create_material_pipeline<M: Material2D>(
+    device: &Device,
+    config: &SurfaceConfiguration,
+    camera_layout: &StrayCameraLayout,
+    texture_layout: &BindGroupLayout,
+    render_settings: &RenderSettings
+) -> (RenderPipeline, BindGroupLayout){
+    let material_layout = create_material_layout::<M>(device);
+    let vertex_shader = match M::vertex_shader(){
+        Some(source) => device.create_shader_module(ShaderModuleDescriptor {
+            label: Some(type_name::<M>()),
+            source: ShaderSource::Wgsl(source.into()),
+        }),
+        None => device.create_shader_module(include_wgsl!("shaders/material.wgsl"))
+    };
+    let fragment_shader = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some(type_name::<M>()),
+        source: ShaderSource::Wgsl(M::fragment_shader().into()),
+    });
+    let render_pipeline_layout = device.create_pipeline_layout(
+        &PipelineLayoutDescriptor {
+            label: Some("Material Pipeline Layout"),
+            bind_group_layouts: &[texture_layout, &camera_layout.0, &material_layout],
+            push_constant_ranges: &[],
+        }
+    );
+    let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor{
+        label: Some(type_name::<M>()),
+        layout: Some(&render_pipeline_layout),
+        vertex: VertexState{
+            module: &vertex_shader,
+            entry_point: "vs_main",
+            buffers: &[RawVertex::desc()]
+        },
+        fragment: Some(FragmentState{
+            module: &fragment_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(M::blend_state()),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: Some(Face::Back),
+            polygon_mode: PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        // Materials which replace pixels are opaque, so they can write depth
+        depth_stencil: depth_stencil_state(render_settings.depth_test, M::blend_state() == BlendState::REPLACE),
+        multisample: multisample_state(render_settings),
+        multiview: None,
+
+    });
+
+    (render_pipeline, material_layout)
+}
+
+/// Builds pipeline of material when it's drawn for the first time
+pub fn prepare_material_pipeline<M: Material2D>(
+    pipelines: &mut MaterialPipelines,
+    device: &Device,
+    config: &SurfaceConfiguration,
+    camera_layout: &StrayCameraLayout,
+    texture_layout: &BindGroupLayout,
+    render_settings: &RenderSettings
+){
+    pipelines.0.entry(TypeId::of::<M>()).or_insert_with(|| {
+        create_material_pipeline::<M>(device, config, camera_layout, texture_layout, render_settings)
+    });
+}
+
+/// Creates bind group with uniform and textures of material, together with ids of render targets it samples.
+/// Returns ``None`` if number of textures doesn't match layout or render target isn't allocated yet
+pub fn create_material_bind_group<M: Material2D>(
+    material: &M,
+    device: &Device,
+    queue: &Queue,
+    layout: &BindGroupLayout,
+    render_targets: &RenderTargets
+) -> Option<(BindGroup, Vec<u32>)>{
+    let textures = material.textures();
+    if textures.len() != M::TEXTURE_COUNT as usize{
+        return None;
+    }
+    // Uniform can't be empty and its size has to be multiple of 16 bytes
+    let mut uniform = material.uniform();
+    uniform.resize(uniform.len().div_ceil(16).max(1) * 16, 0);
+    let buffer = device.create_buffer_init(
+        &util::BufferInitDescriptor {
+            label: Some("Material Buffer"),
+            contents: &uniform,
+            usage: BufferUsages::UNIFORM,
+        }
+    );
+    let uploaded: Vec<Option<(TextureView, Sampler)>> = textures.iter()
+        .map(|texture| match texture.target(){
+            Some(_) => None,
+            None => Some(texture.upload(device, queue))
+        })
+        .collect();
+    let mut entries = vec![
+        BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }
+    ];
+    for (i, (texture, uploaded)) in textures.iter().zip(uploaded.iter()).enumerate(){
+        let (view, sampler) = match uploaded{
+            Some((view, sampler)) => (view, sampler),
+            None => {
+                let gpu_target = render_targets.get(texture.target()?)?;
+                (&gpu_target.view, &gpu_target.sampler)
+            }
+        };
+        entries.push(BindGroupEntry {
+            binding: 1 + 2 * i as u32,
+            resource: BindingResource::TextureView(view),
+        });
+        entries.push(BindGroupEntry {
+            binding: 2 + 2 * i as u32,
+            resource: BindingResource::Sampler(sampler),
+        });
+    }
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        layout,
+        entries: &entries,
+        label: Some("material_bind_group"),
+    });
+    Some((bind_group, textures.iter().filter_map(|texture| texture.target()).collect()))
+}
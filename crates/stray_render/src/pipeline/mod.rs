@@ -1,2 +1,6 @@
 mod render_pipeline;
-pub use render_pipeline::*;
\ No newline at end of file
+mod material_pipeline;
+mod post_pipeline;
+pub use render_pipeline::*;
+pub use material_pipeline::*;
+pub use post_pipeline::*;
@@ -0,0 +1,82 @@
+use wgpu::*;
+
+pub fn create_post_uniform_layout(device: &Device) -> BindGroupLayout{
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: Some("post_uniform_bind_group_layout"),
+    })
+}
+
+/// Pipeline of one post process effect, it draws fullscreen triangle with fragment shader of effect.
+/// Screen texture is ``@group(0)``, uniform of effect is ``@group(1)`` and extra texture is ``@group(2)``
+pub fn create_post_pipeline(
+    device: &Device,
+    format: TextureFormat,
+    texture_layout: &BindGroupLayout,
+    uniform_layout: &BindGroupLayout,
+    shader: &'static str,
+    with_texture: bool
+) -> RenderPipeline{
+    let vertex_shader = device.create_shader_module(include_wgsl!("shaders/post/post.wgsl"));
+    let fragment_shader = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("Post Effect Shader"),
+        source: ShaderSource::Wgsl(shader.into()),
+    });
+    let bind_group_layouts = if with_texture{
+        vec![texture_layout, uniform_layout, texture_layout]
+    } else {
+        vec![texture_layout, uniform_layout]
+    };
+    let render_pipeline_layout = device.create_pipeline_layout(
+        &PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &bind_group_layouts,
+            push_constant_ranges: &[],
+        }
+    );
+    device.create_render_pipeline(&RenderPipelineDescriptor{
+        label: Some("Post Pipeline"),
+        layout: Some(&render_pipeline_layout),
+        vertex: VertexState{
+            module: &vertex_shader,
+            entry_point: "vs_main",
+            buffers: &[]
+        },
+        fragment: Some(FragmentState{
+            module: &fragment_shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
@@ -2,12 +2,90 @@ use wgpu::*;
 
 use stray_scene::*;
 
-pub fn create_shape_pipeline(device: &Device, config: &SurfaceConfiguration) -> StrayShapeRenderPipeline{
+use crate::RenderSettings;
+
+pub fn create_camera_layout(device: &Device) -> StrayCameraLayout{
+    let layout =
+            device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::VERTEX,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+                label: Some("camera_bind_group_layout"),
+            });
+
+    StrayCameraLayout(layout)
+}
+
+/// Layout of lights uniform and storage buffer with edges of occluders
+pub fn create_lighting_layout(device: &Device) -> BindGroupLayout{
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        entries: &[
+            BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: true },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ],
+        label: Some("lighting_bind_group_layout"),
+    })
+}
+
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+// Depth testing is optional, without it objects are only sorted by layer.
+// Only opaque pipelines write depth, blended pixels would hide objects drawn behind them later
+pub(crate) fn depth_stencil_state(depth_test: bool, depth_write: bool) -> Option<DepthStencilState>{
+    if !depth_test{
+        return None;
+    }
+    Some(DepthStencilState {
+        format: DEPTH_FORMAT,
+        depth_write_enabled: depth_write,
+        depth_compare: CompareFunction::LessEqual,
+        stencil: StencilState::default(),
+        bias: DepthBiasState::default(),
+    })
+}
+
+// Pipelines have to match sample count of passes they are drawn in
+pub(crate) fn multisample_state(render_settings: &RenderSettings) -> MultisampleState{
+    MultisampleState {
+        count: render_settings.anti_aliasing.sample_count(),
+        mask: !0,
+        alpha_to_coverage_enabled: false,
+    }
+}
+
+pub fn create_shape_pipeline(device: &Device, config: &SurfaceConfiguration, camera_layout: &StrayCameraLayout, render_settings: &RenderSettings) -> StrayShapeRenderPipeline{
     let shader = device.create_shader_module(include_wgsl!("shaders/shape.wgsl"));
     let render_pipeline_layout = device.create_pipeline_layout(
         &wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[],
+            bind_group_layouts: &[&camera_layout.0],
             push_constant_ranges: &[],
         }
     );
@@ -37,12 +115,8 @@ pub fn create_shape_pipeline(device: &Device, config: &SurfaceConfiguration) ->
             unclipped_depth: false,
             conservative: false,
         },
-        depth_stencil: None,
-        multisample: MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        },
+        depth_stencil: depth_stencil_state(render_settings.depth_test, false),
+        multisample: multisample_state(render_settings),
         multiview: None,
 
     });
@@ -51,7 +125,53 @@ pub fn create_shape_pipeline(device: &Device, config: &SurfaceConfiguration) ->
 }
 
 
-pub fn create_texture_pipeline(device: &Device, config: &SurfaceConfiguration) -> StrayTextureRenderPipeline{
+/// Pipeline of ``SdfShape``, quads are drawn without culling and edges are smoothed in shader
+pub fn create_sdf_pipeline(device: &Device, config: &SurfaceConfiguration, camera_layout: &StrayCameraLayout, render_settings: &RenderSettings) -> StraySdfRenderPipeline{
+    let shader = device.create_shader_module(include_wgsl!("shaders/sdf.wgsl"));
+    let render_pipeline_layout = device.create_pipeline_layout(
+        &PipelineLayoutDescriptor {
+            label: Some("SDF Pipeline Layout"),
+            bind_group_layouts: &[&camera_layout.0],
+            push_constant_ranges: &[],
+        }
+    );
+    let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor{
+        label: Some("SDF Pipeline"),
+        layout: Some(&render_pipeline_layout),
+        vertex: VertexState{
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[RawSdfVertex::desc()]
+        },
+        fragment: Some(FragmentState{
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: depth_stencil_state(render_settings.depth_test, false),
+        multisample: multisample_state(render_settings),
+        multiview: None,
+
+    });
+
+    StraySdfRenderPipeline(render_pipeline)
+}
+
+
+pub fn create_texture_pipeline(device: &Device, config: &SurfaceConfiguration, camera_layout: &StrayCameraLayout, lighting_layout: &BindGroupLayout, render_settings: &RenderSettings) -> StrayTextureRenderPipeline{
     let layout =
             device.create_bind_group_layout(&BindGroupLayoutDescriptor {
                 entries: &[
@@ -79,7 +199,8 @@ pub fn create_texture_pipeline(device: &Device, config: &SurfaceConfiguration) -
     let render_pipeline_layout = device.create_pipeline_layout(
         &PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&layout],
+            // Texture, camera, lights and normal map
+            bind_group_layouts: &[&layout, &camera_layout.0, lighting_layout, &layout],
             push_constant_ranges: &[],
         }
     );
@@ -109,12 +230,8 @@ pub fn create_texture_pipeline(device: &Device, config: &SurfaceConfiguration) -
             unclipped_depth: false,
             conservative: false,
         },
-        depth_stencil: None,
-        multisample: MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        },
+        depth_stencil: depth_stencil_state(render_settings.depth_test, false),
+        multisample: multisample_state(render_settings),
         multiview: None,
 
     });
@@ -124,3 +241,52 @@ pub fn create_texture_pipeline(device: &Device, config: &SurfaceConfiguration) -
 
 
 
+
+pub fn create_background_pipeline(device: &Device, config: &SurfaceConfiguration, texture_layout: &BindGroupLayout, render_settings: &RenderSettings) -> StrayBackgroundRenderPipeline{
+    let shader = device.create_shader_module(include_wgsl!("shaders/background.wgsl"));
+    let render_pipeline_layout = device.create_pipeline_layout(
+        &PipelineLayoutDescriptor {
+            label: Some("Background Pipeline Layout"),
+            bind_group_layouts: &[texture_layout],
+            push_constant_ranges: &[],
+        }
+    );
+    // Background is always behind, so it doesn't write to depth buffer
+    let depth_stencil = depth_stencil_state(render_settings.depth_test, false).map(|state| DepthStencilState {
+        depth_compare: CompareFunction::Always,
+        ..state
+    });
+    let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor{
+        label: Some("Background Pipeline"),
+        layout: Some(&render_pipeline_layout),
+        vertex: VertexState{
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[RawVertex::desc()]
+        },
+        fragment: Some(FragmentState{
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format: config.format,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil,
+        multisample: multisample_state(render_settings),
+        multiview: None,
+
+    });
+
+    StrayBackgroundRenderPipeline(render_pipeline)
+}
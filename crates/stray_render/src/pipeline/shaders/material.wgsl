@@ -0,0 +1,44 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct Transform {
+    @location(3) matrix_0: vec4<f32>,
+    @location(4) matrix_1: vec4<f32>,
+    @location(5) matrix_2: vec4<f32>,
+    @location(6) matrix_3: vec4<f32>,
+}
+
+// Fragment shaders of materials receive this struct
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) world_position: vec2<f32>,
+}
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+@vertex
+fn vs_main(model: VertexInput, transform: Transform) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        transform.matrix_0,
+        transform.matrix_1,
+        transform.matrix_2,
+        transform.matrix_3
+    );
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.color = model.color;
+    out.world_position = world_position.xy;
+    out.clip_position = camera.view_projection * world_position;
+    return out;
+}
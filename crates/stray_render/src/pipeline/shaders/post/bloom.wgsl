@@ -0,0 +1,40 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_screen: texture_2d<f32>;
+@group(0) @binding(1)
+var s_screen: sampler;
+
+struct Bloom {
+    threshold: f32,
+    intensity: f32,
+    radius: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Bloom;
+
+const SAMPLES: i32 = 24;
+
+// Bright parts of screen are gathered from disk around pixel in a single pass
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t_screen));
+    let color = textureSampleLevel(t_screen, s_screen, in.uv, 0.0);
+    var bloom = vec3<f32>(0.0);
+    var weight_sum = 0.0;
+    for (var i = 0; i < SAMPLES; i++) {
+        // Golden angle spiral covers disk evenly
+        let angle = f32(i) * 2.39996;
+        let distance = sqrt((f32(i) + 0.5) / f32(SAMPLES));
+        let offset = vec2<f32>(cos(angle), sin(angle)) * distance * params.radius / size;
+        let tap = textureSampleLevel(t_screen, s_screen, in.uv + offset, 0.0).rgb;
+        let weight = 1.0 - distance * 0.75;
+        bloom += max(tap - vec3<f32>(params.threshold), vec3<f32>(0.0)) * weight;
+        weight_sum += weight;
+    }
+    return vec4<f32>(color.rgb + bloom / weight_sum * params.intensity, color.a);
+}
@@ -0,0 +1,27 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_screen: texture_2d<f32>;
+@group(0) @binding(1)
+var s_screen: sampler;
+
+struct ChromaticAberration {
+    offset: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: ChromaticAberration;
+
+// Red and blue channels are shifted away from center, offset is in pixels at edges of screen
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t_screen));
+    let direction = (in.uv - vec2<f32>(0.5)) * 2.0 * params.offset / size;
+    let color = textureSample(t_screen, s_screen, in.uv);
+    let red = textureSample(t_screen, s_screen, in.uv + direction).r;
+    let blue = textureSample(t_screen, s_screen, in.uv - direction).b;
+    return vec4<f32>(red, color.g, blue, color.a);
+}
@@ -0,0 +1,52 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_screen: texture_2d<f32>;
+@group(0) @binding(1)
+var s_screen: sampler;
+
+struct ColorGrading {
+    intensity: f32,
+    lut_size: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: ColorGrading;
+
+// LUT is horizontal strip of lut_size slices, blue selects slice, red is x and green is y inside slice
+@group(2) @binding(0)
+var t_lut: texture_2d<f32>;
+@group(2) @binding(1)
+var s_lut: sampler;
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - vec3<f32>(0.055);
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn lut_uv(color: vec3<f32>, slice: f32) -> vec2<f32> {
+    let size = params.lut_size;
+    let x = (slice * size + color.r * (size - 1.0) + 0.5) / (size * size);
+    let y = (color.g * (size - 1.0) + 0.5) / size;
+    return vec2<f32>(x, y);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.uv);
+    // LUTs are made in sRGB space, sampled LUT is already converted back to linear space
+    let srgb = clamp(linear_to_srgb(color.rgb), vec3<f32>(0.0), vec3<f32>(1.0));
+    let blue = srgb.b * (params.lut_size - 1.0);
+    let slice = floor(blue);
+    let next_slice = min(slice + 1.0, params.lut_size - 1.0);
+    let graded = mix(
+        textureSample(t_lut, s_lut, lut_uv(srgb, slice)).rgb,
+        textureSample(t_lut, s_lut, lut_uv(srgb, next_slice)).rgb,
+        blue - slice
+    );
+    return vec4<f32>(mix(color.rgb, graded, params.intensity), color.a);
+}
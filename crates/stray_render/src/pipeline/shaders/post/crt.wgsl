@@ -0,0 +1,32 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_screen: texture_2d<f32>;
+@group(0) @binding(1)
+var s_screen: sampler;
+
+struct Crt {
+    curvature: f32,
+    scanline_intensity: f32,
+    scanline_size: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Crt;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t_screen));
+    // Barrel distortion of curved screen
+    var centered = in.uv * 2.0 - vec2<f32>(1.0);
+    centered = centered + centered * centered.yx * centered.yx * params.curvature;
+    let uv = centered * 0.5 + vec2<f32>(0.5);
+    let color = textureSample(t_screen, s_screen, uv);
+    let scanline = sin(uv.y * size.y * 3.14159265 / params.scanline_size) * 0.5 + 0.5;
+    let shade = 1.0 - params.scanline_intensity * (1.0 - scanline);
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    return select(vec4<f32>(0.0, 0.0, 0.0, 1.0), vec4<f32>(color.rgb * shade, color.a), inside);
+}
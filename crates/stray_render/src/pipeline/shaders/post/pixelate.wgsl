@@ -0,0 +1,24 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_screen: texture_2d<f32>;
+@group(0) @binding(1)
+var s_screen: sampler;
+
+struct Pixelate {
+    pixel_size: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Pixelate;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t_screen));
+    let cell = max(params.pixel_size, 1.0) / size;
+    let uv = (floor(in.uv / cell) + vec2<f32>(0.5)) * cell;
+    return textureSample(t_screen, s_screen, uv);
+}
@@ -0,0 +1,28 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var t_screen: texture_2d<f32>;
+@group(0) @binding(1)
+var s_screen: sampler;
+
+struct Vignette {
+    color: vec4<f32>,
+    intensity: f32,
+    radius: f32,
+    smoothness: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> params: Vignette;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(t_screen, s_screen, in.uv);
+    // 0 in center of screen and 1 in corners
+    let distance = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let amount = smoothstep(params.radius, params.radius + params.smoothness, distance) * params.intensity * params.color.a;
+    return vec4<f32>(mix(color.rgb, params.color.rgb, clamp(amount, 0.0, 1.0)), color.a);
+}
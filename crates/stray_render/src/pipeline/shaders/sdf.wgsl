@@ -0,0 +1,99 @@
+struct VertexInput {
+    // Position relative to center of shape, in pixels
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) outline_color: vec4<f32>,
+    @location(3) shadow_color: vec4<f32>,
+    // Kind and its parameters, see SdfShapeKind
+    @location(4) shape: vec4<f32>,
+    // Outline width, shadow blur and shadow offset
+    @location(5) style: vec4<f32>,
+};
+
+struct Transform {
+    @location(6) matrix_0: vec4<f32>,
+    @location(7) matrix_1: vec4<f32>,
+    @location(8) matrix_2: vec4<f32>,
+    @location(9) matrix_3: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) local: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) outline_color: vec4<f32>,
+    @location(3) shadow_color: vec4<f32>,
+    @location(4) shape: vec4<f32>,
+    @location(5) style: vec4<f32>,
+};
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
+@vertex
+fn vs_main(model: VertexInput, transform: Transform) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        transform.matrix_0,
+        transform.matrix_1,
+        transform.matrix_2,
+        transform.matrix_3
+    );
+    var out: VertexOutput;
+    out.local = model.position.xy;
+    out.color = model.color;
+    out.outline_color = model.outline_color;
+    out.shadow_color = model.shadow_color;
+    out.shape = model.shape;
+    out.style = model.style;
+    out.clip_position = camera.view_projection * model_matrix * vec4<f32>(model.position, 1.0);
+    return out;
+}
+
+// Distance to edge of shape, negative inside
+fn shape_distance(p: vec2<f32>, shape: vec4<f32>) -> f32 {
+    switch i32(shape.x) {
+        // Rounded box with half size and corner radius
+        case 1: {
+            let q = abs(p) - shape.yz + shape.w;
+            return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - shape.w;
+        }
+        // Horizontal capsule with half length between centers of caps and radius
+        case 2: {
+            let d = vec2<f32>(p.x - clamp(p.x, -shape.y, shape.y), p.y);
+            return length(d) - shape.w;
+        }
+        // Ring with radius and half of thickness
+        case 3: {
+            return abs(length(p) - shape.y) - shape.w;
+        }
+        // Circle with radius
+        default: {
+            return length(p) - shape.y;
+        }
+    }
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = shape_distance(in.local, in.shape);
+    // Half of pixel in units of shape, so edge stays sharp at any zoom
+    let aa = max(fwidth(distance), 0.0001) * 0.5;
+    let outline = in.style.x;
+    // Outline is drawn outside of edge
+    let color = select(in.color, mix(in.color, in.outline_color, smoothstep(-aa, aa, distance)), outline > 0.0);
+    let shape_alpha = color.a * (1.0 - smoothstep(-aa, aa, distance - outline));
+    let blur = max(in.style.y, aa);
+    let shadow_distance = shape_distance(in.local - in.style.zw, in.shape) - outline;
+    let shadow_alpha = in.shadow_color.a * (1.0 - smoothstep(-blur, blur, shadow_distance));
+    // Shape is drawn over its shadow
+    let alpha = shape_alpha + shadow_alpha * (1.0 - shape_alpha);
+    if alpha <= 0.0 {
+        discard;
+    }
+    let rgb = (color.rgb * shape_alpha + in.shadow_color.rgb * shadow_alpha * (1.0 - shape_alpha)) / alpha;
+    return vec4<f32>(rgb, alpha);
+}
@@ -15,6 +15,13 @@ struct VertexOutput {
     @location(0) color: vec4<f32>,
 };
 
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> camera: Camera;
+
 @vertex
 fn vs_main(model: VertexInput, transform: Transform) -> VertexOutput {
     let model_matrix = mat4x4<f32>(
@@ -25,7 +32,7 @@ fn vs_main(model: VertexInput, transform: Transform) -> VertexOutput {
     );
     var out: VertexOutput;
     out.color = model.color;
-    out.clip_position = model_matrix * vec4<f32>(model.position, 1.0) ;
+    out.clip_position = camera.view_projection * model_matrix * vec4<f32>(model.position, 1.0) ;
     return out;
 }
 
@@ -1,7 +1,7 @@
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
-    @location(2) color: vec3<f32>,
+    @location(2) color: vec4<f32>,
 }
 
 struct Transform {
@@ -14,8 +14,19 @@ struct Transform {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) world_position: vec2<f32>,
+    // X axis of object in world, normal maps are rotated with it
+    @location(3) tangent: vec2<f32>,
 }
 
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
 @vertex
 fn vs_main(model: VertexInput, transform: Transform) -> VertexOutput {
     let model_matrix = mat4x4<f32>(
@@ -24,9 +35,13 @@ fn vs_main(model: VertexInput, transform: Transform) -> VertexOutput {
         transform.matrix_2,
         transform.matrix_3
     );
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
-    out.clip_position = model_matrix * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
+    out.world_position = world_position.xy;
+    out.tangent = normalize(transform.matrix_0.xy);
+    out.clip_position = camera.view_projection * world_position;
     return out;
 }
 
@@ -35,7 +50,99 @@ var t_diffuse: texture_2d<f32>;
 @group(0)@binding(1)
 var s_diffuse: sampler;
 
+struct Light {
+    // x, y, height, radius
+    position: vec4<f32>,
+    // Color multiplied by intensity, size of light source
+    color: vec4<f32>,
+    // Direction of cone, cosines of inner and outer angle
+    direction: vec4<f32>,
+    // Casts shadows
+    flags: vec4<f32>,
+}
+
+struct Lights {
+    ambient: vec4<f32>,
+    count: u32,
+    edge_count: u32,
+    lights: array<Light, 64>,
+}
+
+@group(2) @binding(0)
+var<uniform> lights: Lights;
+// Edges of occluders as x1, y1, x2, y2
+@group(2) @binding(1)
+var<storage, read> edges: array<vec4<f32>>;
+
+@group(3) @binding(0)
+var t_normal: texture_2d<f32>;
+@group(3)@binding(1)
+var s_normal: sampler;
+
+const SHADOW_SAMPLES: i32 = 5;
+
+// Checks if segment from point to light crosses edge
+fn blocked(point: vec2<f32>, light: vec2<f32>, edge: vec4<f32>) -> bool {
+    let r = light - point;
+    let s = edge.zw - edge.xy;
+    let denominator = r.x * s.y - r.y * s.x;
+    if abs(denominator) < 0.0001 {
+        return false;
+    }
+    let offset = edge.xy - point;
+    let t = (offset.x * s.y - offset.y * s.x) / denominator;
+    let u = (offset.x * r.y - offset.y * r.x) / denominator;
+    return t > 0.001 && t < 1.0 && u >= 0.0 && u <= 1.0;
+}
+
+// Part of light source which is visible from point, light source is sampled across its size for soft shadows
+fn visibility(point: vec2<f32>, light: Light) -> f32 {
+    let direction = normalize(light.position.xy - point);
+    let side = vec2<f32>(-direction.y, direction.x);
+    var visible = 0.0;
+    for (var i = 0; i < SHADOW_SAMPLES; i++) {
+        let offset = (f32(i) / f32(SHADOW_SAMPLES - 1) - 0.5) * 2.0 * light.color.w;
+        let source = light.position.xy + side * offset;
+        var hit = false;
+        for (var e = 0u; e < lights.edge_count; e++) {
+            if blocked(point, source, edges[e]) {
+                hit = true;
+                break;
+            }
+        }
+        if !hit {
+            visible += 1.0;
+        }
+    }
+    return visible / f32(SHADOW_SAMPLES);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
-}
\ No newline at end of file
+    // Vertex color tints texture, it's white by default
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color;
+    // Alpha of flat normal map is 0, so objects without normal map are lit evenly
+    let normal_sample = textureSample(t_normal, s_normal, in.tex_coords);
+    let bitangent = vec2<f32>(-in.tangent.y, in.tangent.x);
+    let local_normal = normal_sample.xyz * 2.0 - vec3<f32>(1.0);
+    let normal = normalize(vec3<f32>(in.tangent * local_normal.x + bitangent * local_normal.y, local_normal.z));
+
+    var light_color = lights.ambient.rgb;
+    for (var i = 0u; i < min(lights.count, 64u); i++) {
+        let light = lights.lights[i];
+        let to_light = light.position.xy - in.world_position;
+        let distance = length(to_light);
+        if distance >= light.position.w {
+            continue;
+        }
+        let falloff = 1.0 - distance / light.position.w;
+        let cone = smoothstep(light.direction.w, light.direction.z, dot(-to_light / max(distance, 0.0001), light.direction.xy));
+        let diffuse = max(dot(normal, normalize(vec3<f32>(to_light, light.position.z))), 0.0);
+        var shadow = 1.0;
+        if light.flags.x > 0.5 {
+            shadow = visibility(in.world_position, light);
+        }
+        light_color += light.color.rgb * falloff * falloff * cone * mix(1.0, diffuse, normal_sample.a) * shadow;
+    }
+    return vec4<f32>(color.rgb * light_color, color.a);
+}
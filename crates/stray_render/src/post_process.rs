@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::StrayColor;
+use stray_texture::{StrayTexture, RenderTargets};
+
+use crate::{create_post_pipeline, create_post_uniform_layout};
+
+const COPY_SHADER: &str = include_str!("pipeline/shaders/post/post.wgsl");
+
+/// ## Overview
+/// Screen-wide effect which is applied to window image after all objects are drawn.
+///
+/// Shader of ``Custom`` effect has to contain ``fs_main`` entry point and gets:
+/// - ``@group(0)`` screen texture at ``@binding(0)`` and its sampler at ``@binding(1)``
+/// - ``@group(1) @binding(0)`` uniform with bytes of effect, padded to 16 bytes
+///
+/// ```wgsl
+/// struct VertexOutput {
+///     @builtin(position) clip_position: vec4<f32>,
+///     @location(0) uv: vec2<f32>,
+/// }
+/// ```
+///
+/// ## Example
+///
+/// ```ignore
+/// let grayscale = PostEffect::Custom{ shader: include_str!("grayscale.wgsl"), uniform: vec![] };
+/// Stray::new()
+///     .with_post_effect(PostEffect::Bloom{ threshold: 0.8, intensity: 1.0, radius: 12.0 })
+///     .with_post_effect(grayscale)
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub enum PostEffect{
+    // Bright parts of image glow, radius is in pixels
+    Bloom{ threshold: f32, intensity: f32, radius: f32 },
+    // Corners are darkened, radius and smoothness are relative to distance from center to corner
+    Vignette{ color: StrayColor, intensity: f32, radius: f32, smoothness: f32 },
+    // Color channels are split at edges of screen, offset is in pixels
+    ChromaticAberration{ offset: f32 },
+    // Colors are remapped by LUT strip, e.g. 256x16 image with 16 slices of blue
+    ColorGrading{ lut: StrayTexture, intensity: f32 },
+    // Curved screen with scanlines, scanline size is in pixels
+    Crt{ curvature: f32, scanline_intensity: f32, scanline_size: f32 },
+    Pixelate{ pixel_size: f32 },
+    Custom{ shader: &'static str, uniform: Vec<u8> },
+}
+
+impl PostEffect{
+    /// WGSL source of fragment shader, pipelines are cached by it
+    pub fn shader(&self) -> &'static str{
+        match self{
+            Self::Bloom { .. } => include_str!("pipeline/shaders/post/bloom.wgsl"),
+            Self::Vignette { .. } => include_str!("pipeline/shaders/post/vignette.wgsl"),
+            Self::ChromaticAberration { .. } => include_str!("pipeline/shaders/post/chromatic_aberration.wgsl"),
+            Self::ColorGrading { .. } => include_str!("pipeline/shaders/post/color_grading.wgsl"),
+            Self::Crt { .. } => include_str!("pipeline/shaders/post/crt.wgsl"),
+            Self::Pixelate { .. } => include_str!("pipeline/shaders/post/pixelate.wgsl"),
+            Self::Custom { shader, .. } => shader,
+        }
+    }
+
+    /// Bytes of effect uniform, padded to 16 bytes
+    pub fn uniform(&self, targets: &RenderTargets) -> Vec<u8>{
+        let mut bytes: Vec<u8> = match self{
+            Self::Bloom { threshold, intensity, radius } => f32_bytes(&[*threshold, *intensity, *radius]),
+            Self::Vignette { color, intensity, radius, smoothness } => {
+                let [r, g, b, a] = color.to_linear();
+                f32_bytes(&[r, g, b, a, *intensity, *radius, *smoothness])
+            }
+            Self::ChromaticAberration { offset } => f32_bytes(&[*offset]),
+            Self::ColorGrading { lut, intensity } => f32_bytes(&[*intensity, lut.size(targets).1 as f32]),
+            Self::Crt { curvature, scanline_intensity, scanline_size } => f32_bytes(&[*curvature, *scanline_intensity, *scanline_size]),
+            Self::Pixelate { pixel_size } => f32_bytes(&[*pixel_size]),
+            Self::Custom { uniform, .. } => uniform.clone(),
+        };
+        bytes.resize(bytes.len().div_ceil(16).max(1) * 16, 0);
+        bytes
+    }
+
+    /// Extra texture bound at ``@group(2)``
+    pub fn texture(&self) -> Option<&StrayTexture>{
+        match self{
+            Self::ColorGrading { lut, .. } => Some(lut),
+            _ => None
+        }
+    }
+}
+
+fn f32_bytes(values: &[f32]) -> Vec<u8>{
+    values.iter().flat_map(|value| value.to_ne_bytes()).collect()
+}
+
+/// ## Overview
+/// Ordered stack of post process effects, first effect is applied first.
+/// It's a resource, so effects can be changed while app is running
+///
+/// ## Example
+///
+/// ```ignore
+/// #[system]
+/// fn toggle_crt(#[resource] post_process: &mut PostProcess, #[resource] input: &InputEvent){
+///     if input.is_pressed(Key::C){
+///         post_process.0.clear();
+///     }
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct PostProcess(pub Vec<PostEffect>);
+
+impl PostProcess{
+    pub fn new() -> Self{
+        Self(vec![])
+    }
+
+    pub fn with(mut self, effect: PostEffect) -> Self{
+        self.0.push(effect);
+        self
+    }
+}
+
+/// Pipelines of post process effects stored by shader source
+pub struct StrayPostPipelines{
+    pub pipelines: HashMap<&'static str, RenderPipeline>,
+    pub uniform_layout: BindGroupLayout,
+}
+
+impl StrayPostPipelines{
+    pub fn new(device: &Device) -> Self{
+        Self { pipelines: HashMap::new(), uniform_layout: create_post_uniform_layout(device) }
+    }
+
+    /// Builds pipeline of shader when it's used for the first time
+    pub fn prepare(&mut self, device: &Device, format: TextureFormat, texture_layout: &BindGroupLayout, shader: &'static str, with_texture: bool){
+        if !self.pipelines.contains_key(shader){
+            let pipeline = create_post_pipeline(device, format, texture_layout, &self.uniform_layout, shader, with_texture);
+            self.pipelines.insert(shader, pipeline);
+        }
+    }
+}
+
+/// Two textures of window size, effects read one of them and write to the other one
+#[derive(Default)]
+pub struct StrayPostTextures(pub Vec<TextureView>, pub (u32,u32));
+
+impl StrayPostTextures{
+    /// Creates textures or recreates them when size has changed
+    pub fn prepare(&mut self, device: &Device, format: TextureFormat, size: (u32,u32)){
+        if !self.0.is_empty() && self.1 == size{
+            return;
+        }
+        self.0 = (0..2).map(|_| {
+            device.create_texture(&TextureDescriptor {
+                label: Some("post_process_texture"),
+                size: Extent3d {
+                    width: size.0.max(1),
+                    height: size.1.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            }).create_view(&TextureViewDescriptor::default())
+        }).collect();
+        self.1 = size;
+    }
+
+    /// Texture which scene is drawn into
+    pub fn scene_view(&self) -> &TextureView{
+        &self.0[0]
+    }
+}
+
+/// Draws effects of stack one after another, the last one draws into ``output``.
+/// Scene has to be drawn into ``StrayPostTextures::scene_view`` before
+#[allow(clippy::too_many_arguments)]
+pub fn apply_post_process(
+    encoder: &mut CommandEncoder,
+    device: &Device,
+    queue: &Queue,
+    post_process: &PostProcess,
+    pipelines: &mut StrayPostPipelines,
+    textures: &StrayPostTextures,
+    texture_layout: &BindGroupLayout,
+    render_targets: &RenderTargets,
+    format: TextureFormat,
+    output: &TextureView,
+){
+    // Effect whose texture isn't ready, e.g. unallocated render target, is replaced by copy
+    let effects: Vec<(&'static str, Vec<u8>, Option<BindGroup>)> = post_process.0.iter()
+        .map(|effect| match effect.texture(){
+            Some(texture) => match texture.bind_group(device, queue, texture_layout, render_targets){
+                Some(bind_group) => (effect.shader(), effect.uniform(render_targets), Some(bind_group)),
+                None => (COPY_SHADER, vec![0; 16], None)
+            },
+            None => (effect.shader(), effect.uniform(render_targets), None)
+        })
+        .collect();
+    for (shader, _, texture) in effects.iter(){
+        pipelines.prepare(device, format, texture_layout, shader, texture.is_some());
+    }
+    let sampler = device.create_sampler(&SamplerDescriptor {
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        mipmap_filter: FilterMode::Nearest,
+        ..Default::default()
+    });
+    for (i, (shader, uniform, texture)) in effects.iter().enumerate(){
+        let source = &textures.0[i % 2];
+        let destination = if i + 1 == effects.len() { output } else { &textures.0[(i + 1) % 2] };
+        let source_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            layout: texture_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(source),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&sampler),
+                }
+            ],
+            label: Some("post_source_bind_group"),
+        });
+        let uniform_buffer = device.create_buffer_init(
+            &util::BufferInitDescriptor {
+                label: Some("Post Effect Buffer"),
+                contents: uniform,
+                usage: BufferUsages::UNIFORM,
+            }
+        );
+        let uniform_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            layout: &pipelines.uniform_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }
+            ],
+            label: Some("post_uniform_bind_group"),
+        });
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Post Process Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: destination,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&pipelines.pipelines[*shader]);
+        render_pass.set_bind_group(0, &source_bind_group, &[]);
+        render_pass.set_bind_group(1, &uniform_bind_group, &[]);
+        if let Some(texture) = texture{
+            render_pass.set_bind_group(2, texture, &[]);
+        }
+        render_pass.draw(0..3, 0..1);
+    }
+}
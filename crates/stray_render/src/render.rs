@@ -1,5 +1,7 @@
-//use smaa::{SmaaTarget, SmaaFrame};
-use wgpu::*;
+use smaa::{SmaaTarget, SmaaMode};
+use std::collections::HashMap;
+
+use wgpu::{*, util::DeviceExt};
 
 use legion::{
     Resources, 
@@ -10,100 +12,546 @@ use winit::{
 };
 
 use stray_scene::*;
+use stray_texture::{RenderTargets, StrayTexture};
+use stray_material::StrayColor;
 
-use crate::{create_shape_pipeline, create_texture_pipeline};
+use crate::{
+    create_shape_pipeline, 
+    create_sdf_pipeline, 
+    create_texture_pipeline, 
+    create_background_pipeline, 
+    create_camera_layout, 
+    create_lighting_layout,
+    apply_post_process,
+    PostProcess,
+    StrayPostPipelines,
+    StrayPostTextures,
+    DEPTH_FORMAT
+};
 
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub enum StrayBackend{
     Vulkan,
     Metal,
     DX12,
+    #[default]
     All
 }
 
-impl Default for StrayBackend{
-    fn default() -> Self {
-        Self::All
+/// Order in which objects of the same layer are drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LayerSort{
+    // Objects of the same layer are drawn in order of creating
+    #[default]
+    Layer,
+    // Objects with higher y are drawn first, so lower ones cover them (for top-down games)
+    YSort,
+}
+
+/// ## Overview
+/// Smoothing of jagged edges.
+/// MSAA renders every pass with several samples per pixel and resolves them,
+/// SMAA is post-process which smooths edges of final window image.
+/// Unsupported sample counts are lowered when render is initialized
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AntiAliasing{
+    #[default]
+    None,
+    Msaa2x,
+    Msaa4x,
+    Msaa8x,
+    Smaa,
+}
+
+impl AntiAliasing{
+    /// Samples per pixel of render passes
+    pub fn sample_count(&self) -> u32{
+        match self{
+            Self::Msaa2x => 2,
+            Self::Msaa4x => 4,
+            Self::Msaa8x => 8,
+            _ => 1
+        }
+    }
+
+    fn from_sample_count(sample_count: u32) -> Self{
+        match sample_count{
+            2 => Self::Msaa2x,
+            4 => Self::Msaa4x,
+            8 => Self::Msaa8x,
+            _ => Self::None
+        }
+    }
+
+    /// Returns highest supported anti-aliasing which isn't better than this one.
+    /// Sample count has to be supported by both color and depth format,
+    /// without ``TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`` on device only 1x and 4x are allowed
+    pub fn validate(self, adapter: &Adapter, device: &Device, format: TextureFormat) -> Self{
+        let mut sample_count = self.sample_count();
+        if sample_count == 1{
+            return self;
+        }
+        if !device.features().contains(Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES){
+            return if sample_count >= 4 { Self::Msaa4x } else { Self::None };
+        }
+        let color_flags = adapter.get_texture_format_features(format).flags;
+        let depth_flags = adapter.get_texture_format_features(DEPTH_FORMAT).flags;
+        while sample_count > 1 && !(color_flags.sample_count_supported(sample_count) && depth_flags.sample_count_supported(sample_count)){
+            sample_count /= 2;
+        }
+        Self::from_sample_count(sample_count)
+    }
+}
+
+/// Render configuration, created from stray settings
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderSettings{
+    pub layer_sort: LayerSort,
+    // Enables depth buffer, which is useful for opaque geometry. Blended pipelines only test depth
+    pub depth_test: bool,
+    pub anti_aliasing: AntiAliasing,
+    // Anti-aliasing asked for in settings, it's better than anti_aliasing when adapter doesn't support it
+    pub requested_anti_aliasing: AntiAliasing,
+}
+
+/// Depth textures of window and render targets, stored by id of target
+#[derive(Default)]
+pub struct StrayDepthBuffers(pub HashMap<Option<u32>, (TextureView, (u32,u32))>);
+
+impl StrayDepthBuffers{
+    /// Frees buffers of render targets which aren't in ``used``, buffer of window is kept
+    pub fn retain(&mut self, used: &[u32]){
+        self.0.retain(|target, _| target.map(|id| used.contains(&id)).unwrap_or(true));
+    }
+
+    /// Creates depth texture for target or recreates it when size has changed
+    pub fn prepare(&mut self, device: &Device, target: Option<u32>, size: (u32,u32), sample_count: u32){
+        match self.0.get(&target){
+            Some((_, depth_size)) if *depth_size == size => {}
+            _ => {
+                let texture = device.create_texture(&TextureDescriptor {
+                    label: Some("depth_texture"),
+                    size: Extent3d {
+                        width: size.0.max(1),
+                        height: size.1.max(1),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: TextureDimension::D2,
+                    format: DEPTH_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&TextureViewDescriptor::default());
+                self.0.insert(target, (view, size));
+            }
+        }
+    }
+
+    pub fn attachment(&self, target: Option<u32>) -> Option<RenderPassDepthStencilAttachment<'_>>{
+        self.0.get(&target).map(|(view, _)| RenderPassDepthStencilAttachment {
+            view,
+            depth_ops: Some(Operations {
+                load: LoadOp::Clear(1.0),
+                store: true,
+            }),
+            stencil_ops: None,
+        })
+    }
+}
+
+/// Multisampled color textures of window and render targets, stored by id of target.
+/// Passes draw into them and resolve result into window or target texture
+#[derive(Default)]
+pub struct StrayMsaaBuffers(pub HashMap<Option<u32>, (TextureView, (u32,u32))>);
+
+impl StrayMsaaBuffers{
+    /// Frees buffers of render targets which aren't in ``used``, buffer of window is kept
+    pub fn retain(&mut self, used: &[u32]){
+        self.0.retain(|target, _| target.map(|id| used.contains(&id)).unwrap_or(true));
+    }
+
+    /// Creates multisampled texture for target or recreates it when size has changed
+    pub fn prepare(&mut self, device: &Device, format: TextureFormat, target: Option<u32>, size: (u32,u32), sample_count: u32){
+        match self.0.get(&target){
+            Some((_, msaa_size)) if *msaa_size == size => {}
+            _ => {
+                let texture = device.create_texture(&TextureDescriptor {
+                    label: Some("msaa_texture"),
+                    size: Extent3d {
+                        width: size.0.max(1),
+                        height: size.1.max(1),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: TextureDimension::D2,
+                    format,
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&TextureViewDescriptor::default());
+                self.0.insert(target, (view, size));
+            }
+        }
+    }
+
+    /// Returns view which pass draws into and its resolve target
+    pub fn color_attachment<'a>(&'a self, target: Option<u32>, view: &'a TextureView) -> (&'a TextureView, Option<&'a TextureView>){
+        match self.0.get(&target){
+            Some((msaa_view, _)) => (msaa_view, Some(view)),
+            None => (view, None)
+        }
+    }
+}
+
+/// Sorts objects by layer and then by secondary key of ``LayerSort``.
+/// Sort is stable, so objects with equal keys keep order of creating
+pub fn sort_render_objects(render_query: &mut RenderQuery, layer_sort: LayerSort){
+    match layer_sort{
+        LayerSort::Layer => render_query.0.sort_by_key(|object| object.layer),
+        LayerSort::YSort => render_query.0.sort_by(|a, b| {
+            a.layer.cmp(&b.layer).then(b.y.total_cmp(&a.y))
+        }),
     }
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_redraw(
     surface: &Surface, 
     device: &Device,
     shape_pipeline: &StrayShapeRenderPipeline,
+    sdf_pipeline: &StraySdfRenderPipeline,
     texture_pipeline: &StrayTextureRenderPipeline,
+    background_pipeline: &StrayBackgroundRenderPipeline,
+    material_pipelines: &MaterialPipelines,
+    camera_layout: &StrayCameraLayout,
     queue: &Queue,
+    background: &Background,
     render_query: &mut RenderQuery,
-   // smaa_target: &mut SmaaTarget
+    camera_query: &mut CameraQuery,
+    render_targets: &RenderTargets,
+    render_settings: &RenderSettings,
+    depth_buffers: &mut StrayDepthBuffers,
+    msaa_buffers: &mut StrayMsaaBuffers,
+    smaa_target: &mut SmaaTarget,
+    post_process: &PostProcess,
+    post_pipelines: &mut StrayPostPipelines,
+    post_textures: &mut StrayPostTextures,
+    lighting: &StrayLighting,
+    ambient_light: &AmbientLight,
+    light_query: &mut LightQuery,
 ) {
     let output = surface.get_current_texture().unwrap();
     let view = output.texture.create_view(&TextureViewDescriptor::default());
-    //let smaa_frame = smaa_target.start_frame(&device, &queue, &view);
+    sort_render_objects(render_query, render_settings.layer_sort);
+    let sample_count = render_settings.anti_aliasing.sample_count();
+    let window_texture_size = (output.texture.width(), output.texture.height());
+    if render_settings.depth_test{
+        depth_buffers.prepare(device, None, window_texture_size, sample_count);
+        for camera in camera_query.0.iter(){
+            if let Some(target) = camera.target.and_then(|id| render_targets.get(id)){
+                depth_buffers.prepare(device, camera.target, target.size, sample_count);
+            }
+        }
+    }
+    if sample_count > 1{
+        msaa_buffers.prepare(device, output.texture.format(), None, window_texture_size, sample_count);
+        for camera in camera_query.0.iter(){
+            if let Some(target) = camera.target.and_then(|id| render_targets.get(id)){
+                msaa_buffers.prepare(device, target.texture.format(), camera.target, target.size, sample_count);
+            }
+        }
+    }
+    let post_processing = !post_process.0.is_empty();
+    if post_processing{
+        post_textures.prepare(device, output.texture.format(), window_texture_size);
+    }
+    // With SMAA disabled, frame is the same view as window
+    let smaa_frame = smaa_target.start_frame(device, queue, &view);
+    // With post process, scene is drawn into texture which effects read
+    let scene_view = if post_processing { post_textures.scene_view() } else { &*smaa_frame };
     let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
         label: Some("Render Encoder"),
     });
+    camera_query.0.sort_by_key(|camera| camera.order);
+    let lights_bind_group = create_lights_bind_group(device, lighting, ambient_light, light_query);
+
+    // Cameras with render target are drawn first so window can sample them
+    for camera in camera_query.0.iter(){
+        let target = match camera.target.and_then(|id| render_targets.get(id)){
+            Some(target) => target,
+            None => continue
+        };
+        let default_background;
+        let target_background = match &camera.background{
+            Some(background) => background,
+            None => {
+                default_background = background.to_render_background(device, queue, &texture_pipeline.1, render_targets, [target.size.0, target.size.1]);
+                &default_background
+            }
+        };
+        let (target_view, resolve_target) = msaa_buffers.color_attachment(camera.target, &target.view);
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Render Target Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target_view,
+                resolve_target,
+                ops: Operations {
+                    load: background_load_op(target_background),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: depth_buffers.attachment(camera.target),
+        });
+        draw_background(&mut render_pass, target_background, background_pipeline);
+        draw_render_objects(&mut render_pass, render_query, shape_pipeline, sdf_pipeline, texture_pipeline, material_pipelines, &camera.bind_group, &lights_bind_group, &lighting.flat_normal, camera.target);
+    }
+
+    // Without camera, window shows world around (0,0)
+    let window_size = [output.texture.width(), output.texture.height()];
+    let default_camera = create_camera_bind_group(
+        device, 
+        camera_layout, 
+        Camera2D::new().view_projection(&Transform2D::ZERO, window_size).to_cols_array_2d()
+    );
+    let window_camera = camera_query.0.iter().find(|camera| camera.target.is_none());
+    let window_camera_bind_group = window_camera
+        .map(|camera| &camera.bind_group)
+        .unwrap_or(&default_camera);
+    let default_background;
+    let window_background = match window_camera.and_then(|camera| camera.background.as_ref()){
+        Some(background) => background,
+        None => {
+            default_background = background.to_render_background(device, queue, &texture_pipeline.1, render_targets, window_size);
+            &default_background
+        }
+    };
     {
+        let (window_view, resolve_target) = msaa_buffers.color_attachment(None, scene_view);
         let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
             label: Some("Render Pass"),
             color_attachments: &[Some(RenderPassColorAttachment {
-                //view: &*smaa_frame,
-                view: &view,
-                resolve_target: None,
+                view: window_view,
+                resolve_target,
                 ops: Operations {
-                    load: LoadOp::Clear(Color {
-                        r: 0.06,
-                        g: 0.06,
-                        b: 0.06,
-                        a: 1.0,
-                    }),
+                    load: background_load_op(window_background),
                     store: true,
                 },
             })],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: depth_buffers.attachment(None),
         });
-        for entry in render_query.0.iter(){
-            if &entry.type_id == &0{
-                let vertex = entry.get_vertex();
-                let index = entry.get_index();
-                let v_buff = vertex.0.as_ref().unwrap();
-                let i_buff = index.0.as_ref().unwrap();
-
-                render_pass.set_pipeline(&shape_pipeline.0);
-                render_pass.set_vertex_buffer(0, v_buff.slice(..));
-                if index.1 > 0{
+        draw_background(&mut render_pass, window_background, background_pipeline);
+        draw_render_objects(&mut render_pass, render_query, shape_pipeline, sdf_pipeline, texture_pipeline, material_pipelines, window_camera_bind_group, &lights_bind_group, &lighting.flat_normal, None);
+    }
+    if post_processing{
+        apply_post_process(
+            &mut encoder, 
+            device, 
+            queue, 
+            post_process, 
+            post_pipelines, 
+            post_textures, 
+            &texture_pipeline.1, 
+            render_targets, 
+            output.texture.format(), 
+            &smaa_frame
+        );
+    }
+    render_query.0.clear();
+    camera_query.0.clear();
+    light_query.0.clear();
+    light_query.1.clear();
+    queue.submit(std::iter::once(encoder.finish()));
+    smaa_frame.resolve();
+    output.present();
+}    
+
+/// Converts color to linear clear value of render pass
+pub fn clear_color(color: StrayColor) -> Color{
+    let [r, g, b, a] = color.to_linear();
+    Color { r: r as f64, g: g as f64, b: b as f64, a: a as f64 }
+}
+
+fn background_load_op(background: &RenderBackground) -> LoadOp<Color>{
+    match background.clear{
+        Some(color) => LoadOp::Clear(clear_color(color)),
+        None => LoadOp::Load
+    }
+}
+
+fn draw_background<'a>(
+    render_pass: &mut RenderPass<'a>,
+    background: &'a RenderBackground,
+    background_pipeline: &'a StrayBackgroundRenderPipeline,
+){
+    if let (Some(StrayVertexBuffer(Some(v_buff), len)), Some(bind_group)) = (&background.vertex, &background.bind_group){
+        render_pass.set_pipeline(&background_pipeline.0);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.set_vertex_buffer(0, v_buff.slice(..));
+        render_pass.draw(0..*len, 0..1);
+    }
+}
+
+// Objects which sample render target of current pass are skipped,
+// texture can't be read and written in the same pass
+#[allow(clippy::too_many_arguments)]
+fn draw_render_objects<'a>(
+    render_pass: &mut RenderPass<'a>,
+    render_query: &'a RenderQuery,
+    shape_pipeline: &'a StrayShapeRenderPipeline,
+    sdf_pipeline: &'a StraySdfRenderPipeline,
+    texture_pipeline: &'a StrayTextureRenderPipeline,
+    material_pipelines: &'a MaterialPipelines,
+    camera_bind_group: &'a BindGroup,
+    lights_bind_group: &'a BindGroup,
+    flat_normal: &'a BindGroup,
+    current_target: Option<u32>,
+){
+    for entry in render_query.0.iter(){
+        if current_target.map(|target| entry.source_targets.contains(&target)).unwrap_or(false){
+            continue;
+        }
+        if entry.type_id == 0{
+            let vertex = entry.get_vertex();
+            let index = entry.get_index();
+            let v_buff = vertex.0.as_ref().unwrap();
+            let i_buff = index.0.as_ref().unwrap();
+
+            render_pass.set_pipeline(&shape_pipeline.0);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, v_buff.slice(..));
+            if index.1 > 0{
+                render_pass.set_index_buffer(i_buff.slice(..), IndexFormat::Uint16);
+                render_pass.draw_indexed(0..index.1, 0, 0..1); 
+            } else {
+                render_pass.draw(0..vertex.1,0..1);
+            }
+            
+        }
+
+        if entry.type_id == 3{
+            let v_buff = entry.get_vertex().0.as_ref().unwrap();
+            let index = entry.get_index();
+            render_pass.set_pipeline(&sdf_pipeline.0);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, v_buff.slice(..));
+            render_pass.set_index_buffer(index.0.as_ref().unwrap().slice(..), IndexFormat::Uint16);
+            render_pass.draw_indexed(0..index.1, 0, 0..1);
+        }
+
+        if entry.type_id == 1{
+            let vertex = entry.get_vertex();
+            let v_buff = vertex.0.as_ref().unwrap();
+            render_pass.set_pipeline(&texture_pipeline.0);
+            render_pass.set_bind_group(0, entry.bind_group.as_ref().unwrap(), &[]);
+            render_pass.set_bind_group(1, camera_bind_group, &[]);
+            render_pass.set_bind_group(2, lights_bind_group, &[]);
+            render_pass.set_bind_group(3, entry.normal_bind_group.as_ref().unwrap_or(flat_normal), &[]);
+            render_pass.set_vertex_buffer(0, v_buff.slice(..));
+            match &entry.index{
+                Some(StrayIndexBuffer(Some(i_buff), len)) if *len > 0 => {
                     render_pass.set_index_buffer(i_buff.slice(..), IndexFormat::Uint16);
-                    render_pass.draw_indexed(0..index.1, 0, 0..1); 
-                } else {
-                    render_pass.draw(0..vertex.1,0..1);
+                    render_pass.draw_indexed(0..*len, 0, 0..1);
                 }
-                
+                _ => render_pass.draw(0..vertex.1, 0..1)
             }
 
-            if &entry.type_id == &1{
-                let vertex = entry.get_vertex();
-                let v_buff = vertex.0.as_ref().unwrap();
-                render_pass.set_pipeline(&texture_pipeline.0);
-                render_pass.set_bind_group(0, &entry.bind_group.as_ref().unwrap(), &[]);
-                render_pass.set_vertex_buffer(0, v_buff.slice(..));
-                render_pass.draw(0..vertex.1, 0..1);
+        }
 
+        if entry.type_id == 2{
+            let pipeline = match entry.material.and_then(|material| material_pipelines.0.get(&material)){
+                Some((pipeline, _)) => pipeline,
+                None => continue
+            };
+            let vertex = entry.get_vertex();
+            let v_buff = vertex.0.as_ref().unwrap();
+            render_pass.set_pipeline(pipeline);
+            render_pass.set_bind_group(0, entry.get_bind_group(), &[]);
+            render_pass.set_bind_group(1, camera_bind_group, &[]);
+            render_pass.set_bind_group(2, entry.material_bind_group.as_ref().unwrap(), &[]);
+            render_pass.set_vertex_buffer(0, v_buff.slice(..));
+            match &entry.index{
+                Some(StrayIndexBuffer(Some(i_buff), len)) if *len > 0 => {
+                    render_pass.set_index_buffer(i_buff.slice(..), IndexFormat::Uint16);
+                    render_pass.draw_indexed(0..*len, 0, 0..1);
+                }
+                _ => render_pass.draw(0..vertex.1, 0..1)
             }
-            
         }
+        
     }
-    render_query.0.clear();
-    //smaa_frame.resolve();
-    queue.submit(std::iter::once(encoder.finish()));
-    output.present();
-}    
+}
+
+/// Creates uniform with ambient light and lights of frame, and storage buffer with occluder edges.
+/// Lights above ``MAX_LIGHTS`` are ignored
+pub fn create_lights_bind_group(device: &Device, lighting: &StrayLighting, ambient_light: &AmbientLight, light_query: &LightQuery) -> BindGroup{
+    let lights = &light_query.0[..light_query.0.len().min(MAX_LIGHTS)];
+    let mut uniform: Vec<u8> = bytemuck::cast_slice(&ambient_light.to_raw()).to_vec();
+    uniform.extend_from_slice(bytemuck::cast_slice(&[lights.len() as u32, light_query.1.len() as u32, 0, 0]));
+    uniform.extend_from_slice(bytemuck::cast_slice(lights));
+    uniform.resize(32 + MAX_LIGHTS * std::mem::size_of::<RawLight>(), 0);
+    // Storage buffer can't be empty
+    let edges: &[[f32;4]] = if light_query.1.is_empty() { &[[0.0;4]] } else { &light_query.1 };
+    let uniform_buffer = device.create_buffer_init(
+        &util::BufferInitDescriptor {
+            label: Some("Lights Buffer"),
+            contents: &uniform,
+            usage: BufferUsages::UNIFORM,
+        }
+    );
+    let edges_buffer = device.create_buffer_init(
+        &util::BufferInitDescriptor {
+            label: Some("Occluders Buffer"),
+            contents: bytemuck::cast_slice(edges),
+            usage: BufferUsages::STORAGE,
+        }
+    );
+    device.create_bind_group(&BindGroupDescriptor {
+        layout: &lighting.layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: edges_buffer.as_entire_binding(),
+            }
+        ],
+        label: Some("lights_bind_group"),
+    })
+}
+
+/// Creates uniform with camera matrix, see ``Camera2D::view_projection``
+pub fn create_camera_bind_group(device: &Device, layout: &StrayCameraLayout, view_projection: [[f32;4];4]) -> BindGroup{
+    let buffer = device.create_buffer_init(
+        &util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[view_projection]),
+            usage: BufferUsages::UNIFORM,
+        }
+    );
+    device.create_bind_group(&BindGroupDescriptor {
+        layout: &layout.0,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }
+        ],
+        label: Some("camera_bind_group"),
+    })
+}
 
 
 
 // Initializing render and write data into resources as Engine Data
 
-pub fn initialize_render(res: &mut Resources, window: &Window, backend: StrayBackend) -> Result<(), &'static str>{
+pub fn initialize_render(res: &mut Resources, window: &Window, backend: StrayBackend, render_settings: RenderSettings) -> Result<(), &'static str>{
     let instance = match backend{
         StrayBackend::Vulkan => {Instance::new( InstanceDescriptor {
             backends:  Backends::VULKAN,
@@ -131,30 +579,48 @@ pub fn initialize_render(res: &mut Resources, window: &Window, backend: StrayBac
     };
     let adapter = adapters.into_iter().next().unwrap();
     let surface = unsafe {instance.create_surface(&window).unwrap()};
-    let adapter_features = adapter.features();
+    // Adapter specific format features allow MSAA sample counts other than 4x
+    let adapter_features = adapter.features() & Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
     let (device, queue) = block_on(request_device(&adapter, adapter_features));
     let window_size = window.inner_size();
+    // Shaders output linear colors, so sRGB surface is used to encode them for display
+    let surface_format = surface.get_capabilities(&adapter).formats.into_iter()
+        .find(|format| format.describe().srgb)
+        .unwrap_or(TextureFormat::Rgba8UnormSrgb);
     let config = SurfaceConfiguration {
         usage: TextureUsages::RENDER_ATTACHMENT,
-        format: TextureFormat::Rgba8Unorm,
+        format: surface_format,
         width: window_size.width,
         height: window_size.height,
         present_mode: PresentMode::AutoVsync,
         alpha_mode: CompositeAlphaMode::Auto,
-        view_formats: vec![TextureFormat::Rgba8UnormSrgb]
+        view_formats: vec![]
     };
-    // let swapchain_format = surface.get_supported_formats(&adapter)[0];
+    // Fallback is visible in RenderSettings resource, requested_anti_aliasing keeps the original value
+    let anti_aliasing = render_settings.anti_aliasing.validate(&adapter, &device, surface_format);
+    let render_settings = RenderSettings { anti_aliasing, requested_anti_aliasing: render_settings.anti_aliasing, ..render_settings };
 
     // Pipeline creation, see pipeline/mod.rs
-    let shape_pipeline = create_shape_pipeline(&device, &config);
-    let texture_pipeline = create_texture_pipeline(&device, &config);
-    //let smaa_target = SmaaTarget::new(&device, &queue, config.width, config.height, swapchain_format, smaa::SmaaMode::Smaa1X);
+    let camera_layout = create_camera_layout(&device);
+    let shape_pipeline = create_shape_pipeline(&device, &config, &camera_layout, &render_settings);
+    let sdf_pipeline = create_sdf_pipeline(&device, &config, &camera_layout, &render_settings);
+    let lighting_layout = create_lighting_layout(&device);
+    let texture_pipeline = create_texture_pipeline(&device, &config, &camera_layout, &lighting_layout, &render_settings);
+    // Alpha 0 tells shader that object doesn't have normal map
+    let flat_normal = StrayTexture::from_pixel([128, 128, 255, 0]).as_linear().write_texture(&device, &queue, &texture_pipeline.1);
+    let background_pipeline = create_background_pipeline(&device, &config, &texture_pipeline.1, &render_settings);
+    let smaa_mode = match anti_aliasing{
+        AntiAliasing::Smaa => SmaaMode::Smaa1X,
+        _ => SmaaMode::Disabled
+    };
+    let post_pipelines = StrayPostPipelines::new(&device);
+    let smaa_target = SmaaTarget::new(&device, &queue, config.width, config.height, surface_format, smaa_mode);
     surface.configure(&device, &config);
     println!("Stray Engine v0.1");
     println!("Using {} ({:?})", adapter.get_info().name, adapter.get_info().backend);
 
     // Insert all of these struct as Engine Data
-    //res.insert(EngineData(smaa_target));
+    res.insert(EngineData(smaa_target));
     res.insert(EngineData(instance));
     res.insert(EngineData(adapter));
     res.insert(EngineData(surface));
@@ -164,8 +630,21 @@ pub fn initialize_render(res: &mut Resources, window: &Window, backend: StrayBac
     res.insert(EngineData(StrayIndexBuffer(None, 0)));
     res.insert(EngineData(StrayVertexBuffer(None, 0)));
     res.insert(shape_pipeline);
+    res.insert(sdf_pipeline);
     res.insert(texture_pipeline);
+    res.insert(background_pipeline);
+    res.insert(MaterialPipelines::default());
+    res.insert(post_pipelines);
+    res.insert(StrayLighting { layout: lighting_layout, flat_normal });
+    res.insert(LightQuery(vec![], vec![]));
+    res.insert(StrayPostTextures::default());
+    res.insert(camera_layout);
     res.insert(RenderQuery(vec![]));
+    res.insert(CameraQuery(vec![]));
+    res.insert(RenderTargets::default());
+    res.insert(StrayDepthBuffers::default());
+    res.insert(StrayMsaaBuffers::default());
+    res.insert(render_settings);
 
     Ok(())
 }
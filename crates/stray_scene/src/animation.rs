@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+
+use legion::Entity;
+
+use stray_texture::{AnimationClip, AnimationMode, AnimatedTexture};
+
+use crate::AtlasSprite;
+
+/// What happened in animation of entity
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationEventKind{
+    // Clip in Once mode reached its last frame
+    Finished,
+    // Looping clip started again, ping-pong clip returned to first frame
+    Looped,
+    // Frame with marker of this name was shown
+    Marker(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEvent{
+    pub entity: Entity,
+    pub clip: String,
+    pub kind: AnimationEventKind,
+}
+
+/// ## Overview
+/// Events emitted by animators in current frame, they're cleared at the end of frame
+///
+/// ## Example
+///
+/// ```
+/// use legion::*;
+/// use stray_scene::*;
+///
+/// #[system]
+/// fn deal_damage(#[resource] events: &AnimationEvents){
+///     for event in events.markers("hit"){
+///         println!("{:?} hits", event.entity);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnimationEvents(pub Vec<AnimationEvent>);
+
+impl AnimationEvents{
+    /// Events of marker with given name
+    pub fn markers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a AnimationEvent>{
+        self.0.iter().filter(move |event| matches!(&event.kind, AnimationEventKind::Marker(marker) if marker == name))
+    }
+
+    /// Entities whose clip has finished in this frame
+    pub fn finished(&self) -> impl Iterator<Item = &AnimationEvent>{
+        self.0.iter().filter(|event| event.kind == AnimationEventKind::Finished)
+    }
+}
+
+/// ## Overview
+/// Plays animation clips on ``AtlasSprite`` of the same entity, frame of sprite is set every frame.
+/// Clips are played by name and animator keeps playing clip when it's played again
+///
+/// ## Example
+///
+/// ```ignore
+/// let atlas = TextureAtlas::from_grid(Sprite::new(include_bytes!("player.png"), 0).texture, (32, 32), (0, 0), (0, 0));
+/// let animator = SpriteAnimator::new()
+///     .with_clip(AnimationClip::from_fps("idle", vec![0, 1, 2, 3], 8.0))
+///     .with_clip(AnimationClip::from_fps("jump", vec![8, 9, 10], 12.0).with_mode(AnimationMode::Once))
+///     .playing("idle");
+/// Stray::new()
+///     .push((Transform2D::ZERO, AtlasSprite::new(atlas, 0, 0), animator))
+///     .build()
+///     .run();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpriteAnimator{
+    pub clips: HashMap<String, AnimationClip>,
+    // Multiplier of time, 2 plays clips twice as fast
+    pub speed: f32,
+    pub paused: bool,
+    current: Option<String>,
+    // Position in frames of clip
+    frame: usize,
+    // Time spent on current frame
+    elapsed: f32,
+    backward: bool,
+    finished: bool,
+    // Markers of first frame are emitted by next update
+    entered: bool,
+}
+
+impl Default for SpriteAnimator{
+    fn default() -> Self {
+        Self {
+            clips: HashMap::new(),
+            speed: 1.0,
+            paused: false,
+            current: None,
+            frame: 0,
+            elapsed: 0.0,
+            backward: false,
+            finished: false,
+            entered: false,
+        }
+    }
+}
+
+impl SpriteAnimator{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Animator which plays all frames of GIF or APNG in loop
+    pub fn from_animation(animation: &AnimatedTexture) -> Self{
+        Self::new().with_clip(animation.clip()).playing(AnimatedTexture::CLIP)
+    }
+
+    pub fn with_clip(mut self, clip: AnimationClip) -> Self{
+        self.add_clip(clip);
+        self
+    }
+
+    /// Adds clips of importer, e.g. ``Aseprite::clips``
+    pub fn with_clips(mut self, clips: impl IntoIterator<Item = AnimationClip>) -> Self{
+        for clip in clips{
+            self.add_clip(clip);
+        }
+        self
+    }
+
+    pub fn add_clip(&mut self, clip: AnimationClip){
+        self.clips.insert(clip.name.clone(), clip);
+    }
+
+    /// Same as ``play`` but it can be chained
+    pub fn playing(mut self, name: &str) -> Self{
+        self.play(name);
+        self
+    }
+
+    /// Starts clip from its first frame, clip which is already playing continues.
+    /// Returns false if animator doesn't have clip
+    pub fn play(&mut self, name: &str) -> bool{
+        if !self.clips.contains_key(name){
+            return false;
+        }
+        if self.current.as_deref() != Some(name){
+            self.current = Some(name.to_string());
+            self.restart();
+        }
+        true
+    }
+
+    /// Starts current clip from its first frame
+    pub fn restart(&mut self){
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.backward = false;
+        self.finished = false;
+        self.entered = false;
+    }
+
+    pub fn stop(&mut self){
+        self.current = None;
+    }
+
+    pub fn current_clip(&self) -> Option<&AnimationClip>{
+        self.current.as_ref().and_then(|name| self.clips.get(name))
+    }
+
+    /// Position of current frame in clip
+    pub fn frame_in_clip(&self) -> usize{
+        self.frame
+    }
+
+    /// Index of current frame in atlas
+    pub fn current_frame(&self) -> Option<usize>{
+        self.current_clip().and_then(|clip| clip.frames.get(self.frame).copied())
+    }
+
+    /// Clip in Once mode has reached its last frame
+    pub fn is_finished(&self) -> bool{
+        self.finished
+    }
+
+    /// ## Overview
+    /// Advances current clip by time in seconds and returns what happened
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stray_scene::*;
+    /// use stray_texture::*;
+    ///
+    /// let clip = AnimationClip::from_fps("wave", vec![0, 1, 2], 10.0).with_mode(AnimationMode::PingPong);
+    /// let mut animator = SpriteAnimator::new().with_clip(clip).playing("wave");
+    /// animator.update(0.25);
+    /// assert_eq!(animator.current_frame(), Some(2));
+    /// animator.update(0.1);
+    /// assert_eq!(animator.current_frame(), Some(1));
+    /// assert_eq!(animator.update(0.1), vec![AnimationEventKind::Looped]);
+    /// assert_eq!(animator.current_frame(), Some(0));
+    /// ```
+    pub fn update(&mut self, delta: f32) -> Vec<AnimationEventKind>{
+        let mut events = vec![];
+        let clip = match self.current.as_ref().and_then(|name| self.clips.get(name)){
+            Some(clip) if !clip.frames.is_empty() => clip,
+            _ => return events
+        };
+        if !self.entered{
+            self.entered = true;
+            events.extend(clip.markers_at(self.frame).map(|name| AnimationEventKind::Marker(name.to_string())));
+        }
+        if self.paused || self.finished{
+            return events;
+        }
+        self.elapsed += delta * self.speed;
+        let last = clip.frames.len() - 1;
+        loop{
+            // Frames without duration would never end
+            let duration = clip.frame_duration(self.frame).max(0.001);
+            if self.elapsed < duration{
+                break;
+            }
+            self.elapsed -= duration;
+            match clip.mode{
+                AnimationMode::Loop => {
+                    if self.frame == last{
+                        self.frame = 0;
+                        events.push(AnimationEventKind::Looped);
+                    }
+                    else{
+                        self.frame += 1;
+                    }
+                }
+                AnimationMode::Once => {
+                    if self.frame == last{
+                        self.finished = true;
+                        self.elapsed = 0.0;
+                        events.push(AnimationEventKind::Finished);
+                        break;
+                    }
+                    self.frame += 1;
+                }
+                AnimationMode::PingPong => {
+                    if last == 0{
+                        events.push(AnimationEventKind::Looped);
+                    }
+                    else if self.backward{
+                        self.frame -= 1;
+                        if self.frame == 0{
+                            self.backward = false;
+                            events.push(AnimationEventKind::Looped);
+                        }
+                    }
+                    else{
+                        self.frame += 1;
+                        self.backward = self.frame == last;
+                    }
+                }
+            }
+            events.extend(clip.markers_at(self.frame).map(|name| AnimationEventKind::Marker(name.to_string())));
+        }
+        events
+    }
+}
+
+impl AtlasSprite{
+    /// ## Overview
+    /// Decodes animated GIF or APNG into sprite and animator which plays it, both are pushed to the same entity
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// let (sprite, animator) = AtlasSprite::animated(include_bytes!("fire.gif"), 0).unwrap();
+    /// Stray::new()
+    ///     .push((Transform2D::ZERO, sprite, animator))
+    ///     .build()
+    ///     .run();
+    /// ```
+    pub fn animated(bytes: &[u8], layer: i32) -> Result<(Self, SpriteAnimator), &'static str>{
+        let animation = AnimatedTexture::from_bytes(bytes)?;
+        let animator = SpriteAnimator::from_animation(&animation);
+        Ok((Self::new(animation.atlas, 0, layer), animator))
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn default_animator_advances(){
+        let mut animator = SpriteAnimator::default().with_clip(AnimationClip::new("walk", vec![4, 5, 6], vec![0.1; 3])).playing("walk");
+        assert_eq!(animator.current_frame(), Some(4));
+        animator.update(0.15);
+        assert_eq!(animator.current_frame(), Some(5));
+    }
+}
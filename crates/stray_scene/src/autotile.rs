@@ -0,0 +1,196 @@
+use crate::tilemap::{Tilemap, Tile};
+
+// Bits of neighbours, corners are only counted when both of their edges are set
+const NORTH: u8 = 1;
+const NORTH_EAST: u8 = 2;
+const EAST: u8 = 4;
+const SOUTH_EAST: u8 = 8;
+const SOUTH: u8 = 16;
+const SOUTH_WEST: u8 = 32;
+const WEST: u8 = 64;
+const NORTH_WEST: u8 = 128;
+
+/// Masks of 47 tiles of blob set in order of their tiles, every other mask is reduced to one of them
+pub const BLOB_MASKS: [u8;47] = [
+    0, 1, 4, 5, 7, 16, 17, 20, 21, 23, 28, 29, 31, 64, 65, 68, 69, 71, 80, 81, 84, 85, 87, 92,
+    93, 95, 112, 113, 116, 117, 119, 124, 125, 127, 193, 197, 199, 209, 213, 215, 221, 223, 241, 245, 247, 253, 255
+];
+
+/// How tile variant is picked from neighbours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutotileScheme{
+    // 47 tiles, edges and corners of 8 neighbours, tiles are in order of ``BLOB_MASKS``
+    Blob47,
+    // 16 tiles, edges of 4 neighbours, tile of mask is at index ``north + 2 * east + 4 * south + 8 * west``
+    Wang16,
+}
+
+/// ## Overview
+/// Tiles of one terrain with variant for every combination of neighbours. Cells whose tile is one of variants
+/// belong to terrain, painting or erasing cell picks variants of cell and its 8 neighbours again
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+/// use stray_texture::*;
+/// use glam::vec2;
+///
+/// // Water tiles are frames 16 to 31 of tileset
+/// let water = AutotileSet::new(AutotileScheme::Wang16, (16..32).collect()).unwrap();
+/// let mut map = Tilemap::new(TextureAtlas::new(StrayTexture::white()), (8, 8), vec2(16.0, 16.0)).with_layer("ground", 0);
+/// map.paint_autotile(0, 2, 2, &water);
+/// assert_eq!(map.get(0, 2, 2).unwrap().index, 16);
+/// map.paint_autotile(0, 3, 2, &water);
+/// // Left tile connects east and right tile connects west
+/// assert_eq!(map.get(0, 2, 2).unwrap().index, 16 + 2);
+/// assert_eq!(map.get(0, 3, 2).unwrap().index, 16 + 8);
+/// map.erase_autotile(0, 3, 2, &water);
+/// assert_eq!(map.get(0, 2, 2).unwrap().index, 16);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutotileSet{
+    pub scheme: AutotileScheme,
+    // Index of tile for every variant of scheme
+    tiles: Vec<usize>,
+    // Cells outside of tilemap count as terrain
+    pub connect_to_edges: bool,
+}
+
+impl AutotileSet{
+    /// Tiles must have one index for every variant of scheme
+    pub fn new(scheme: AutotileScheme, tiles: Vec<usize>) -> Result<Self, &'static str>{
+        let count = match scheme{
+            AutotileScheme::Blob47 => BLOB_MASKS.len(),
+            AutotileScheme::Wang16 => 16
+        };
+        if tiles.len() != count{
+            return Err("Autotile set must have tile for every variant of its scheme");
+        }
+        Ok(Self { scheme, tiles, connect_to_edges: false })
+    }
+
+    pub fn with_edges(mut self, connect_to_edges: bool) -> Self{
+        self.connect_to_edges = connect_to_edges;
+        self
+    }
+
+    pub fn contains(&self, index: usize) -> bool{
+        self.tiles.contains(&index)
+    }
+
+    pub fn tiles(&self) -> &[usize]{
+        &self.tiles
+    }
+
+    /// ## Overview
+    /// Tile for neighbours in bits, north is 1 and bits go clockwise to north-west which is 128
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stray_scene::*;
+    ///
+    /// let blob = AutotileSet::new(AutotileScheme::Blob47, (0..47).collect()).unwrap();
+    /// // North-east corner without east edge doesn't change tile
+    /// assert_eq!(blob.tile_for(1 | 2), blob.tile_for(1));
+    /// assert_eq!(blob.tile_for(255), 46);
+    /// ```
+    pub fn tile_for(&self, neighbours: u8) -> usize{
+        match self.scheme{
+            AutotileScheme::Blob47 => {
+                let mask = reduce_corners(neighbours);
+                let variant = BLOB_MASKS.iter().position(|blob| *blob == mask).unwrap_or(0);
+                self.tiles[variant]
+            }
+            AutotileScheme::Wang16 => {
+                let bit = |side: u8, value: usize| if neighbours & side != 0 { value } else { 0 };
+                self.tiles[bit(NORTH, 1) + bit(EAST, 2) + bit(SOUTH, 4) + bit(WEST, 8)]
+            }
+        }
+    }
+}
+
+/// Removes corners whose edges aren't both set
+fn reduce_corners(mask: u8) -> u8{
+    let mut reduced = mask & (NORTH | EAST | SOUTH | WEST);
+    for (corner, first, second) in [(NORTH_EAST, NORTH, EAST), (SOUTH_EAST, SOUTH, EAST), (SOUTH_WEST, SOUTH, WEST), (NORTH_WEST, NORTH, WEST)]{
+        if mask & corner != 0 && mask & first != 0 && mask & second != 0{
+            reduced |= corner;
+        }
+    }
+    reduced
+}
+
+impl Tilemap{
+    /// Places tile of terrain and updates cell and its neighbours, returns false if cell or layer is outside of tilemap
+    pub fn paint_autotile(&mut self, layer: usize, x: u32, y: u32, set: &AutotileSet) -> bool{
+        if !self.set(layer, x, y, Some(Tile::new(set.tiles[0]))){
+            return false;
+        }
+        self.update_autotiles_around(layer, x, y, set);
+        true
+    }
+
+    /// Removes tile of terrain and updates its neighbours, tiles of other terrains aren't removed
+    pub fn erase_autotile(&mut self, layer: usize, x: u32, y: u32, set: &AutotileSet) -> bool{
+        match self.get(layer, x, y){
+            Some(tile) if set.contains(tile.index) => {}
+            _ => return false
+        }
+        self.set(layer, x, y, None);
+        self.update_autotiles_around(layer, x, y, set);
+        true
+    }
+
+    /// Picks variant of every tile of terrain in layer, used after tiles were placed with ``set`` or ``fill``
+    pub fn apply_autotiles(&mut self, layer: usize, set: &AutotileSet){
+        let (width, height) = self.size();
+        for y in 0..height{
+            for x in 0..width{
+                self.update_autotile(layer, x, y, set);
+            }
+        }
+    }
+
+    /// Neighbours of cell in bits of ``AutotileSet::tile_for``
+    pub fn autotile_neighbours(&self, layer: usize, x: u32, y: u32, set: &AutotileSet) -> u8{
+        let (width, height) = self.size();
+        let offsets = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+        let mut mask = 0;
+        for (bit, (dx, dy)) in offsets.iter().enumerate(){
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            let inside = nx >= 0 && ny >= 0 && nx < width as i64 && ny < height as i64;
+            let connected = if inside{
+                self.get(layer, nx as u32, ny as u32).map(|tile| set.contains(tile.index)).unwrap_or(false)
+            }
+            else{
+                set.connect_to_edges
+            };
+            if connected{
+                mask |= 1 << bit;
+            }
+        }
+        mask
+    }
+
+    fn update_autotiles_around(&mut self, layer: usize, x: u32, y: u32, set: &AutotileSet){
+        for ny in y.saturating_sub(1)..=y + 1{
+            for nx in x.saturating_sub(1)..=x + 1{
+                self.update_autotile(layer, nx, ny, set);
+            }
+        }
+    }
+
+    // Tint and flips of tile are kept
+    fn update_autotile(&mut self, layer: usize, x: u32, y: u32, set: &AutotileSet){
+        let tile = match self.get(layer, x, y){
+            Some(tile) if set.contains(tile.index) => tile,
+            _ => return
+        };
+        let index = set.tile_for(self.autotile_neighbours(layer, x, y, set));
+        if index != tile.index{
+            self.set(layer, x, y, Some(Tile { index, ..tile }));
+        }
+    }
+}
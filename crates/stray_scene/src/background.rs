@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::StrayColor;
+use stray_texture::{StrayTexture, RenderTargets};
+
+use crate::engine_data::{
+    TextureVertex,
+    RawVertex,
+    RenderBackground,
+    StrayVertexBuffer
+};
+use crate::types::Transform2D;
+
+/// How texture of background fills the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackgroundMode{
+    Stretch,
+    // Texture is repeated in its size in pixels, starting from top left corner
+    Tile,
+}
+
+/// ## Overview
+/// What is drawn behind all objects.
+/// It's a resource used by every camera, ``Camera2D::background`` overrides it
+/// 
+/// ## Example
+/// 
+/// ```ignore
+/// Stray::new()
+///     .insert(Background::Gradient{ 
+///         top: StrayColor::hex("#1e3c72").unwrap(), 
+///         bottom: StrayColor::hex("#2a5298").unwrap() 
+///     })
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub enum Background{
+    Color(StrayColor),
+    Gradient{ top: StrayColor, bottom: StrayColor },
+    Texture{ texture: StrayTexture, mode: BackgroundMode },
+    // Keeps content of previous frame
+    None,
+}
+
+impl Default for Background{
+    fn default() -> Self {
+        Self::Color(StrayColor::srgb(0.06, 0.06, 0.06))
+    }
+}
+
+impl Background{
+    /// Creates gpu data of background for target with given size in pixels
+    pub fn to_render_background(&self, 
+        device: &Device, 
+        queue: &Queue, 
+        layout: &BindGroupLayout, 
+        targets: &RenderTargets,
+        target_size: [u32;2]
+    ) -> RenderBackground{
+        match self{
+            Self::Color(color) => RenderBackground { clear: Some(*color), vertex: None, bind_group: None },
+            Self::None => RenderBackground { clear: None, vertex: None, bind_group: None },
+            Self::Gradient { top, bottom } => {
+                let bind_group = StrayTexture::white().write_texture(device, queue, layout);
+                Self::quad(device, [1.0, 1.0], [*top, *bottom], bind_group)
+            }
+            Self::Texture { texture, mode } => {
+                let (width, height) = texture.size(targets);
+                let (texture, uv) = match mode{
+                    BackgroundMode::Stretch => (texture.clone(), [1.0, 1.0]),
+                    BackgroundMode::Tile => (
+                        texture.clone().with_sampling(texture.sampling.with_address_mode(AddressMode::Repeat)),
+                        [target_size[0] as f32 / width.max(1) as f32, target_size[1] as f32 / height.max(1) as f32]
+                    )
+                };
+                match texture.bind_group(device, queue, layout, targets){
+                    Some(bind_group) => Self::quad(device, uv, [StrayColor::WHITE, StrayColor::WHITE], bind_group),
+                    None => RenderBackground { clear: Some(StrayColor::TRANSPARENT), vertex: None, bind_group: None }
+                }
+            }
+        }
+    }
+
+    // Fullscreen quad in clip space
+    fn quad(device: &Device, uv: [f32;2], colors: [StrayColor;2], bind_group: BindGroup) -> RenderBackground{
+        let corner = |x: f32, y: f32| {
+            let mut vertex = TextureVertex::new(x, y, 0, (x + 1.0) / 2.0 * uv[0], (1.0 - y) / 2.0 * uv[1]);
+            vertex.material.color = if y > 0.0 { colors[0] } else { colors[1] };
+            vertex.to_raw(&Transform2D::ZERO)
+        };
+        let vertices: Vec<RawVertex> = vec![
+            corner(-1.0, -1.0),
+            corner(1.0, -1.0),
+            corner(-1.0, 1.0),
+            corner(-1.0, 1.0),
+            corner(1.0, -1.0),
+            corner(1.0, 1.0),
+        ];
+        let vertex_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Background Vertex Buffer"),
+                contents: bytemuck::cast_slice(vertices.as_slice()),
+                usage: BufferUsages::VERTEX,
+            }
+        );
+        RenderBackground { 
+            clear: Some(StrayColor::TRANSPARENT), 
+            vertex: Some(StrayVertexBuffer(Some(Arc::new(vertex_buffer)), vertices.len() as u32)), 
+            bind_group: Some(bind_group) 
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use glam::vec2;
+use image::{load_from_memory, RgbaImage, Rgba, GenericImageView};
+
+use crate::font::{GlyphInfo, FontMetrics};
+
+/// Character of bitmap font, values are in pixels of native size
+#[derive(Debug, Clone, Copy, Default)]
+struct BitmapGlyph{
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    // Offset of top left corner from pen position at top of line, y points down
+    x_offset: f32,
+    y_offset: f32,
+    advance: f32,
+    page: u32,
+}
+
+/// AngelCode BMFont, glyphs of all pages are in one atlas where pages are stacked vertically
+pub(crate) struct BitmapFont{
+    pub size: f32,
+    line_height: f32,
+    base: f32,
+    glyphs: HashMap<char, BitmapGlyph>,
+    kernings: HashMap<(char, char), f32>,
+}
+
+impl BitmapFont{
+    /// Parses descriptor and builds atlas from pages, binary descriptor starts with ``BMF``
+    pub fn parse(fnt: &[u8], pages: &[&[u8]]) -> Result<(Self, RgbaImage), &'static str>{
+        let (mut font, page_count, page_size) = if fnt.starts_with(b"BMF"){
+            parse_binary(fnt)?
+        }
+        else{
+            parse_text(std::str::from_utf8(fnt).map_err(|_| "BMFont descriptor isn't valid UTF-8")?)?
+        };
+        if pages.len() < page_count as usize{
+            return Err("BMFont has more pages than images which were passed");
+        }
+        // Pages are decoded before atlas is allocated, so its size is limited by real images
+        let images = pages.iter().take(page_count as usize)
+            .map(|bytes| {
+                let image = load_from_memory(bytes).map_err(|_| "BMFont page can't be decoded")?;
+                if image.dimensions() != page_size{
+                    return Err("Size of BMFont page doesn't match scaleW and scaleH");
+                }
+                Ok(image)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let atlas_height = page_size.1.checked_mul(page_count.max(1)).ok_or("BMFont pages are too large")?;
+        let mut atlas = RgbaImage::new(page_size.0, atlas_height);
+        for (i, image) in images.iter().enumerate(){
+            // Pages without alpha store coverage in color, glyphs are white and tinted by text color
+            let has_alpha = image.color().has_alpha();
+            let y_offset = page_size.1 * i as u32;
+            for (x, y, pixel) in image.to_rgba8().enumerate_pixels(){
+                let pixel = if has_alpha { *pixel } else { Rgba([255, 255, 255, pixel.0[0]]) };
+                atlas.put_pixel(x, y + y_offset, pixel);
+            }
+        }
+        for glyph in font.glyphs.values_mut(){
+            glyph.y += glyph.page * page_size.1;
+        }
+        Ok((font, atlas))
+    }
+
+    fn scale(&self, size: f32) -> f32{
+        size / self.size
+    }
+
+    pub fn metrics(&self, size: f32) -> FontMetrics{
+        let scale = self.scale(size);
+        FontMetrics { ascent: self.base * scale, descent: (self.base - self.line_height) * scale, line_gap: 0.0 }
+    }
+
+    pub fn advance(&self, character: char, size: f32) -> f32{
+        self.glyphs.get(&character).map(|glyph| glyph.advance * self.scale(size)).unwrap_or(0.0)
+    }
+
+    pub fn kerning(&self, left: char, right: char, size: f32) -> f32{
+        self.kernings.get(&(left, right)).map(|amount| amount * self.scale(size)).unwrap_or(0.0)
+    }
+
+    /// Glyph scaled to size, characters which aren't in font are empty
+    pub fn glyph(&self, character: char, size: f32) -> GlyphInfo{
+        let scale = self.scale(size);
+        let glyph = self.glyphs.get(&character).copied().unwrap_or_default();
+        GlyphInfo {
+            advance: glyph.advance * scale,
+            offset: vec2(glyph.x_offset, self.base - glyph.y_offset - glyph.height as f32) * scale,
+            size: vec2(glyph.width as f32, glyph.height as f32) * scale,
+            atlas_rect: (glyph.x, glyph.y, glyph.width, glyph.height),
+        }
+    }
+}
+
+/// Splits line of text descriptor into tag and its ``key=value`` pairs, values can be quoted
+fn parse_tags(line: &str) -> (&str, HashMap<&str, &str>){
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let mut values = HashMap::new();
+    while let Some((key, after)) = rest.trim_start().split_once('='){
+        let (value, after) = match after.strip_prefix('"'){
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => after.split_once(char::is_whitespace).unwrap_or((after, ""))
+        };
+        values.insert(key.trim(), value);
+        rest = after;
+    }
+    (tag, values)
+}
+
+fn number(values: &HashMap<&str, &str>, key: &str) -> Result<i64, &'static str>{
+    values.get(key).unwrap_or(&"0").parse().map_err(|_| "BMFont descriptor has invalid number")
+}
+
+fn unsigned(values: &HashMap<&str, &str>, key: &str) -> Result<u32, &'static str>{
+    u32::try_from(number(values, key)?).map_err(|_| "BMFont descriptor has negative or too large number")
+}
+
+fn to_char(id: i64) -> Option<char>{
+    u32::try_from(id).ok().and_then(char::from_u32)
+}
+
+fn parse_text(fnt: &str) -> Result<(BitmapFont, u32, (u32,u32)), &'static str>{
+    let mut font = BitmapFont { size: 0.0, line_height: 0.0, base: 0.0, glyphs: HashMap::new(), kernings: HashMap::new() };
+    let mut page_count = 0;
+    let mut page_size = (0, 0);
+    for line in fnt.lines(){
+        let (tag, values) = parse_tags(line);
+        match tag{
+            // Size is negative when font was generated with matching of char height
+            "info" => font.size = number(&values, "size")?.abs() as f32,
+            "common" => {
+                font.line_height = number(&values, "lineHeight")? as f32;
+                font.base = number(&values, "base")? as f32;
+                page_size = (unsigned(&values, "scaleW")?, unsigned(&values, "scaleH")?);
+                page_count = unsigned(&values, "pages")?;
+            }
+            "char" => {
+                if let Some(character) = to_char(number(&values, "id")?){
+                    font.glyphs.insert(character, BitmapGlyph {
+                        x: unsigned(&values, "x")?,
+                        y: unsigned(&values, "y")?,
+                        width: unsigned(&values, "width")?,
+                        height: unsigned(&values, "height")?,
+                        x_offset: number(&values, "xoffset")? as f32,
+                        y_offset: number(&values, "yoffset")? as f32,
+                        advance: number(&values, "xadvance")? as f32,
+                        page: unsigned(&values, "page")?,
+                    });
+                }
+            }
+            "kerning" => {
+                if let (Some(first), Some(second)) = (to_char(number(&values, "first")?), to_char(number(&values, "second")?)){
+                    font.kernings.insert((first, second), number(&values, "amount")? as f32);
+                }
+            }
+            _ => {}
+        }
+    }
+    validate(font, page_count, page_size)
+}
+
+fn parse_binary(fnt: &[u8]) -> Result<(BitmapFont, u32, (u32,u32)), &'static str>{
+    if fnt.len() < 4 || fnt[3] != 3{
+        return Err("Only version 3 of binary BMFont is supported");
+    }
+    let u16_at = |block: &[u8], i: usize| u16::from_le_bytes([block[i], block[i + 1]]);
+    let i16_at = |block: &[u8], i: usize| i16::from_le_bytes([block[i], block[i + 1]]);
+    let u32_at = |block: &[u8], i: usize| u32::from_le_bytes([block[i], block[i + 1], block[i + 2], block[i + 3]]);
+    let mut font = BitmapFont { size: 0.0, line_height: 0.0, base: 0.0, glyphs: HashMap::new(), kernings: HashMap::new() };
+    let mut page_count = 0;
+    let mut page_size = (0, 0);
+    let mut offset = 4;
+    while offset + 5 <= fnt.len(){
+        let block_type = fnt[offset];
+        let size = u32_at(fnt, offset + 1) as usize;
+        let block = fnt.get(offset + 5..offset + 5 + size).ok_or("Binary BMFont is truncated")?;
+        match block_type{
+            1 if size >= 2 => font.size = i16_at(block, 0).unsigned_abs() as f32,
+            2 if size >= 10 => {
+                font.line_height = u16_at(block, 0) as f32;
+                font.base = u16_at(block, 2) as f32;
+                page_size = (u16_at(block, 4) as u32, u16_at(block, 6) as u32);
+                page_count = u16_at(block, 8) as u32;
+            }
+            4 => {
+                for char_block in block.chunks_exact(20){
+                    if let Some(character) = char::from_u32(u32_at(char_block, 0)){
+                        font.glyphs.insert(character, BitmapGlyph {
+                            x: u16_at(char_block, 4) as u32,
+                            y: u16_at(char_block, 6) as u32,
+                            width: u16_at(char_block, 8) as u32,
+                            height: u16_at(char_block, 10) as u32,
+                            x_offset: i16_at(char_block, 12) as f32,
+                            y_offset: i16_at(char_block, 14) as f32,
+                            advance: i16_at(char_block, 16) as f32,
+                            page: char_block[18] as u32,
+                        });
+                    }
+                }
+            }
+            5 => {
+                for kerning_block in block.chunks_exact(10){
+                    if let (Some(first), Some(second)) = (char::from_u32(u32_at(kerning_block, 0)), char::from_u32(u32_at(kerning_block, 4))){
+                        font.kernings.insert((first, second), i16_at(kerning_block, 8) as f32);
+                    }
+                }
+            }
+            _ => {}
+        }
+        offset += 5 + size;
+    }
+    validate(font, page_count, page_size)
+}
+
+fn validate(mut font: BitmapFont, page_count: u32, page_size: (u32,u32)) -> Result<(BitmapFont, u32, (u32,u32)), &'static str>{
+    if page_size.0 == 0 || page_size.1 == 0{
+        return Err("BMFont descriptor doesn't have common block");
+    }
+    let inside = |start: u32, length: u32, size: u32| start.checked_add(length).map(|end| end <= size).unwrap_or(false);
+    if font.glyphs.values().any(|glyph| glyph.page >= page_count || !inside(glyph.x, glyph.width, page_size.0) || !inside(glyph.y, glyph.height, page_size.1)){
+        return Err("BMFont character is outside of its page");
+    }
+    if font.size == 0.0{
+        font.size = font.line_height;
+    }
+    Ok((font, page_count, page_size))
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn descriptor(common: &str, glyph: &str) -> String{
+        format!("info size=16\ncommon lineHeight=16 base=12 {}\nchar id=65 {} xoffset=0 yoffset=0 xadvance=8\n", common, glyph)
+    }
+
+    #[test]
+    fn valid_text_descriptor_is_parsed(){
+        let (font, page_count, page_size) = parse_text(&descriptor("scaleW=64 scaleH=32 pages=2", "x=56 y=24 width=8 height=8 page=1")).unwrap();
+        assert_eq!((page_count, page_size), (2, (64, 32)));
+        assert_eq!(font.glyphs[&'A'].page, 1);
+    }
+
+    #[test]
+    fn negative_numbers_are_rejected(){
+        assert!(parse_text(&descriptor("scaleW=-64 scaleH=32 pages=1", "x=0 y=0 width=8 height=8 page=0")).is_err());
+        assert!(parse_text(&descriptor("scaleW=64 scaleH=32 pages=-1", "x=0 y=0 width=8 height=8 page=0")).is_err());
+        assert!(parse_text(&descriptor("scaleW=64 scaleH=32 pages=1", "x=-8 y=0 width=8 height=8 page=0")).is_err());
+        assert!(parse_text(&descriptor("scaleW=64 scaleH=32 pages=1", "x=0 y=0 width=8 height=-8 page=0")).is_err());
+    }
+
+    #[test]
+    fn overflowing_numbers_are_rejected(){
+        assert!(parse_text(&descriptor("scaleW=4294967296 scaleH=32 pages=1", "x=0 y=0 width=8 height=8 page=0")).is_err());
+        assert!(parse_text(&descriptor("scaleW=64 scaleH=32 pages=1", "x=4294967295 y=0 width=8 height=8 page=0")).is_err());
+        assert!(parse_text(&descriptor("scaleW=64 scaleH=32 pages=1", "x=0 y=4294967290 width=8 height=4294967290 page=0")).is_err());
+        assert!(parse_text(&descriptor("scaleW=64 scaleH=32 pages=1", "x=0 y=0 width=8 height=8 page=99999999999")).is_err());
+    }
+
+    #[test]
+    fn huge_pages_are_rejected_before_atlas_is_allocated(){
+        let fnt = descriptor("scaleW=1 scaleH=65536 pages=65536", "x=0 y=0 width=1 height=1 page=0");
+        let page: &[u8] = &[];
+        let pages = vec![page; 65536];
+        assert!(BitmapFont::parse(fnt.as_bytes(), &pages).is_err());
+    }
+}
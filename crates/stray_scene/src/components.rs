@@ -1,10 +1,12 @@
-use glam::vec2;
+use std::sync::Arc;
+
+use glam::{vec2, vec3, Mat4, Vec2};
 use wgpu::{*, util::DeviceExt};
 
 use image::*;
 
-use stray_texture::StrayTexture;
-use stray_material::StandardMaterial;
+use stray_texture::{StrayTexture, RenderTarget, RenderTargets, TextureAtlas, AtlasFrame};
+use stray_material::{StandardMaterial, Material2D};
 
 use crate::engine_data::{
     TextureVertex,
@@ -15,10 +17,16 @@ use crate::engine_data::{
     Vertex
 };
 use crate::types::Transform2D;
+use crate::background::Background;
 
 pub struct Sprite{
     pub texture: StrayTexture,
     pub layer: i32,
+    // Point of texture which is placed at position of transform and which sprite rotates around,
+    // (0,0) is top left corner and (1,1) is bottom right corner of texture
+    pub pivot: Vec2,
+    // Tangent space normal map with the same size as texture, used by lights
+    pub normal_map: Option<StrayTexture>,
 }
 
 impl Sprite{
@@ -26,49 +34,257 @@ impl Sprite{
         let image = load_from_memory(bytes).unwrap();
         let rgba = image.to_rgba8();
         let dimensions = image.dimensions();
-        Self { texture: StrayTexture::with(rgba, dimensions) , layer: layer}
+        Self { texture: StrayTexture::with(rgba, dimensions) , layer, pivot: vec2(0.5, 0.5), normal_map: None}
+    }
+    /// Creates sprite which draws content of render target
+    pub fn from_target(target: &RenderTarget, layer: i32) -> Self{
+        Self { texture: StrayTexture::from_target(target), layer, pivot: vec2(0.5, 0.5), normal_map: None}
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self{
+        self.pivot = pivot;
+        self
+    }
+
+    /// Sets normal map, green channel points up in texture like in most of editors
+    pub fn with_normal_map(mut self, bytes: &[u8]) -> Self{
+        let image = load_from_memory(bytes).unwrap();
+        let dimensions = image.dimensions();
+        self.normal_map = Some(StrayTexture::with(image.to_rgba8(), dimensions).as_linear());
+        self
+    }
+
+    /// Creates sprite from already loaded texture
+    pub fn from_texture(texture: StrayTexture, layer: i32) -> Self{
+        Self { texture, layer, pivot: vec2(0.5, 0.5), normal_map: None}
+    }
+
+    /// ## Overview
+    /// Quad of sprite in pixels, relative to its transform
+    /// 
+    /// ## Example
+    /// 
+    /// ```
+    /// use glam::*;
+    /// use image::RgbaImage;
+    /// use stray_scene::*;
+    /// use stray_texture::StrayTexture;
+    /// 
+    /// let texture = StrayTexture::with(RgbaImage::new(64, 32), (64, 32));
+    /// let sprite = Sprite::from_texture(texture, 0);
+    /// let vertices = sprite.vertices((64, 32));
+    /// 
+    /// // Quad has size of texture and is centered on pivot
+    /// assert_eq!(vertices[0].position(), vec2(-32.0, -16.0));
+    /// assert_eq!(vertices[5].position(), vec2(32.0, 16.0));
+    /// 
+    /// // Pivot in top left corner
+    /// let vertices = sprite.with_pivot(vec2(0.0, 0.0)).vertices((64, 32));
+    /// assert_eq!(vertices[0].position(), vec2(0.0, -32.0));
+    /// assert_eq!(vertices[5].position(), vec2(64.0, 0.0));
+    /// ```
+    pub fn vertices(&self, dimensions: (u32,u32)) -> Vec<TextureVertex>{
+        let size = vec2(dimensions.0 as f32, dimensions.1 as f32);
+        let corner = |u: f32, v: f32| {
+            let offset = vec2(u - self.pivot.x, self.pivot.y - v) * size;
+            TextureVertex::new(offset.x, offset.y, self.layer, u, v)
+        };
+        vec![
+            corner(0.0, 1.0), 
+            corner(1.0, 1.0), 
+            corner(0.0, 0.0), 
+            corner(0.0, 0.0), 
+            corner(1.0, 1.0), 
+            corner(1.0, 0.0),
+        ]
     }
+
     pub fn to_render_object(&self, 
         device: &Device, 
-        config: &SurfaceConfiguration, 
         queue: &Queue, 
         layout: &BindGroupLayout,
         transform: &Transform2D
     ) -> RenderObject{
-        let th = (self.texture.dimensions.1 as f32 * transform.scale) as i32 ;
-        let tw = (self.texture.dimensions.0 as f32 * transform.scale) as i32;
-        let raw_size = [config.width as i32,config.height as i32];
-        let vertices_data = vec![
-            TextureVertex::new(-tw, -th, self.layer, 0.0, 1.0), 
-            TextureVertex::new(tw, -th, self.layer, 1.0, 1.0), 
-            TextureVertex::new(-tw, th, self.layer, 0.0, 0.0), 
-            TextureVertex::new(-tw, th, self.layer, 0.0, 0.0), 
-            TextureVertex::new(tw, -th, self.layer, 1.0, 1.0 ), 
-            TextureVertex::new(tw, th, self.layer, 1.0, 0.0),
-        ];
+        let bind_group = self.texture.write_texture(device, queue, layout);
+        self.to_render_object_with(device, transform, self.texture.dimensions, Arc::new(bind_group))
+    }
 
-        // Apply transform scale and make position in [-1,1] range
-        let true_transform = Transform2D { 
-            position: transform.position/vec2((config.width/2) as f32, (config.height/2) as f32), 
-            rotation: transform.rotation, 
-            scale: transform.scale };
-        
-        let vertices: Vec<RawVertex> = vertices_data.iter().map(|x| x.to_raw(raw_size, &true_transform)).collect();
-        let vertex_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(vertices.as_slice()),
-                usage: BufferUsages::VERTEX,
+    /// Creates render object from already created bind group,
+    /// used for textures which aren't stored on cpu like render targets
+    pub fn to_render_object_with(&self, 
+        device: &Device, 
+        transform: &Transform2D,
+        dimensions: (u32,u32),
+        bind_group: Arc<BindGroup>
+    ) -> RenderObject{
+        textured_render_object(device, &self.vertices(dimensions), transform, self.layer, bind_group, self.texture.target())
+    }
+}
+
+/// Render object of texture pipeline with vertices which aren't indexed
+fn textured_render_object(
+    device: &Device,
+    vertices_data: &[TextureVertex],
+    transform: &Transform2D,
+    layer: i32,
+    bind_group: Arc<BindGroup>,
+    source_target: Option<u32>
+) -> RenderObject{
+    let vertices: Vec<RawVertex> = vertices_data.iter().map(|x| x.to_raw(transform)).collect();
+    let vertex_buffer = device.create_buffer_init(
+        &wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices.as_slice()),
+            usage: BufferUsages::VERTEX,
+        }
+    );
+    let vertex_buffer_len = vertices_data.len() as u32;
+    RenderObject { 
+        type_id: 1, 
+        vertex: Some(StrayVertexBuffer(Some(Arc::new(vertex_buffer)), vertex_buffer_len)), 
+        index: None, 
+        bind_group: Some(bind_group),
+        source_targets: source_target.into_iter().collect(),
+        layer,
+        y: transform.position.y,
+        material: None,
+        material_bind_group: None,
+        normal_bind_group: None,
+    }
+}
+
+/// ## Overview
+/// Sprite which draws one frame of texture atlas, frame is selected by index or name.
+/// Quad has size of untrimmed frame, so trimmed frames of animation don't jump around.
+/// Pivot is taken from frame when sprite doesn't override it
+///
+/// ## Example
+///
+/// ```ignore
+/// let atlas = TextureAtlas::from_json(Sprite::new(include_bytes!("items.png"), 0).texture, include_str!("items.json")).unwrap();
+/// let sword = AtlasSprite::from_name(atlas, "sword.png", 0).unwrap();
+/// Stray::new()
+///     .push((Transform2D::ZERO, sword))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct AtlasSprite{
+    pub atlas: TextureAtlas,
+    pub index: usize,
+    pub layer: i32,
+    // Overrides pivot of frame, (0,0) is top left corner and (1,1) is bottom right corner of frame
+    pub pivot: Option<Vec2>,
+    // Frame is mirrored around pivot
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl AtlasSprite{
+    pub fn new(atlas: TextureAtlas, index: usize, layer: i32) -> Self{
+        Self { atlas, index, layer, pivot: None, flip_x: false, flip_y: false }
+    }
+
+    /// Creates sprite with named frame, returns ``None`` if atlas doesn't have it
+    pub fn from_name(atlas: TextureAtlas, name: &str, layer: i32) -> Option<Self>{
+        let index = atlas.index_of(name)?;
+        Some(Self::new(atlas, index, layer))
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self{
+        self.pivot = Some(pivot);
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self{
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Selects named frame, returns false and keeps current frame if atlas doesn't have it
+    pub fn set_frame(&mut self, name: &str) -> bool{
+        match self.atlas.index_of(name){
+            Some(index) => {
+                self.index = index;
+                true
             }
-        );
-        let vertex_buffer_len = vertices_data.len() as u32;
-        let bind_group = self.texture.write_texture(device, queue, layout);
-        RenderObject { 
-            type_id: 1, 
-            vertex: Some(StrayVertexBuffer(Some(vertex_buffer), vertex_buffer_len)), 
-            index: None, 
-            bind_group: Some(bind_group),
+            None => false
+        }
+    }
+
+    pub fn frame(&self) -> Option<&AtlasFrame>{
+        self.atlas.frame(self.index)
+    }
+
+    /// ## Overview
+    /// Quad of current frame in pixels relative to transform, it's empty when index is out of atlas
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use glam::*;
+    /// use image::RgbaImage;
+    /// use stray_scene::*;
+    /// use stray_texture::*;
+    ///
+    /// let atlas = TextureAtlas::from_grid(StrayTexture::with(RgbaImage::new(64, 32), (64, 32)), (32, 32), (0, 0), (0, 0));
+    /// let sprite = AtlasSprite::new(atlas, 1, 0).with_pivot(vec2(0.5, 1.0));
+    /// let vertices = sprite.vertices();
+    ///
+    /// // Quad stands on pivot and samples right half of texture
+    /// assert_eq!(vertices[0].position(), vec2(-16.0, 0.0));
+    /// assert_eq!(vertices[5].position(), vec2(16.0, 32.0));
+    /// assert_eq!(vertices[0].tex_coords(), vec2(0.5, 1.0));
+    /// ```
+    pub fn vertices(&self) -> Vec<TextureVertex>{
+        let frame = match self.frame(){
+            Some(frame) => frame,
+            None => return vec![]
+        };
+        let source_size = vec2(frame.source_size.0 as f32, frame.source_size.1 as f32);
+        let pivot = self.pivot
+            .or(frame.pivot.map(|(x, y)| vec2(x, y)))
+            .unwrap_or(vec2(0.5, 0.5)) * source_size;
+        // Trimmed rectangle relative to pivot, y points down
+        let mut min = vec2(frame.offset.0 as f32, frame.offset.1 as f32) - pivot;
+        let mut max = min + vec2(frame.size.0 as f32, frame.size.1 as f32);
+        if self.flip_x{
+            (min.x, max.x) = (-max.x, -min.x);
+        }
+        if self.flip_y{
+            (min.y, max.y) = (-max.y, -min.y);
+        }
+        let texture_size = self.atlas.texture.dimensions;
+        let corner = |u: f32, v: f32| {
+            let position = min + vec2(u, v) * (max - min);
+            // Mirrored quad samples frame from the other side, so triangles keep their winding
+            let sample_u = if self.flip_x { 1.0 - u } else { u };
+            let sample_v = if self.flip_y { 1.0 - v } else { v };
+            let (tex_u, tex_v) = frame.uv(sample_u, sample_v, texture_size);
+            TextureVertex::new(position.x, -position.y, self.layer, tex_u, tex_v)
+        };
+        vec![
+            corner(0.0, 1.0), 
+            corner(1.0, 1.0), 
+            corner(0.0, 0.0), 
+            corner(0.0, 0.0), 
+            corner(1.0, 1.0), 
+            corner(1.0, 0.0),
+        ]
+    }
+
+    /// Creates render object from bind group of atlas texture, returns ``None`` if there is no frame to draw
+    pub fn to_render_object_with(&self, 
+        device: &Device, 
+        transform: &Transform2D,
+        bind_group: BindGroup
+    ) -> Option<RenderObject>{
+        let vertices = self.vertices();
+        if vertices.is_empty(){
+            return None;
         }
+        Some(textured_render_object(device, &vertices, transform, self.layer, Arc::new(bind_group), self.atlas.texture.target()))
     }
 }
 
@@ -78,11 +294,13 @@ pub struct ScreenDraw{
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u16>,
     pub material: Option<StandardMaterial>,
+    pub texture: Option<StrayTexture>,
+    pub layer: i32,
 }
 
 impl ScreenDraw{
-    pub fn init(x: i32, y: i32, rotation: i32) -> Self{
-        Self{transform: Transform2D::new(x, y, rotation, 1.0),vertices: vec![], indices: vec![], material: None}
+    pub fn init(x: f32, y: f32, rotation: f32) -> Self{
+        Self{transform: Transform2D::new(x, y, rotation, 1.0),vertices: vec![], indices: vec![], material: None, texture: None, layer: 0}
     }
 
     pub fn set_vertices(&mut self, vertices: Vec<Vertex> ){
@@ -100,6 +318,18 @@ impl ScreenDraw{
         }
     }
 
+    pub fn set_layer(&mut self, layer: i32){
+        self.layer = layer;
+        for v in self.vertices.iter_mut(){
+            v.set_layer(layer);
+        }
+    }
+
+    /// Sets texture which is sampled with texture coordinates of vertices
+    pub fn set_texture(&mut self, texture: StrayTexture){
+        self.texture = Some(texture);
+    }
+
     pub fn create_indices_buffer(&self, device: &Device) -> StrayIndexBuffer{
         let index_buffer = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
@@ -109,16 +339,11 @@ impl ScreenDraw{
             }
         );
         let index_buffer_len = self.indices.len() as u32;
-        StrayIndexBuffer(Some(index_buffer), index_buffer_len)
+        StrayIndexBuffer(Some(Arc::new(index_buffer)), index_buffer_len)
     }
 
-    pub fn create_vertex_buffer(&self, device: &Device, config: &SurfaceConfiguration) -> StrayVertexBuffer{
-        let raw_size = [config.width as i32,config.height as i32];
-        let true_transform = Transform2D { 
-            position: self.transform.position/vec2((config.width/2) as f32, (config.height/2) as f32), 
-            rotation: self.transform.rotation, 
-            scale: self.transform.scale };
-        let vertices: Vec<RawVertex> = self.vertices.iter().map(|x| x.to_raw(raw_size, true_transform)).collect();
+    pub fn create_vertex_buffer(&self, device: &Device) -> StrayVertexBuffer{
+        let vertices: Vec<RawVertex> = self.vertices.iter().map(|x| x.to_raw(&self.transform)).collect();
         let vertex_buffer = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
                 label: Some("Vertex Buffer"),
@@ -127,16 +352,345 @@ impl ScreenDraw{
             }
         );
         let vertex_buffer_len = self.vertices.len() as u32;
-        StrayVertexBuffer(Some(vertex_buffer), vertex_buffer_len)
+        StrayVertexBuffer(Some(Arc::new(vertex_buffer)), vertex_buffer_len)
     }
-    pub fn to_render_object(&self, device: &Device, config: &SurfaceConfiguration) -> RenderObject{
-        let vertex = self.create_vertex_buffer(&device, &config);
-        let index = self.create_indices_buffer(&device);
+    pub fn to_render_object(&self, device: &Device) -> RenderObject{
+        let vertex = self.create_vertex_buffer(device);
+        let index = self.create_indices_buffer(device);
         RenderObject{
             type_id: 0,
             vertex: Some(vertex),
             index: Some(index),
-            bind_group: None
+            bind_group: None,
+            source_targets: vec![],
+            layer: self.layer,
+            y: self.transform.position.y,
+            material: None,
+            material_bind_group: None,
+            normal_bind_group: None,
+        }
+    }
+
+    /// Same as ``to_render_object`` but draws vertices with texture pipeline
+    pub fn to_textured_render_object(&self, device: &Device, bind_group: Arc<BindGroup>) -> RenderObject{
+        let vertex = self.create_vertex_buffer(device);
+        let index = self.create_indices_buffer(device);
+        RenderObject{
+            type_id: 1,
+            vertex: Some(vertex),
+            index: Some(index),
+            bind_group: Some(bind_group),
+            source_targets: self.texture.iter().filter_map(|texture| texture.target()).collect(),
+            layer: self.layer,
+            y: self.transform.position.y,
+            material: None,
+            material_bind_group: None,
+            normal_bind_group: None,
+        }
+    }
+}
+
+
+/// Sprite drawn with custom material, texture of sprite is bound at ``@group(0)``
+pub struct MaterialSprite<M: Material2D>{
+    pub sprite: Sprite,
+    pub material: M,
+    // Bind groups are created again only when texture or material changes
+    pub bind_groups: MaterialBindGroups,
+}
+
+impl<M: Material2D> MaterialSprite<M>{
+    pub fn new(sprite: Sprite, material: M) -> Self{
+        Self{sprite, material, bind_groups: MaterialBindGroups::default()}
+    }
+}
+
+/// Geometry drawn with custom material, texture of draw or white texture is bound at ``@group(0)``
+pub struct MaterialDraw<M: Material2D>{
+    pub draw: ScreenDraw,
+    pub material: M,
+    // Bind groups are created again only when texture or material changes
+    pub bind_groups: MaterialBindGroups,
+}
+
+impl<M: Material2D> MaterialDraw<M>{
+    pub fn new(draw: ScreenDraw, material: M) -> Self{
+        Self{draw, material, bind_groups: MaterialBindGroups::default()}
+    }
+}
+
+/// Texture and material bind groups of ``MaterialSprite`` or ``MaterialDraw``,
+/// together with ids of render targets which are sampled by material
+pub type MaterialBindGroupSet = (Arc<BindGroup>, Arc<BindGroup>, Vec<u32>);
+
+/// Cache of material bind groups, they are reused while texture, uniform and textures of material
+/// stay the same and render targets they sample aren't reallocated
+#[derive(Default)]
+pub struct MaterialBindGroups{
+    texture: Option<StrayTexture>,
+    uniform: Vec<u8>,
+    textures: Vec<StrayTexture>,
+    // Allocations of sampled render targets, None for textures which aren't render targets
+    allocations: Vec<Option<u32>>,
+    bind_groups: Option<MaterialBindGroupSet>,
+}
+
+impl MaterialBindGroups{
+    /// Returns cached bind groups or creates them with ``create`` when something has changed.
+    /// Nothing is cached when ``create`` returns ``None``
+    pub fn get_or_create<M: Material2D>(
+        &mut self,
+        texture: &StrayTexture,
+        material: &M,
+        render_targets: &RenderTargets,
+        create: impl FnOnce() -> Option<(BindGroup, BindGroup, Vec<u32>)>
+    ) -> Option<MaterialBindGroupSet>{
+        let uniform = material.uniform();
+        let textures = material.textures();
+        let allocations: Vec<Option<u32>> = std::iter::once(texture).chain(textures.iter().copied())
+            .map(|texture| texture.target().and_then(|id| render_targets.get(id)).map(|target| target.allocation))
+            .collect();
+        let unchanged = self.bind_groups.is_some()
+            && self.texture.as_ref() == Some(texture)
+            && self.uniform == uniform
+            && self.textures.len() == textures.len()
+            && self.textures.iter().zip(textures.iter()).all(|(cached, texture)| cached == *texture)
+            && self.allocations == allocations;
+        if !unchanged{
+            let (bind_group, material_bind_group, source_targets) = create()?;
+            self.texture = Some(texture.clone());
+            self.uniform = uniform;
+            self.textures = textures.into_iter().cloned().collect();
+            self.allocations = allocations;
+            self.bind_groups = Some((Arc::new(bind_group), Arc::new(material_bind_group), source_targets));
         }
+        self.bind_groups.clone()
+    }
+}
+
+/// ## Overview
+/// Camera which decides what part of world is drawn.
+/// Position of camera is taken from ``Transform2D`` of the same entity.
+/// Camera without target renders to window, otherwise it renders to ``RenderTarget``
+/// which can be drawn by ``Sprite::from_target``
+///
+/// ## Example
+///
+/// ```ignore
+/// Stray::new()
+///     .push((Transform2D::new(100.0, 0.0, 0.0, 1.0), Camera2D::new()))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct Camera2D{
+    pub zoom: f32,
+    // Cameras with lower order are rendered first
+    pub order: i32,
+    pub target: Option<RenderTarget>,
+    // Overrides Background resource for this camera
+    pub background: Option<Background>,
+}
+
+impl Camera2D{
+    pub fn new() -> Self{
+        Self{zoom: 1.0, order: 0, target: None, background: None}
+    }
+
+    pub fn with_target(target: RenderTarget) -> Self{
+        Self{zoom: 1.0, order: -1, target: Some(target), background: None}
+    }
+
+    pub fn with_background(mut self, background: Background) -> Self{
+        self.background = Some(background);
+        self
+    }
+
+    /// Size of surface which camera renders into
+    pub fn target_size(&self, window_size: [u32;2]) -> [u32;2]{
+        match self.target{
+            Some(target) => [target.width.max(1), target.height.max(1)],
+            None => window_size
+        }
+    }
+
+    /// ## Overview
+    /// Matrix which maps world space in pixels to clip space of camera target.
+    /// It's orthographic projection of target size multiplied by inverse of camera transform,
+    /// so one world unit is one pixel when zoom is 1
+    /// 
+    /// ## Example
+    /// 
+    /// ```
+    /// use glam::*;
+    /// use stray_scene::*;
+    /// 
+    /// let camera = Camera2D::new();
+    /// let view_projection = camera.view_projection(&Transform2D::ZERO, [800, 600]);
+    /// 
+    /// // Top right corner of window
+    /// assert_eq!(view_projection.transform_point3(vec3(400.0, 300.0, 0.0)), vec3(1.0, 1.0, 0.0));
+    /// 
+    /// // Rotation is rigid, point keeps its distance in pixels after rotating by 90 degrees
+    /// let model = Transform2D::new(0.0, 0.0, deg2rad(90.0), 1.0).to_mat4();
+    /// let clip = (view_projection * model).transform_point3(vec3(100.0, 0.0, 0.0));
+    /// assert!(clip.x.abs() < 1e-5);
+    /// assert!((clip.y * 300.0 - 100.0).abs() < 1e-3);
+    /// 
+    /// // Camera moved to the right sees world shifted to the left
+    /// let view_projection = camera.view_projection(&Transform2D::new(400.0, 0.0, 0.0, 1.0), [800, 600]);
+    /// assert_eq!(view_projection.transform_point3(vec3(400.0, 0.0, 0.0)), vec3(0.0, 0.0, 0.0));
+    /// ```
+    pub fn view_projection(&self, transform: &Transform2D, window_size: [u32;2]) -> Mat4{
+        let target_size = self.target_size(window_size);
+        orthographic_projection(target_size[0] as f32, target_size[1] as f32)
+            * Mat4::from_scale(vec3(self.zoom, self.zoom, 1.0))
+            * transform.inverse().to_mat4()
+    }
+
+    /// Converts point in world to pixels of target, (0,0) is top left corner
+    pub fn world_to_viewport(&self, transform: &Transform2D, window_size: [u32;2], point: Vec2) -> Vec2{
+        let target_size = self.target_size(window_size);
+        let clip = self.view_projection(transform, window_size).transform_point3(point.extend(0.0));
+        vec2((clip.x + 1.0) * 0.5 * target_size[0] as f32, (1.0 - clip.y) * 0.5 * target_size[1] as f32)
+    }
+
+    /// Converts pixel of target, for example mouse position, to point in world
+    pub fn viewport_to_world(&self, transform: &Transform2D, window_size: [u32;2], point: Vec2) -> Vec2{
+        let target_size = self.target_size(window_size);
+        let clip = vec3(
+            point.x / target_size[0] as f32 * 2.0 - 1.0, 
+            1.0 - point.y / target_size[1] as f32 * 2.0,
+            0.0
+        );
+        self.view_projection(transform, window_size).inverse().transform_point3(clip).truncate()
     }
-}
\ No newline at end of file
+}
+
+/// Projection of area with given size in pixels, centered on (0,0).
+/// Depth of vertices is not changed
+pub fn orthographic_projection(width: f32, height: f32) -> Mat4{
+    Mat4::from_scale(vec3(2.0 / width.max(1.0), 2.0 / height.max(1.0), 1.0))
+}
+
+impl Default for Camera2D{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(test)]
+mod tests{
+    use glam::{vec4, Vec3};
+    use image::RgbaImage;
+
+    use super::*;
+    use crate::types::{deg2rad, layer_depth};
+
+    fn sprite(width: u32, height: u32) -> Sprite{
+        Sprite::from_texture(StrayTexture::with(RgbaImage::new(width, height), (width, height)), 3)
+    }
+
+    // Pixel of window where vertex ends up, the same way as vertex shader places it
+    fn to_screen(camera: &Camera2D, camera_transform: &Transform2D, window_size: [u32;2], raw: &RawVertex) -> Vec2{
+        let position = raw.position();
+        let clip = camera.view_projection(camera_transform, window_size) * raw.model() * vec4(position[0], position[1], position[2], 1.0);
+        vec2((clip.x / clip.w + 1.0) * 0.5 * window_size[0] as f32, (1.0 - clip.y / clip.w) * 0.5 * window_size[1] as f32)
+    }
+
+    fn world(raw: &RawVertex) -> Vec2{
+        raw.model().transform_point3(Vec3::from(raw.position())).truncate()
+    }
+
+    fn assert_near(a: Vec2, b: Vec2){
+        assert!((a - b).length() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn sprite_quad_is_placed_around_pivot(){
+        let vertices = sprite(64, 32).vertices((64, 32));
+        let positions: Vec<Vec2> = vertices.iter().map(|vertex| vertex.position()).collect();
+        assert_eq!(positions, vec![
+            vec2(-32.0, -16.0), vec2(32.0, -16.0), vec2(-32.0, 16.0),
+            vec2(-32.0, 16.0), vec2(32.0, -16.0), vec2(32.0, 16.0),
+        ]);
+        // Top of texture is at the top of quad
+        assert_eq!(vertices[2].tex_coords(), vec2(0.0, 0.0));
+        assert_eq!(vertices[1].tex_coords(), vec2(1.0, 1.0));
+
+        let vertices = sprite(64, 32).with_pivot(vec2(1.0, 1.0)).vertices((64, 32));
+        assert_eq!(vertices[0].position(), vec2(-64.0, 0.0));
+        assert_eq!(vertices[5].position(), vec2(0.0, 32.0));
+    }
+
+    #[test]
+    fn texture_vertex_is_moved_by_transform(){
+        let vertex = TextureVertex::new(10.0, 0.0, 5, 0.0, 0.0);
+        let raw = vertex.to_raw(&Transform2D::new(100.0, 50.0, 0.0, 1.0));
+        assert_eq!(raw.position(), [10.0, 0.0, layer_depth(5)]);
+        assert_near(world(&raw), vec2(110.0, 50.0));
+
+        let raw = vertex.to_raw(&Transform2D::new(100.0, 50.0, deg2rad(90.0), 1.0));
+        assert_near(world(&raw), vec2(100.0, 60.0));
+
+        let raw = vertex.to_raw(&Transform2D::new(100.0, 50.0, deg2rad(90.0), 3.0));
+        assert_near(world(&raw), vec2(100.0, 80.0));
+    }
+
+    #[test]
+    fn vertex_is_moved_by_transform(){
+        let vertex = Vertex::new(0, -20, -2);
+        let raw = vertex.to_raw(&Transform2D::new(-10.0, 0.0, deg2rad(180.0), 0.5));
+        assert_eq!(raw.position(), [0.0, -20.0, layer_depth(-2)]);
+        assert_near(world(&raw), vec2(-10.0, 10.0));
+        // Higher layers are drawn in front of lower ones
+        assert!(Vertex::new(0, 0, 1).to_raw(&Transform2D::ZERO).position()[2] < raw.position()[2]);
+    }
+
+    #[test]
+    fn rotated_sprite_keeps_its_size_in_non_square_window(){
+        let camera = Camera2D::new();
+        let window_size = [800, 600];
+        let sprite = sprite(100, 50).with_pivot(vec2(0.0, 0.5));
+        let transform = Transform2D::new(0.0, 0.0, deg2rad(90.0), 1.0);
+        let screen: Vec<Vec2> = sprite.vertices((100, 50)).iter()
+            .map(|vertex| to_screen(&camera, &Transform2D::ZERO, window_size, &vertex.to_raw(&transform)))
+            .collect();
+        // Pivot on left edge stays in center of window, sprite points up after rotation
+        assert_near((screen[0] + screen[2]) * 0.5, vec2(400.0, 300.0));
+        assert_near((screen[1] + screen[5]) * 0.5, vec2(400.0, 200.0));
+        assert!((screen[0].distance(screen[2]) - 50.0).abs() < 1e-3);
+        assert!((screen[0].distance(screen[1]) - 100.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn camera_zoom_and_position_move_vertices_on_screen(){
+        let camera = Camera2D{zoom: 2.0, ..Camera2D::new()};
+        let window_size = [640, 360];
+        let raw = TextureVertex::new(10.0, 0.0, 0, 0.0, 0.0).to_raw(&Transform2D::new(50.0, 20.0, 0.0, 1.0));
+        let camera_transform = Transform2D::new(50.0, 0.0, 0.0, 1.0);
+        assert_near(to_screen(&camera, &camera_transform, window_size, &raw), vec2(340.0, 140.0));
+        assert_near(camera.world_to_viewport(&camera_transform, window_size, world(&raw)), vec2(340.0, 140.0));
+    }
+
+    #[test]
+    fn viewport_round_trips_to_world(){
+        let window_size = [1280, 720];
+        let points = [vec2(0.0, 0.0), vec2(1280.0, 720.0), vec2(17.5, 600.25), vec2(640.0, 360.0)];
+        let cameras = [
+            (Camera2D::new(), Transform2D::ZERO),
+            (Camera2D{zoom: 0.5, ..Camera2D::new()}, Transform2D::new(-300.0, 120.0, deg2rad(30.0), 1.0)),
+            (Camera2D::with_target(RenderTarget::new(320, 200)), Transform2D::new(10.0, 10.0, deg2rad(-45.0), 2.0)),
+        ];
+        for (camera, transform) in cameras.iter(){
+            for point in points{
+                let world = camera.viewport_to_world(transform, window_size, point);
+                let viewport = camera.world_to_viewport(transform, window_size, world);
+                assert!((viewport - point).length() < 1e-2, "{:?} != {:?}", viewport, point);
+            }
+        }
+        // Center of window is position of camera, y of world points up
+        let transform = Transform2D::new(-300.0, 120.0, 0.0, 1.0);
+        assert_near(Camera2D::new().viewport_to_world(&transform, window_size, vec2(640.0, 360.0)), vec2(-300.0, 120.0));
+        assert_near(Camera2D::new().viewport_to_world(&transform, window_size, vec2(640.0, 0.0)), vec2(-300.0, 480.0));
+    }
+}
@@ -1,22 +1,50 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::{vec2, Vec2, Mat4};
 use wgpu::*;
 
-use stray_material::{
-    StandardMaterial,
-    StrayColor,
-};
+use stray_material::{StandardMaterial, StrayColor};
 
-use crate::types::Transform2D;
+use crate::types::{Transform2D, layer_depth};
+use crate::light::RawLight;
 
 
 pub struct RenderObject{
-    // 0 is normal drawing, 1 is texture drawing
+    // 0 is normal drawing, 1 is texture drawing, 2 is drawing with custom material, 3 is drawing of SDF shapes
     pub type_id: i32,
     pub vertex: Option<StrayVertexBuffer>,
     pub index: Option<StrayIndexBuffer>,
-    pub bind_group: Option<BindGroup>,
+    pub bind_group: Option<Arc<BindGroup>>,
+    // Ids of render targets which are sampled by this object, it's skipped while they're drawn into
+    pub source_targets: Vec<u32>,
+    // Objects are sorted by layer and then by y position, see LayerSort
+    pub layer: i32,
+    pub y: f32,
+    // Type of custom material, its pipeline is stored in MaterialPipelines
+    pub material: Option<TypeId>,
+    pub material_bind_group: Option<Arc<BindGroup>>,
+    // Normal map of texture drawing, flat normal is used without it
+    pub normal_bind_group: Option<BindGroup>,
 }
 
 impl RenderObject{
+    pub fn with_normal_map(mut self, normal_bind_group: BindGroup) -> Self{
+        self.normal_bind_group = Some(normal_bind_group);
+        self
+    }
+
+    /// Draws object with pipeline of custom material instead of texture pipeline,
+    /// ``source_targets`` are render targets sampled by textures of material
+    pub fn with_material(mut self, material: TypeId, material_bind_group: Arc<BindGroup>, source_targets: &[u32]) -> Self{
+        self.type_id = 2;
+        self.material = Some(material);
+        self.material_bind_group = Some(material_bind_group);
+        self.source_targets.extend_from_slice(source_targets);
+        self
+    }
+
     pub fn get_type(&self) -> i32{
         self.type_id
     }
@@ -34,8 +62,8 @@ impl RenderObject{
 
 #[derive(Debug, Copy, Clone)]
 pub struct TextureVertex{
-    x: i32,
-    y: i32,
+    x: f32,
+    y: f32,
     layer: i32,
     tex_coordx: f32,
     tex_coordy: f32,
@@ -43,26 +71,30 @@ pub struct TextureVertex{
 }
 
 impl TextureVertex{
-    pub fn new(x: i32, y: i32, layer: i32, tex_coordx: f32, tex_coordy: f32) -> Self{
-        Self{x: x, y: y, layer: layer, tex_coordx: tex_coordx, tex_coordy: tex_coordy, material: StandardMaterial::new(StrayColor::default())}
+    pub fn new(x: f32, y: f32, layer: i32, tex_coordx: f32, tex_coordy: f32) -> Self{
+        Self{x, y, layer, tex_coordx, tex_coordy, material: StandardMaterial::default()}
     }
 
-    pub fn to_raw(&self, win_size: [i32;2], transform: &Transform2D) -> RawVertex{
+    /// Position relative to transform, in pixels
+    pub fn position(&self) -> Vec2{
+        vec2(self.x, self.y)
+    }
+
+    pub fn tex_coords(&self) -> Vec2{
+        vec2(self.tex_coordx, self.tex_coordy)
+    }
+
+    pub fn to_raw(&self, transform: &Transform2D) -> RawVertex{
         RawVertex { 
             position: [
-                self.x as f32/win_size[0] as f32, 
-                self.y as f32/win_size[1] as f32, 
-                self.layer as f32
+                self.x, 
+                self.y, 
+                layer_depth(self.layer)
             ], 
             tex_coords: [
                 self.tex_coordx, self.tex_coordy
             ],
-            color: [
-                (((self.material.color.r / 255) as f32 + 0.055) / 1.055).powf(2.4), 
-                (((self.material.color.g / 255) as f32 + 0.055) / 1.055).powf(2.4), 
-                (((self.material.color.b / 255) as f32 + 0.055) / 1.055).powf(2.4), 
-                self.material.color.a
-            ],
+            color: self.material.color.to_linear(),
             model: transform.to_raw()
 
 
@@ -75,31 +107,39 @@ pub struct Vertex{
     pub x: i32,
     pub y: i32,
     layer: i32,
+    tex_coords: [f32;2],
     
     pub material: StandardMaterial,
 }
 
 impl Vertex{
     pub fn new(x: i32, y: i32, layer: i32) -> Self{
-        Self{x: x, y: y, layer: layer, material: StandardMaterial::new(StrayColor::default())}
+        Self{x, y, layer, tex_coords: [0.0, 0.0], material: StandardMaterial::default()}
+    }
+
+    pub fn layer(&self) -> i32{
+        self.layer
+    }
+
+    pub fn set_layer(&mut self, layer: i32){
+        self.layer = layer;
     }
 
-    pub fn to_raw(&self, win_size: [i32;2], transform: Transform2D) -> RawVertex{
+    /// Sets texture coordinates used when ``ScreenDraw`` has texture
+    pub fn with_tex_coords(mut self, u: f32, v: f32) -> Self{
+        self.tex_coords = [u, v];
+        self
+    }
+
+    pub fn to_raw(&self, transform: &Transform2D) -> RawVertex{
         RawVertex { 
             position: [
-                self.x as f32/win_size[0] as f32, 
-                self.y as f32/win_size[1] as f32, 
-                self.layer as f32
+                self.x as f32, 
+                self.y as f32, 
+                layer_depth(self.layer)
             ], 
-            tex_coords: [
-                0.,0.
-            ],
-            color: [
-                (((self.material.color.r / 255) as f32 + 0.055) / 1.055).powf(2.4), 
-                (((self.material.color.g / 255) as f32 + 0.055) / 1.055).powf(2.4), 
-                (((self.material.color.b / 255) as f32 + 0.055) / 1.055).powf(2.4), 
-                self.material.color.a
-            ],
+            tex_coords: self.tex_coords,
+            color: self.material.color.to_linear(),
             model: transform.to_raw()
 
 
@@ -117,6 +157,14 @@ pub struct RawVertex{
 }
 
 impl RawVertex{
+    pub fn position(&self) -> [f32;3]{
+        self.position
+    }
+
+    pub fn model(&self) -> Mat4{
+        Mat4::from_cols_array_2d(&self.model)
+    }
+
     const ATTRIBS: [VertexAttribute; 7] =
         vertex_attr_array![
             0 => Float32x3, 
@@ -138,12 +186,84 @@ impl RawVertex{
     }
 }
 
+/// Corner of quad of ``SdfShape``, shape is drawn from its parameters in fragment shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RawSdfVertex{
+    pub(crate) position: [f32;3],
+    pub(crate) color: [f32;4],
+    pub(crate) outline_color: [f32;4],
+    pub(crate) shadow_color: [f32;4],
+    // Kind and its parameters
+    pub(crate) shape: [f32;4],
+    // Outline width, shadow blur and shadow offset
+    pub(crate) style: [f32;4],
+    pub(crate) model: [[f32;4];4]
+}
+
+impl RawSdfVertex{
+    const ATTRIBS: [VertexAttribute; 10] =
+        vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x4,
+            2 => Float32x4,
+            3 => Float32x4,
+            4 => Float32x4,
+            5 => Float32x4,
+            // Matrix
+            6 => Float32x4,
+            7 => Float32x4,
+            8 => Float32x4,
+            9 => Float32x4
+        ];
+
+    pub fn desc<'a>() -> VertexBufferLayout<'a> {
+        VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as BufferAddress,
+            step_mode: VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
 
-pub struct StrayIndexBuffer(pub Option<Buffer>,pub u32);  
-pub struct StrayVertexBuffer(pub Option<Buffer>, pub u32);
+// Buffers are shared, so objects like chunks of tilemap can keep them between frames
+pub struct StrayIndexBuffer(pub Option<Arc<Buffer>>,pub u32);
+pub struct StrayVertexBuffer(pub Option<Arc<Buffer>>, pub u32);
 pub struct StrayTextureRenderPipeline(pub RenderPipeline, pub BindGroupLayout);
 pub struct StrayShapeRenderPipeline(pub RenderPipeline);
+pub struct StraySdfRenderPipeline(pub RenderPipeline);
+pub struct StrayBackgroundRenderPipeline(pub RenderPipeline);
+pub struct StrayCameraLayout(pub BindGroupLayout);
+/// Pipeline and material bind group layout of every ``Material2D`` type
+#[derive(Default)]
+pub struct MaterialPipelines(pub HashMap<TypeId, (RenderPipeline, BindGroupLayout)>);
 pub struct RenderQuery(pub Vec<RenderObject>);
+/// Lights and occluder edges of current frame
+pub struct LightQuery(pub Vec<RawLight>, pub Vec<[f32;4]>);
+/// Layout of lights bind group and normal map which is used by objects without normal map
+pub struct StrayLighting{
+    pub layout: BindGroupLayout,
+    pub flat_normal: BindGroup,
+}
+pub struct CameraQuery(pub Vec<RenderCamera>);
+
+/// Camera prepared for rendering, ``target`` is ``None`` when camera renders to window
+pub struct RenderCamera{
+    pub target: Option<u32>,
+    pub order: i32,
+    pub bind_group: BindGroup,
+    // Overrides background resource
+    pub background: Option<RenderBackground>,
+}
+
+/// Background prepared for rendering, ``clear`` is ``None`` when target isn't cleared.
+/// Gradients and textures are drawn as fullscreen quad after clearing
+pub struct RenderBackground{
+    pub clear: Option<StrayColor>,
+    pub vertex: Option<StrayVertexBuffer>,
+    pub bind_group: Option<BindGroup>,
+}
 pub struct EngineData<D>(pub D);
 pub struct PhysicData<D>(pub D);
 
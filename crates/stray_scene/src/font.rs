@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use glam::{vec2, Vec2};
+use image::{GrayImage, RgbaImage, Rgba};
+use wgpu::*;
+
+use stray_texture::{StrayTexture, TextureSampling};
+
+use crate::bmfont::BitmapFont;
+
+const ATLAS_SIZE: u32 = 512;
+// Empty pixels between glyphs, so linear filtering doesn't bleed neighbours
+const GLYPH_PADDING: u32 = 1;
+
+/// Glyph which is rasterized into atlas, positions are in pixels
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphInfo{
+    pub advance: f32,
+    // Offset of bitmap from pen position, y points up from baseline
+    pub offset: Vec2,
+    pub size: Vec2,
+    // Position and size of glyph in atlas
+    pub atlas_rect: (u32,u32,u32,u32),
+}
+
+/// Vertical metrics of font at some size, descent is negative
+#[derive(Debug, Clone, Copy)]
+pub struct FontMetrics{
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+impl FontMetrics{
+    pub fn line_height(&self) -> f32{
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+pub(crate) struct GlyphAtlas{
+    image: RgbaImage,
+    glyphs: HashMap<(char, u32), GlyphInfo>,
+    // Shelf packing, glyphs are placed in rows
+    cursor: (u32,u32),
+    row_height: u32,
+    uploaded: Option<(TextureView, Sampler)>,
+}
+
+impl GlyphAtlas{
+    /// Atlas of already packed glyphs, e.g. pages of bitmap font
+    pub(crate) fn from_image(image: RgbaImage) -> Self{
+        Self { image, glyphs: HashMap::new(), cursor: (0, 0), row_height: 0, uploaded: None }
+    }
+
+    fn new() -> Self{
+        Self {
+            image: RgbaImage::from_pixel(ATLAS_SIZE, ATLAS_SIZE, Rgba([255, 255, 255, 0])),
+            glyphs: HashMap::new(),
+            cursor: (GLYPH_PADDING, GLYPH_PADDING),
+            row_height: 0,
+            uploaded: None
+        }
+    }
+
+    /// Finds free place for bitmap, atlas grows in height when it's full
+    fn allocate(&mut self, width: u32, height: u32) -> (u32,u32){
+        if self.cursor.0 + width + GLYPH_PADDING > self.image.width(){
+            self.cursor = (GLYPH_PADDING, self.cursor.1 + self.row_height + GLYPH_PADDING);
+            self.row_height = 0;
+        }
+        while self.cursor.1 + height + GLYPH_PADDING > self.image.height(){
+            let mut image = RgbaImage::from_pixel(self.image.width(), self.image.height() * 2, Rgba([255, 255, 255, 0]));
+            image::imageops::replace(&mut image, &self.image, 0, 0);
+            self.image = image;
+        }
+        let position = self.cursor;
+        self.cursor.0 += width + GLYPH_PADDING;
+        self.row_height = self.row_height.max(height);
+        position
+    }
+
+    fn insert(&mut self, coverage: &GrayImage) -> (u32,u32){
+        let position = self.allocate(coverage.width(), coverage.height());
+        for (x, y, pixel) in coverage.enumerate_pixels(){
+            self.image.put_pixel(position.0 + x, position.1 + y, Rgba([255, 255, 255, pixel.0[0]]));
+        }
+        self.uploaded = None;
+        position
+    }
+}
+
+enum FontSource{
+    Vector(fontdue::Font),
+    Bitmap(BitmapFont),
+}
+
+struct FontData{
+    source: FontSource,
+    atlas: Mutex<GlyphAtlas>,
+    sampling: TextureSampling,
+}
+
+/// ## Overview
+/// TTF or OTF font, glyphs are rasterized into atlas when they're drawn for the first time.
+/// Font is cheap to clone, clones share the same atlas, so one font should be loaded once
+/// and cloned into ``Text2D`` components.
+///
+/// Bitmap fonts in BMFont format are loaded by ``Font::from_bmfont``, they're drawn with
+/// nearest filtering and look crisp when text size is multiple of font size
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+///
+/// let font = Font::new(include_bytes!("../../../examples/fonts/Cantarell-Regular.ttf")).unwrap();
+/// let size = font.measure("Score: 100", 32.0);
+/// ```
+#[derive(Clone)]
+pub struct Font(Arc<FontData>);
+
+impl Font{
+    pub fn new(bytes: &[u8]) -> Result<Self, &'static str>{
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())?;
+        let sampling = TextureSampling { mag_filter: FilterMode::Linear, min_filter: FilterMode::Linear, address_mode: AddressMode::ClampToEdge };
+        Ok(Self(Arc::new(FontData { source: FontSource::Vector(font), atlas: Mutex::new(GlyphAtlas::new()), sampling })))
+    }
+
+    /// ## Overview
+    /// Loads AngelCode BMFont, ``fnt`` is text or binary descriptor and ``pages`` are
+    /// images of its pages ordered by page id
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stray_scene::*;
+    ///
+    /// let font = Font::from_bmfont(include_bytes!("../../../examples/fonts/pixel.fnt"), &[include_bytes!("../../../examples/fonts/pixel_0.png")]).unwrap();
+    /// let text = Text2D::new("Game Over", font, 32.0);
+    /// ```
+    pub fn from_bmfont(fnt: &[u8], pages: &[&[u8]]) -> Result<Self, &'static str>{
+        let (font, atlas) = BitmapFont::parse(fnt, pages)?;
+        Ok(Self(Arc::new(FontData { source: FontSource::Bitmap(font), atlas: Mutex::new(GlyphAtlas::from_image(atlas)), sampling: TextureSampling::NEAREST })))
+    }
+
+    /// Returns true if both fonts are clones of the same loaded font and share atlas
+    pub fn ptr_eq(&self, other: &Font) -> bool{
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Size which font is designed for, ``None`` for vector fonts which can be drawn in any size
+    pub fn native_size(&self) -> Option<f32>{
+        match &self.0.source{
+            FontSource::Vector(_) => None,
+            FontSource::Bitmap(font) => Some(font.size)
+        }
+    }
+
+    pub fn metrics(&self, size: f32) -> FontMetrics{
+        match &self.0.source{
+            FontSource::Vector(font) => match font.horizontal_line_metrics(size){
+                Some(metrics) => FontMetrics { ascent: metrics.ascent, descent: metrics.descent, line_gap: metrics.line_gap },
+                None => FontMetrics { ascent: size, descent: 0.0, line_gap: 0.0 }
+            },
+            FontSource::Bitmap(font) => font.metrics(size)
+        }
+    }
+
+    /// Distance between baselines of two lines
+    pub fn line_height(&self, size: f32) -> f32{
+        self.metrics(size).line_height()
+    }
+
+    /// Horizontal advance of character without rasterizing it
+    pub fn advance(&self, character: char, size: f32) -> f32{
+        match &self.0.source{
+            FontSource::Vector(font) => font.metrics(character, size).advance_width,
+            FontSource::Bitmap(font) => font.advance(character, size)
+        }
+    }
+
+    pub fn kerning(&self, left: char, right: char, size: f32) -> f32{
+        match &self.0.source{
+            FontSource::Vector(font) => font.horizontal_kern(left, right, size).unwrap_or(0.0),
+            FontSource::Bitmap(font) => font.kerning(left, right, size)
+        }
+    }
+
+    /// Size of single line of text in pixels, new lines aren't handled, see ``Text2D::measure``
+    pub fn measure(&self, text: &str, size: f32) -> Vec2{
+        let mut width = 0.0;
+        let mut previous: Option<char> = None;
+        for character in text.chars(){
+            if let Some(previous) = previous{
+                width += self.kerning(previous, character, size);
+            }
+            width += self.advance(character, size);
+            previous = Some(character);
+        }
+        vec2(width, self.line_height(size))
+    }
+
+    /// Returns glyph from atlas, rasterizes it when it isn't there yet
+    pub fn glyph(&self, character: char, size: f32) -> GlyphInfo{
+        let font = match &self.0.source{
+            FontSource::Vector(font) => font,
+            FontSource::Bitmap(font) => return font.glyph(character, size)
+        };
+        let mut atlas = self.0.atlas.lock().unwrap();
+        let key = (character, size.to_bits());
+        if let Some(glyph) = atlas.glyphs.get(&key){
+            return *glyph;
+        }
+        let (metrics, bitmap) = font.rasterize(character, size);
+        let (x, y) = match GrayImage::from_raw(metrics.width as u32, metrics.height as u32, bitmap){
+            Some(coverage) if metrics.width > 0 && metrics.height > 0 => atlas.insert(&coverage),
+            _ => (0, 0)
+        };
+        let glyph = GlyphInfo {
+            advance: metrics.advance_width,
+            offset: vec2(metrics.xmin as f32, metrics.ymin as f32),
+            size: vec2(metrics.width as f32, metrics.height as f32),
+            atlas_rect: (x, y, metrics.width as u32, metrics.height as u32),
+        };
+        atlas.glyphs.insert(key, glyph);
+        glyph
+    }
+
+    /// Size of atlas texture, it grows when glyphs don't fit
+    pub fn atlas_size(&self) -> (u32,u32){
+        self.0.atlas.lock().unwrap().image.dimensions()
+    }
+
+    /// Copy of atlas, white image where alpha is coverage of glyphs
+    pub fn atlas_texture(&self) -> StrayTexture{
+        let atlas = self.0.atlas.lock().unwrap();
+        StrayTexture::with(atlas.image.clone(), atlas.image.dimensions()).with_sampling(self.0.sampling)
+    }
+
+    /// Creates bind group of atlas, atlas is uploaded again only when new glyphs were added
+    pub fn bind_group(&self, device: &Device, queue: &Queue, layout: &BindGroupLayout) -> BindGroup{
+        let mut atlas = self.0.atlas.lock().unwrap();
+        if atlas.uploaded.is_none(){
+            let texture = StrayTexture::with(atlas.image.clone(), atlas.image.dimensions()).with_sampling(self.0.sampling);
+            atlas.uploaded = Some(texture.upload(device, queue));
+        }
+        let (view, sampler) = atlas.uploaded.as_ref().unwrap();
+        device.create_bind_group(&BindGroupDescriptor {
+            layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                }
+            ],
+            label: Some("font_atlas_bind_group"),
+        })
+    }
+}
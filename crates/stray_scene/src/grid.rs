@@ -0,0 +1,464 @@
+use std::ops::{Add, Sub, Mul};
+
+use glam::{ivec2, ivec3, vec2, IVec2, IVec3, Vec2};
+
+use crate::types::Transform2D;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// How cells of isometric grid are numbered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsoLayout{
+    // Map is diamond, x goes down-right and y goes down-left like in isometric Tilemap
+    #[default]
+    Diamond,
+    // Map is rectangle of half-height rows, odd rows are shifted right by half of tile
+    Staggered,
+}
+
+/// ## Overview
+/// Coordinates of isometric grid. Positions are local to transform of grid with y pointing up,
+/// diamond grid matches ``Tilemap`` with isometric orientation and the same tile size.
+/// Distance, rings and lines count steps between cells which share edge
+///
+/// Sprites drawn in ``LayerSort::YSort`` are ordered correctly when they stand on bottom of their cell,
+/// ``draw_order`` gives the same order for things drawn manually
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+/// use glam::{ivec2, vec2};
+///
+/// let grid = IsoGrid::new(vec2(64.0, 32.0));
+/// let center = grid.cell_to_local(ivec2(2, 1));
+/// assert_eq!(center, vec2(32.0, -64.0));
+/// assert_eq!(grid.local_to_cell(center), ivec2(2, 1));
+/// assert_eq!(grid.distance(ivec2(0, 0), ivec2(2, -1)), 3);
+/// assert_eq!(grid.ring(ivec2(0, 0), 2).len(), 8);
+///
+/// let staggered = IsoGrid::staggered(vec2(64.0, 32.0));
+/// // Odd row is shifted, so cell below-right of (0, 0) is (0, 1)
+/// assert!(staggered.neighbours(ivec2(0, 0)).contains(&ivec2(0, 1)));
+/// assert_eq!(staggered.local_to_cell(staggered.cell_to_local(ivec2(3, 5))), ivec2(3, 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IsoGrid{
+    pub layout: IsoLayout,
+    // Width and height of diamond of cell
+    pub tile_size: Vec2,
+}
+
+impl IsoGrid{
+    pub fn new(tile_size: Vec2) -> Self{
+        Self { layout: IsoLayout::Diamond, tile_size }
+    }
+
+    pub fn staggered(tile_size: Vec2) -> Self{
+        Self { layout: IsoLayout::Staggered, tile_size }
+    }
+
+    /// Center of cell
+    pub fn cell_to_local(&self, cell: IVec2) -> Vec2{
+        let diamond = self.to_diamond(cell);
+        let center = vec2(
+            (diamond.x - diamond.y) as f32 * self.tile_size.x / 2.0,
+            -(diamond.x + diamond.y + 1) as f32 * self.tile_size.y / 2.0
+        );
+        center + self.origin()
+    }
+
+    /// Cell which contains point
+    pub fn local_to_cell(&self, position: Vec2) -> IVec2{
+        let position = position - self.origin();
+        let (column, row) = (position.x / (self.tile_size.x / 2.0), -position.y / (self.tile_size.y / 2.0));
+        let diamond = ivec2(((row + column) / 2.0).floor() as i32, ((row - column) / 2.0).floor() as i32);
+        self.cell_from_diamond(diamond)
+    }
+
+    pub fn cell_to_world(&self, transform: &Transform2D, cell: IVec2) -> Vec2{
+        transform.transform_point(self.cell_to_local(cell))
+    }
+
+    pub fn world_to_cell(&self, transform: &Transform2D, position: Vec2) -> IVec2{
+        self.local_to_cell(transform.inverse().transform_point(position))
+    }
+
+    /// Corners of diamond of cell, top, right, bottom and left
+    pub fn corners(&self, cell: IVec2) -> [Vec2;4]{
+        let center = self.cell_to_local(cell);
+        let half = self.tile_size / 2.0;
+        [center + vec2(0.0, half.y), center + vec2(half.x, 0.0), center - vec2(0.0, half.y), center - vec2(half.x, 0.0)]
+    }
+
+    /// Cells which share edge with cell, up-right, down-right, down-left and up-left
+    pub fn neighbours(&self, cell: IVec2) -> [IVec2;4]{
+        let diamond = self.to_diamond(cell);
+        [ivec2(0, -1), ivec2(1, 0), ivec2(0, 1), ivec2(-1, 0)].map(|step| self.cell_from_diamond(diamond + step))
+    }
+
+    /// Cells which share edge or corner with cell
+    pub fn neighbours8(&self, cell: IVec2) -> [IVec2;8]{
+        let diamond = self.to_diamond(cell);
+        [ivec2(0, -1), ivec2(1, -1), ivec2(1, 0), ivec2(1, 1), ivec2(0, 1), ivec2(-1, 1), ivec2(-1, 0), ivec2(-1, -1)]
+            .map(|step| self.cell_from_diamond(diamond + step))
+    }
+
+    /// Number of steps through edges between cells
+    pub fn distance(&self, a: IVec2, b: IVec2) -> i32{
+        let delta = self.to_diamond(b) - self.to_diamond(a);
+        delta.x.abs() + delta.y.abs()
+    }
+
+    /// Cells at distance from center, empty for negative radius
+    pub fn ring(&self, center: IVec2, radius: i32) -> Vec<IVec2>{
+        if radius < 0{
+            return vec![];
+        }
+        if radius == 0{
+            return vec![center];
+        }
+        let diamond = self.to_diamond(center);
+        let mut cells = Vec::with_capacity(radius as usize * 4);
+        for i in 0..radius{
+            let j = radius - i;
+            for step in [ivec2(i, -j), ivec2(j, i), ivec2(-i, j), ivec2(-j, -i)]{
+                cells.push(self.cell_from_diamond(diamond + step));
+            }
+        }
+        cells
+    }
+
+    /// Cells between two cells including both, every cell shares edge with previous one
+    pub fn line(&self, from: IVec2, to: IVec2) -> Vec<IVec2>{
+        let (start, end) = (self.to_diamond(from), self.to_diamond(to));
+        let delta = end - start;
+        let (steps_x, steps_y) = (delta.x.abs(), delta.y.abs());
+        let (sign_x, sign_y) = (delta.x.signum(), delta.y.signum());
+        let mut cell = start;
+        let mut cells = vec![self.cell_from_diamond(cell)];
+        let (mut x, mut y) = (0, 0);
+        while x < steps_x || y < steps_y{
+            // Step along axis which is further behind the ideal line
+            if (1 + 2 * x) * steps_y < (1 + 2 * y) * steps_x{
+                cell.x += sign_x;
+                x += 1;
+            }
+            else{
+                cell.y += sign_y;
+                y += 1;
+            }
+            cells.push(self.cell_from_diamond(cell));
+        }
+        cells
+    }
+
+    /// Key of cell for drawing, cells with lower key are further and are drawn first
+    pub fn draw_order(&self, cell: IVec2) -> i32{
+        let diamond = self.to_diamond(cell);
+        diamond.x + diamond.y
+    }
+
+    /// Sorts cells from back to front
+    pub fn sort_back_to_front(&self, cells: &mut [IVec2]){
+        cells.sort_by_key(|cell| {
+            let diamond = self.to_diamond(*cell);
+            (diamond.x + diamond.y, diamond.x)
+        });
+    }
+
+    /// Position of diamond grid whose cell (0, 0) is at the same place
+    fn origin(&self) -> Vec2{
+        match self.layout{
+            IsoLayout::Diamond => Vec2::ZERO,
+            IsoLayout::Staggered => vec2(self.tile_size.x / 2.0, 0.0)
+        }
+    }
+
+    fn to_diamond(self, cell: IVec2) -> IVec2{
+        match self.layout{
+            IsoLayout::Diamond => cell,
+            IsoLayout::Staggered => {
+                let (column, row) = (cell.x, cell.y);
+                let shift = row & 1;
+                ivec2((row + 2 * column + shift) / 2, (row - 2 * column - shift) / 2)
+            }
+        }
+    }
+
+    fn cell_from_diamond(&self, cell: IVec2) -> IVec2{
+        match self.layout{
+            IsoLayout::Diamond => cell,
+            IsoLayout::Staggered => {
+                let row = cell.x + cell.y;
+                ivec2((cell.x - cell.y - (row & 1)).div_euclid(2), row)
+            }
+        }
+    }
+}
+
+/// Direction of corner at top of hexagon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HexOrientation{
+    // Corner points up, rows are horizontal
+    #[default]
+    Pointy,
+    // Edge is at top, columns are vertical
+    Flat,
+}
+
+/// Which rows of pointy or columns of flat grid are shifted in offset coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HexOffset{
+    #[default]
+    Odd,
+    Even,
+}
+
+/// Steps to 6 neighbours in axial coordinates, counter-clockwise from east of pointy grid
+pub const HEX_DIRECTIONS: [Hex;6] = [
+    Hex::new(1, 0), Hex::new(1, -1), Hex::new(0, -1), Hex::new(-1, 0), Hex::new(-1, 1), Hex::new(0, 1)
+];
+
+/// ## Overview
+/// Cell of hexagonal grid in axial coordinates, third cube coordinate is ``s = -q - r``.
+/// ``r`` grows down on screen like rows of offset coordinates
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+///
+/// let a = Hex::new(0, 0);
+/// let b = Hex::new(3, -1);
+/// assert_eq!(a.distance(b), 3);
+/// assert_eq!(a.line_to(b).len(), 4);
+/// assert_eq!(a.ring(2).len(), 12);
+/// assert_eq!(a.spiral(2).len(), 19);
+/// assert_eq!(Hex::from_cube(b.to_cube()), b);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hex{
+    pub q: i32,
+    pub r: i32,
+}
+
+impl Hex{
+    pub const fn new(q: i32, r: i32) -> Self{
+        Self { q, r }
+    }
+
+    pub fn s(&self) -> i32{
+        -self.q - self.r
+    }
+
+    pub fn to_cube(&self) -> IVec3{
+        ivec3(self.q, self.r, self.s())
+    }
+
+    /// Cube coordinates must sum to zero, ``z`` is ignored
+    pub fn from_cube(cube: IVec3) -> Self{
+        Self::new(cube.x, cube.y)
+    }
+
+    /// Hex which contains fractional axial coordinates
+    pub fn round(q: f32, r: f32) -> Self{
+        let s = -q - r;
+        let (mut rounded_q, mut rounded_r, rounded_s) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rounded_q - q).abs(), (rounded_r - r).abs(), (rounded_s - s).abs());
+        if dq > dr && dq > ds{
+            rounded_q = -rounded_r - rounded_s;
+        }
+        else if dr > ds{
+            rounded_r = -rounded_q - rounded_s;
+        }
+        Self::new(rounded_q as i32, rounded_r as i32)
+    }
+
+    /// Neighbour in one of ``HEX_DIRECTIONS``
+    pub fn neighbour(&self, direction: usize) -> Self{
+        *self + HEX_DIRECTIONS[direction % 6]
+    }
+
+    pub fn neighbours(&self) -> [Self;6]{
+        HEX_DIRECTIONS.map(|direction| *self + direction)
+    }
+
+    pub fn distance(&self, other: Self) -> i32{
+        let delta = *self - other;
+        (delta.q.abs() + delta.r.abs() + delta.s().abs()) / 2
+    }
+
+    /// Hexes at distance from this one, empty for negative radius
+    pub fn ring(&self, radius: i32) -> Vec<Self>{
+        if radius < 0{
+            return vec![];
+        }
+        if radius == 0{
+            return vec![*self];
+        }
+        let mut hex = *self + HEX_DIRECTIONS[4] * radius;
+        let mut hexes = Vec::with_capacity(radius as usize * 6);
+        for direction in 0..6{
+            for _ in 0..radius{
+                hexes.push(hex);
+                hex = hex.neighbour(direction);
+            }
+        }
+        hexes
+    }
+
+    /// This hex and rings around it up to radius
+    pub fn spiral(&self, radius: i32) -> Vec<Self>{
+        (0..=radius).flat_map(|radius| self.ring(radius)).collect()
+    }
+
+    /// Hexes between two hexes including both
+    pub fn line_to(&self, other: Self) -> Vec<Self>{
+        let steps = self.distance(other);
+        // Nudge keeps points on edges between hexes on one side
+        let (q, r) = (self.q as f32 + 1e-6, self.r as f32 + 1e-6);
+        (0..=steps).map(|step| {
+            let t = if steps == 0 { 0.0 } else { step as f32 / steps as f32 };
+            Self::round(q + (other.q as f32 - q) * t, r + (other.r as f32 - r) * t)
+        }).collect()
+    }
+
+    /// Offset coordinates of cell in rectangular map, x is column and y is row
+    pub fn to_offset(&self, orientation: HexOrientation, offset: HexOffset) -> IVec2{
+        let shift = |value: i32| match offset{
+            HexOffset::Odd => (value - (value & 1)) / 2,
+            HexOffset::Even => (value + (value & 1)) / 2
+        };
+        match orientation{
+            HexOrientation::Pointy => ivec2(self.q + shift(self.r), self.r),
+            HexOrientation::Flat => ivec2(self.q, self.r + shift(self.q))
+        }
+    }
+
+    pub fn from_offset(cell: IVec2, orientation: HexOrientation, offset: HexOffset) -> Self{
+        let shift = |value: i32| match offset{
+            HexOffset::Odd => (value - (value & 1)) / 2,
+            HexOffset::Even => (value + (value & 1)) / 2
+        };
+        match orientation{
+            HexOrientation::Pointy => Self::new(cell.x - shift(cell.y), cell.y),
+            HexOrientation::Flat => Self::new(cell.x, cell.y - shift(cell.x))
+        }
+    }
+}
+
+impl Add for Hex{
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self{
+        Self::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for Hex{
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self{
+        Self::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<i32> for Hex{
+    type Output = Self;
+
+    fn mul(self, factor: i32) -> Self{
+        Self::new(self.q * factor, self.r * factor)
+    }
+}
+
+/// ## Overview
+/// Positions of hexagonal grid, hex (0, 0) is centered at origin of transform and y points up.
+/// ``size`` is distance from center to corner, it can differ on axes for squashed hexagons
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+/// use glam::{ivec2, vec2};
+///
+/// let grid = HexGrid::new(HexOrientation::Flat, vec2(20.0, 20.0));
+/// let hex = grid.offset_to_hex(ivec2(3, 2));
+/// assert_eq!(grid.hex_to_offset(hex), ivec2(3, 2));
+/// assert_eq!(grid.local_to_hex(grid.hex_to_local(hex) + vec2(5.0, 5.0)), hex);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexGrid{
+    pub orientation: HexOrientation,
+    pub size: Vec2,
+    // Shifted rows or columns of offset coordinates
+    pub offset: HexOffset,
+}
+
+impl HexGrid{
+    pub fn new(orientation: HexOrientation, size: Vec2) -> Self{
+        Self { orientation, size, offset: HexOffset::Odd }
+    }
+
+    pub fn with_offset(mut self, offset: HexOffset) -> Self{
+        self.offset = offset;
+        self
+    }
+
+    /// Distance between centers of neighbouring columns and rows
+    pub fn spacing(&self) -> Vec2{
+        match self.orientation{
+            HexOrientation::Pointy => vec2(SQRT_3 * self.size.x, 1.5 * self.size.y),
+            HexOrientation::Flat => vec2(1.5 * self.size.x, SQRT_3 * self.size.y)
+        }
+    }
+
+    /// Center of hex
+    pub fn hex_to_local(&self, hex: Hex) -> Vec2{
+        let (q, r) = (hex.q as f32, hex.r as f32);
+        let (x, y) = match self.orientation{
+            HexOrientation::Pointy => (SQRT_3 * q + SQRT_3 / 2.0 * r, 1.5 * r),
+            HexOrientation::Flat => (1.5 * q, SQRT_3 / 2.0 * q + SQRT_3 * r)
+        };
+        vec2(x * self.size.x, -y * self.size.y)
+    }
+
+    /// Hex which contains point
+    pub fn local_to_hex(&self, position: Vec2) -> Hex{
+        let (x, y) = (position.x / self.size.x, -position.y / self.size.y);
+        match self.orientation{
+            HexOrientation::Pointy => Hex::round(SQRT_3 / 3.0 * x - y / 3.0, 2.0 / 3.0 * y),
+            HexOrientation::Flat => Hex::round(2.0 / 3.0 * x, -x / 3.0 + SQRT_3 / 3.0 * y)
+        }
+    }
+
+    pub fn hex_to_world(&self, transform: &Transform2D, hex: Hex) -> Vec2{
+        transform.transform_point(self.hex_to_local(hex))
+    }
+
+    pub fn world_to_hex(&self, transform: &Transform2D, position: Vec2) -> Hex{
+        self.local_to_hex(transform.inverse().transform_point(position))
+    }
+
+    /// Corners of hex counter-clockwise
+    pub fn corners(&self, hex: Hex) -> [Vec2;6]{
+        let center = self.hex_to_local(hex);
+        let start = match self.orientation{
+            HexOrientation::Pointy => 30.0f32,
+            HexOrientation::Flat => 0.0
+        };
+        [0, 1, 2, 3, 4, 5].map(|corner| {
+            let angle = (start + 60.0 * corner as f32).to_radians();
+            center + vec2(angle.cos() * self.size.x, angle.sin() * self.size.y)
+        })
+    }
+
+    /// Hex of column and row of rectangular map
+    pub fn offset_to_hex(&self, cell: IVec2) -> Hex{
+        Hex::from_offset(cell, self.orientation, self.offset)
+    }
+
+    pub fn hex_to_offset(&self, hex: Hex) -> IVec2{
+        hex.to_offset(self.orientation, self.offset)
+    }
+}
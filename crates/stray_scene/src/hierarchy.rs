@@ -0,0 +1,265 @@
+use legion::{
+    *,
+    systems::CommandBuffer,
+};
+
+use crate::types::Transform2D;
+
+/// Entity which transform of this entity is relative to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parent(pub Entity);
+
+/// Entities which have this entity as ``Parent``
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Children(pub Vec<Entity>);
+
+/// ## Overview
+/// Transform of entity in world space.
+/// It's computed from ``Transform2D`` of entity and its parents before rendering,
+/// so changing it has no effect, change ``Transform2D`` instead
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlobalTransform2D(pub Transform2D);
+
+/// Computes world transform of entity by walking up through its parents
+pub fn world_transform(world: &World, entity: Entity) -> Option<Transform2D>{
+    let entry = world.entry_ref(entity).ok()?;
+    let local = *entry.get_component::<Transform2D>().ok()?;
+    match entry.get_component::<Parent>(){
+        Ok(parent) => Some(world_transform(world, parent.0).unwrap_or(Transform2D::ZERO).mul_transform(&local)),
+        Err(_) => Some(local)
+    }
+}
+
+/// Returns true if ``ancestor`` is ``entity`` or one of its parents
+pub fn is_ancestor(world: &World, ancestor: Entity, entity: Entity) -> bool{
+    let mut current = Some(entity);
+    while let Some(entity) = current{
+        if entity == ancestor{
+            return true;
+        }
+        current = world.entry_ref(entity).ok()
+            .and_then(|entry| entry.get_component::<Parent>().ok().map(|parent| parent.0));
+    }
+    false
+}
+
+/// ## Overview
+/// Changes parent of entity, ``None`` makes entity a root.
+/// Local transform is recalculated, so entity keeps its position in world.
+/// Returns false and doesn't change anything when parent is entity itself or one of its descendants
+/// 
+/// ## Example
+/// 
+/// ```
+/// use legion::World;
+/// use stray_scene::*;
+///
+/// let mut world = World::default();
+/// let player = world.push((Transform2D::new(100.0, 0.0, 0.0, 1.0),));
+/// let gun = world.push((Transform2D::new(120.0, 0.0, 0.0, 1.0),));
+/// assert!(set_parent(&mut world, gun, Some(player)));
+/// // Transform2D of gun is now (20, 0)
+/// let local = *world.entry(gun).unwrap().get_component::<Transform2D>().unwrap();
+/// assert_eq!(local.position.x, 20.0);
+/// // Player can't become child of its own gun
+/// assert!(!set_parent(&mut world, player, Some(gun)));
+/// ```
+pub fn set_parent(world: &mut World, child: Entity, parent: Option<Entity>) -> bool{
+    if let Some(parent) = parent{
+        if is_ancestor(world, child, parent){
+            return false;
+        }
+    }
+    let child_global = world_transform(world, child).unwrap_or(Transform2D::ZERO);
+    let parent_global = parent
+        .and_then(|parent| world_transform(world, parent))
+        .unwrap_or(Transform2D::ZERO);
+    detach(world, child);
+
+    let mut entry = match world.entry(child){
+        Some(entry) => entry,
+        None => return false
+    };
+    let parent = match parent{
+        Some(parent) => parent,
+        None => {
+            entry.add_component(child_global);
+            entry.remove_component::<GlobalTransform2D>();
+            return true;
+        }
+    };
+    entry.add_component(Parent(parent));
+    entry.add_component(parent_global.inverse().mul_transform(&child_global));
+    entry.add_component(GlobalTransform2D(child_global));
+
+    if let Some(mut parent_entry) = world.entry(parent){
+        match parent_entry.get_component_mut::<Children>(){
+            Ok(children) => children.0.push(child),
+            Err(_) => parent_entry.add_component(Children(vec![child]))
+        }
+    }
+    true
+}
+
+/// Removes entity and all of its descendants from world
+pub fn despawn_recursive(world: &mut World, entity: Entity){
+    detach(world, entity);
+    despawn_children(world, entity);
+}
+
+fn despawn_children(world: &mut World, entity: Entity){
+    let children = world.entry_ref(entity).ok()
+        .and_then(|entry| entry.get_component::<Children>().ok().cloned())
+        .unwrap_or_default();
+    for child in children.0{
+        despawn_children(world, child);
+    }
+    world.remove(entity);
+}
+
+// Removes entity from children of its current parent
+fn detach(world: &mut World, child: Entity){
+    let parent = match world.entry_ref(child).ok().and_then(|entry| entry.get_component::<Parent>().ok().copied()){
+        Some(parent) => parent.0,
+        None => return
+    };
+    if let Some(mut entry) = world.entry(parent){
+        if let Ok(children) = entry.get_component_mut::<Children>(){
+            children.0.retain(|entity| *entity != child);
+        }
+    }
+    if let Some(mut entry) = world.entry(child){
+        entry.remove_component::<Parent>();
+    }
+}
+
+/// Hierarchy operations which can be used from systems
+/// 
+/// ## Example
+/// 
+/// ```
+/// use legion::{*, systems::CommandBuffer};
+/// use stray_scene::*;
+///
+/// struct Player;
+/// struct Gun;
+///
+/// #[system]
+/// fn spawn_player(cmd: &mut CommandBuffer){
+///     let player = cmd.push((Player, Transform2D::ZERO));
+///     let gun = cmd.push((Gun, Transform2D::new(20.0, 0.0, 0.0, 1.0)));
+///     cmd.set_parent(gun, Some(player));
+/// }
+///
+/// let mut world = World::default();
+/// let mut schedule = Schedule::builder().add_system(spawn_player_system()).build();
+/// schedule.execute(&mut world, &mut Resources::default());
+/// assert_eq!(<&Parent>::query().iter(&world).count(), 1);
+/// ```
+pub trait HierarchyCommands{
+    fn set_parent(&mut self, child: Entity, parent: Option<Entity>);
+    fn despawn_recursive(&mut self, entity: Entity);
+}
+
+impl HierarchyCommands for CommandBuffer{
+    fn set_parent(&mut self, child: Entity, parent: Option<Entity>){
+        self.exec_mut(move |world, _| {
+            set_parent(world, child, parent);
+        });
+    }
+
+    fn despawn_recursive(&mut self, entity: Entity){
+        self.exec_mut(move |world, _| despawn_recursive(world, entity));
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use glam::vec2;
+
+    use super::*;
+
+    fn global(world: &World, entity: Entity) -> Transform2D{
+        world_transform(world, entity).unwrap()
+    }
+
+    fn local(world: &mut World, entity: Entity) -> Transform2D{
+        *world.entry(entity).unwrap().get_component::<Transform2D>().unwrap()
+    }
+
+    fn children(world: &mut World, entity: Entity) -> Vec<Entity>{
+        world.entry(entity).unwrap().get_component::<Children>().map(|children| children.0.clone()).unwrap_or_default()
+    }
+
+    fn assert_near(a: Transform2D, b: Transform2D){
+        assert!((a.position - b.position).length() < 1e-3, "{:?} != {:?}", a.position, b.position);
+        assert!((a.rotation - b.rotation).abs() < 1e-4, "{} != {}", a.rotation, b.rotation);
+        assert!((a.scale - b.scale).length() < 1e-4, "{:?} != {:?}", a.scale, b.scale);
+    }
+
+    #[test]
+    fn reparenting_keeps_world_transform(){
+        let mut world = World::default();
+        let first = world.push((Transform2D::new(100.0, 50.0, 0.5, 2.0),));
+        let second = world.push((Transform2D::new(-30.0, 10.0, -1.0, 0.5),));
+        let child = world.push((Transform2D::new(10.0, 20.0, 0.25, 1.0),));
+        let start = global(&world, child);
+
+        assert!(set_parent(&mut world, child, Some(first)));
+        assert_near(global(&world, child), start);
+        assert_eq!(children(&mut world, first), vec![child]);
+
+        assert!(set_parent(&mut world, child, Some(second)));
+        assert_near(global(&world, child), start);
+        assert!(children(&mut world, first).is_empty());
+        assert_eq!(children(&mut world, second), vec![child]);
+
+        // Moving parent moves child with it
+        world.entry(second).unwrap().get_component_mut::<Transform2D>().unwrap().position += vec2(5.0, 0.0);
+        assert_near(global(&world, child), Transform2D { position: start.position + vec2(5.0, 0.0), ..start });
+
+        assert!(set_parent(&mut world, child, None));
+        assert_near(local(&mut world, child), Transform2D { position: start.position + vec2(5.0, 0.0), ..start });
+        assert!(children(&mut world, second).is_empty());
+        assert!(world.entry(child).unwrap().get_component::<Parent>().is_err());
+        assert!(world.entry(child).unwrap().get_component::<GlobalTransform2D>().is_err());
+    }
+
+    #[test]
+    fn parenting_under_descendant_is_refused(){
+        let mut world = World::default();
+        let root = world.push((Transform2D::ZERO,));
+        let child = world.push((Transform2D::new(10.0, 0.0, 0.0, 1.0),));
+        let grandchild = world.push((Transform2D::new(20.0, 0.0, 0.0, 1.0),));
+        assert!(set_parent(&mut world, child, Some(root)));
+        assert!(set_parent(&mut world, grandchild, Some(child)));
+
+        assert!(!set_parent(&mut world, root, Some(grandchild)));
+        assert!(!set_parent(&mut world, root, Some(root)));
+        assert!(world.entry(root).unwrap().get_component::<Parent>().is_err());
+        assert_eq!(local(&mut world, root), Transform2D::ZERO);
+        assert_near(global(&world, grandchild), Transform2D::new(20.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn despawn_recursive_removes_descendants(){
+        let mut world = World::default();
+        let root = world.push((Transform2D::ZERO,));
+        let child = world.push((Transform2D::ZERO,));
+        let sibling = world.push((Transform2D::ZERO,));
+        let grandchild = world.push((Transform2D::ZERO,));
+        set_parent(&mut world, child, Some(root));
+        set_parent(&mut world, sibling, Some(root));
+        set_parent(&mut world, grandchild, Some(child));
+
+        despawn_recursive(&mut world, child);
+        assert!(world.entry(child).is_none());
+        assert!(world.entry(grandchild).is_none());
+        assert!(world.entry(sibling).is_some());
+        assert_eq!(children(&mut world, root), vec![sibling]);
+
+        despawn_recursive(&mut world, root);
+        assert!(world.entry(root).is_none());
+        assert!(world.entry(sibling).is_none());
+    }
+}
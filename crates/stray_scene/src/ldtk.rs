@@ -0,0 +1,711 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use glam::{vec2, Vec2};
+use legion::{Entity, systems::CommandBuffer};
+use serde_json::Value;
+
+use stray_material::StrayColor;
+use stray_texture::{TextureAtlas, AtlasFrame, TextureSampling};
+
+use crate::components::{Sprite, AtlasSprite};
+use crate::hierarchy::{Parent, Children};
+use crate::tilemap::{Tilemap, Tile};
+use crate::types::Transform2D;
+
+/// Value of field of LDtk level or entity
+#[derive(Debug, Clone, PartialEq)]
+pub enum LdtkValue{
+    // Field without value
+    Null,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    // Strings, multilines and file paths
+    String(String),
+    Color(StrayColor),
+    // Name of enum value
+    Enum(String),
+    // Cell of grid, y points down
+    Point(i32, i32),
+    // Iid of referenced entity
+    EntityRef(String),
+    // Uid of tileset and rectangle in its image
+    Tile(i64, (u32,u32,u32,u32)),
+    Array(Vec<LdtkValue>),
+}
+
+/// ## Overview
+/// Fields of LDtk level or entity by identifier. Entities and levels are spawned with their fields as component
+///
+/// ## Example
+///
+/// ```
+/// use legion::*;
+/// use stray_scene::*;
+///
+/// #[system(for_each)]
+/// fn read_chests(entity: &LdtkEntity, fields: &LdtkFields){
+///     if entity.identifier == "Chest"{
+///         println!("{:?} has {} coins", fields.get_str("owner"), fields.get_int("coins").unwrap_or(0));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LdtkFields(pub HashMap<String, LdtkValue>);
+
+impl LdtkFields{
+    pub fn get(&self, name: &str) -> Option<&LdtkValue>{
+        self.0.get(name)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool>{
+        match self.get(name)?{
+            LdtkValue::Bool(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64>{
+        match self.get(name)?{
+            LdtkValue::Int(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    /// Floats and ints are both numbers
+    pub fn get_float(&self, name: &str) -> Option<f64>{
+        match self.get(name)?{
+            LdtkValue::Float(value) => Some(*value),
+            LdtkValue::Int(value) => Some(*value as f64),
+            _ => None
+        }
+    }
+
+    /// Strings and enum values are both text
+    pub fn get_str(&self, name: &str) -> Option<&str>{
+        match self.get(name)?{
+            LdtkValue::String(value) | LdtkValue::Enum(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn get_color(&self, name: &str) -> Option<StrayColor>{
+        match self.get(name)?{
+            LdtkValue::Color(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn get_array(&self, name: &str) -> Option<&[LdtkValue]>{
+        match self.get(name)?{
+            LdtkValue::Array(values) => Some(values),
+            _ => None
+        }
+    }
+}
+
+/// Level which touches level of this neighbour
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdtkNeighbour{
+    // Identifier of neighbouring level
+    pub level: String,
+    // Side written by LDtk, "n", "s", "w", "e", "<" and ">" for levels below and above, "o" for overlapping
+    pub direction: String,
+}
+
+/// ## Overview
+/// Level of LDtk project, entity of spawned level has this as component
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdtkLevel{
+    pub identifier: String,
+    pub iid: String,
+    // Position in world, y points up
+    pub world_position: Vec2,
+    pub world_depth: i32,
+    // Size in pixels
+    pub size: Vec2,
+    pub background: Option<StrayColor>,
+    pub neighbours: Vec<LdtkNeighbour>,
+    pub fields: LdtkFields,
+}
+
+impl LdtkLevel{
+    /// Neighbour in direction, e.g. "e" is level right of this one
+    pub fn neighbour(&self, direction: &str) -> Option<&str>{
+        self.neighbours.iter().find(|neighbour| neighbour.direction == direction).map(|neighbour| neighbour.level.as_str())
+    }
+}
+
+/// Entity instance of LDtk level, position is relative to level with y pointing up
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdtkEntity{
+    pub identifier: String,
+    pub iid: String,
+    pub tags: Vec<String>,
+    // Identifier of level and layer
+    pub level: String,
+    pub layer: String,
+    // Cell in layer, y points down
+    pub grid: (i32,i32),
+    pub position: Vec2,
+    pub size: Vec2,
+    // Point of entity which is on position, (0, 0) is top left corner
+    pub pivot: Vec2,
+    pub fields: LdtkFields,
+}
+
+/// ## Overview
+/// Values of IntGrid layer, 0 is empty cell. Entity of layer has this component, so grid can be used for collisions.
+/// Positions are relative to layer entity, its top left corner is at origin and y points up
+///
+/// ## Example
+///
+/// ```
+/// use glam::vec2;
+/// use legion::{*, world::SubWorld};
+/// use stray_scene::*;
+///
+/// struct Player;
+///
+/// #[system(for_each)]
+/// #[read_component(LdtkIntGrid)]
+/// fn fall(transform: &mut Transform2D, player: &Player, world: &SubWorld){
+///     for grid in <&LdtkIntGrid>::query().iter(world).filter(|grid| grid.name == "Collisions"){
+///         let feet = transform.position - vec2(0.0, 1.0);
+///         if grid.value_name(grid.value_at(feet)) != Some("wall"){
+///             transform.translate(vec2(0.0, -1.0));
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LdtkIntGrid{
+    pub name: String,
+    // Size of cell in pixels
+    pub grid_size: f32,
+    // Number of cells in row and column
+    size: (u32,u32),
+    values: Vec<i32>,
+    names: HashMap<i32, String>,
+}
+
+impl LdtkIntGrid{
+    pub fn size(&self) -> (u32,u32){
+        self.size
+    }
+
+    /// Value of cell, cells outside of grid are empty
+    pub fn get(&self, x: u32, y: u32) -> i32{
+        if x >= self.size.0 || y >= self.size.1{
+            return 0;
+        }
+        self.values[(y * self.size.0 + x) as usize]
+    }
+
+    /// Identifier of value set in LDtk
+    pub fn value_name(&self, value: i32) -> Option<&str>{
+        self.names.get(&value).map(String::as_str)
+    }
+
+    pub fn local_to_cell(&self, position: Vec2) -> Option<(u32,u32)>{
+        let (x, y) = (position.x / self.grid_size, -position.y / self.grid_size);
+        if x < 0.0 || y < 0.0 || x >= self.size.0 as f32 || y >= self.size.1 as f32{
+            return None;
+        }
+        Some((x as u32, y as u32))
+    }
+
+    /// Center of cell relative to layer
+    pub fn cell_to_local(&self, x: u32, y: u32) -> Vec2{
+        vec2((x as f32 + 0.5) * self.grid_size, -(y as f32 + 0.5) * self.grid_size)
+    }
+
+    pub fn value_at(&self, position: Vec2) -> i32{
+        self.local_to_cell(position).map(|(x, y)| self.get(x, y)).unwrap_or(0)
+    }
+
+    /// Rectangle between corners touches cell which isn't empty
+    pub fn collides(&self, min: Vec2, max: Vec2) -> bool{
+        let first = ((min.x / self.grid_size).floor().max(0.0) as u32, (-max.y / self.grid_size).floor().max(0.0) as u32);
+        let last = ((max.x / self.grid_size).ceil().max(0.0) as u32, (-min.y / self.grid_size).ceil().max(0.0) as u32);
+        (first.1..last.1.min(self.size.1)).any(|y| (first.0..last.0.min(self.size.0)).any(|x| self.get(x, y) != 0))
+    }
+}
+
+// Tile placed in layer, several tiles can be in one cell of auto layer
+#[derive(Clone)]
+struct PlacedTile{
+    cell: (u32,u32),
+    frame: usize,
+    flip_x: bool,
+    flip_y: bool,
+    alpha: f32,
+}
+
+#[derive(Clone)]
+struct LayerData{
+    name: String,
+    render_layer: i32,
+    visible: bool,
+    // Offset in pixels, y points up
+    offset: Vec2,
+    grid_size: f32,
+    size: (u32,u32),
+    tileset: Option<i64>,
+    tiles: Vec<PlacedTile>,
+    int_grid: Option<LdtkIntGrid>,
+}
+
+#[derive(Clone)]
+struct EntityData{
+    entity: LdtkEntity,
+    render_layer: i32,
+    // Tileset and frame of sprite
+    sprite: Option<(i64, usize)>,
+}
+
+struct LevelData{
+    level: LdtkLevel,
+    layers: Vec<LayerData>,
+    entities: Vec<EntityData>,
+}
+
+/// ## Overview
+/// Project of LDtk with all its levels. Tile and auto layers become ``Tilemap``s, IntGrid layers have ``LdtkIntGrid``
+/// and entity instances are spawned with ``LdtkEntity`` and ``LdtkFields``. Layers are drawn on render layers
+/// from 0 for the bottom layer of level. Levels are spawned directly or switched by ``LdtkWorld``
+///
+/// ## Example
+///
+/// ```
+/// use legion::{*, systems::CommandBuffer};
+/// use stray_scene::*;
+///
+/// #[system]
+/// fn load_level(commands: &mut CommandBuffer){
+///     let project = LdtkProject::load("assets/world.ldtk").unwrap();
+///     project.spawn_level(commands, "Level_0", Transform2D::ZERO);
+/// }
+/// ```
+pub struct LdtkProject{
+    pub background: Option<StrayColor>,
+    // Tilesets by uid
+    tilesets: HashMap<i64, TextureAtlas>,
+    levels: Vec<LevelData>,
+}
+
+impl LdtkProject{
+    /// Reads project and files which it uses from disk
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, &'static str>{
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|_| "Project file can't be read")?;
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Self::from_json(&text, move |file: &str| std::fs::read(directory.join(file)).ok())
+    }
+
+    /// ## Overview
+    /// Parses project, ``files`` returns content of tileset images and external levels by path relative to project
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// let project = LdtkProject::from_json(include_str!("world.ldtk"), |path| match path{
+    ///     "tiles.png" => Some(include_bytes!("tiles.png").to_vec()),
+    ///     _ => None
+    /// }).unwrap();
+    /// ```
+    pub fn from_json(json: &str, files: impl Fn(&str) -> Option<Vec<u8>>) -> Result<Self, &'static str>{
+        let project: Value = serde_json::from_str(json).map_err(|_| "LDtk project can't be parsed")?;
+        let defs = &project["defs"];
+
+        let mut tilesets = HashMap::new();
+        for tileset in defs["tilesets"].as_array().into_iter().flatten(){
+            // Internal icons of LDtk don't have image
+            let path = match tileset["relPath"].as_str(){
+                Some(path) => path,
+                None => continue
+            };
+            let bytes = files(path).ok_or("Image of tileset can't be read")?;
+            let texture = Sprite::new(&bytes, 0).texture.with_sampling(TextureSampling::NEAREST);
+            tilesets.insert(int(tileset, "uid"), TileFrames {
+                atlas: TextureAtlas::new(texture),
+                grid_size: int(tileset, "tileGridSize").max(1) as u32,
+                frames: HashMap::new(),
+            });
+        }
+        let mut value_names = HashMap::new();
+        for layer in defs["layers"].as_array().into_iter().flatten(){
+            let names: HashMap<i32, String> = layer["intGridValues"].as_array().into_iter().flatten()
+                .filter_map(|value| value["identifier"].as_str().map(|name| (int(value, "value") as i32, name.to_string())))
+                .collect();
+            value_names.insert(int(layer, "uid"), names);
+        }
+
+        // Levels of multi-world projects are in worlds
+        let raw_levels: Vec<&Value> = project["levels"].as_array().into_iter().flatten()
+            .chain(project["worlds"].as_array().into_iter().flatten().flat_map(|world| world["levels"].as_array().into_iter().flatten()))
+            .collect();
+        let identifiers: HashMap<&str, &str> = raw_levels.iter().map(|level| (text(level, "iid"), text(level, "identifier"))).collect();
+        let mut levels = vec![];
+        for level in raw_levels.iter(){
+            let external;
+            let layers = match (level["layerInstances"].as_array(), level["externalRelPath"].as_str()){
+                (Some(layers), _) => layers,
+                (None, Some(path)) => {
+                    let bytes = files(path).ok_or("External level can't be read")?;
+                    external = serde_json::from_slice::<Value>(&bytes).map_err(|_| "External level can't be parsed")?;
+                    external["layerInstances"].as_array().ok_or("External level doesn't have layers")?
+                }
+                (None, None) => return Err("Level doesn't have layers")
+            };
+            let neighbours = level["__neighbours"].as_array().into_iter().flatten()
+                .filter_map(|neighbour| identifiers.get(text(neighbour, "levelIid")).map(|level| LdtkNeighbour {
+                    level: level.to_string(),
+                    direction: text(neighbour, "dir").to_string(),
+                }))
+                .collect();
+            let data = LdtkLevel {
+                identifier: text(level, "identifier").to_string(),
+                iid: text(level, "iid").to_string(),
+                world_position: vec2(int(level, "worldX") as f32, -int(level, "worldY") as f32),
+                world_depth: int(level, "worldDepth") as i32,
+                size: vec2(int(level, "pxWid") as f32, int(level, "pxHei") as f32),
+                background: level["__bgColor"].as_str().and_then(|color| StrayColor::hex(color).ok()),
+                neighbours,
+                fields: fields(&level["fieldInstances"]),
+            };
+            levels.push(parse_level(data, layers, &value_names, &mut tilesets));
+        }
+
+        Ok(Self {
+            background: project["bgColor"].as_str().and_then(|color| StrayColor::hex(color).ok()),
+            tilesets: tilesets.into_iter().map(|(uid, tileset)| (uid, tileset.atlas)).collect(),
+            levels,
+        })
+    }
+
+    pub fn levels(&self) -> impl Iterator<Item = &LdtkLevel>{
+        self.levels.iter().map(|level| &level.level)
+    }
+
+    pub fn level(&self, identifier: &str) -> Option<&LdtkLevel>{
+        self.levels().find(|level| level.identifier == identifier)
+    }
+
+    /// Entity instances of level
+    pub fn entities<'a>(&'a self, level: &str) -> impl Iterator<Item = &'a LdtkEntity> + 'a{
+        let level = level.to_string();
+        self.levels.iter().filter(move |data| data.level.identifier == level).flat_map(|data| data.entities.iter().map(|data| &data.entity))
+    }
+
+    /// IntGrid layer of level by identifier of layer
+    pub fn int_grid(&self, level: &str, layer: &str) -> Option<&LdtkIntGrid>{
+        let data = self.levels.iter().find(|data| data.level.identifier == level)?;
+        data.layers.iter().filter_map(|data| data.int_grid.as_ref()).find(|grid| grid.name == layer)
+    }
+
+    /// Same as ``spawn_level_with`` without callback
+    pub fn spawn_level(&self, commands: &mut CommandBuffer, identifier: &str, transform: Transform2D) -> Option<Entity>{
+        self.spawn_level_with(commands, identifier, transform, |_, _, _| {}).map(|(level, _)| level)
+    }
+
+    /// ## Overview
+    /// Pushes level with ``LdtkLevel`` and ``LdtkFields``, its layers and entity instances are its children.
+    /// Callback is called for every entity instance, so components of game can be added from identifier and fields.
+    /// Returns entity of level and its children, ``None`` if project doesn't have level
+    pub fn spawn_level_with(
+        &self,
+        commands: &mut CommandBuffer,
+        identifier: &str,
+        transform: Transform2D,
+        mut callback: impl FnMut(&LdtkEntity, Entity, &mut CommandBuffer)
+    ) -> Option<(Entity, Vec<Entity>)>{
+        let data = self.levels.iter().find(|level| level.level.identifier == identifier)?;
+        let level = commands.push((transform, data.level.clone(), data.level.fields.clone()));
+        let mut children = vec![];
+        for layer in data.layers.iter(){
+            let entity = commands.push((Transform2D::from_translation(layer.offset), Parent(level)));
+            if let Some(atlas) = layer.tileset.and_then(|uid| self.tilesets.get(&uid)).filter(|_| !layer.tiles.is_empty()){
+                commands.add_component(entity, layer_tilemap(layer, atlas.clone()));
+            }
+            if let Some(grid) = &layer.int_grid{
+                commands.add_component(entity, grid.clone());
+            }
+            children.push(entity);
+        }
+        for data in data.entities.iter(){
+            let entity = commands.push((Transform2D::from_translation(data.entity.position), Parent(level), data.entity.fields.clone()));
+            if let Some((uid, frame)) = data.sprite{
+                if let Some(atlas) = self.tilesets.get(&uid){
+                    let sprite = AtlasSprite::new(atlas.clone(), frame, data.render_layer).with_pivot(data.entity.pivot);
+                    // Tile is stretched over size of entity
+                    if let Some(frame) = sprite.frame(){
+                        let (width, height) = (frame.source_size.0.max(1) as f32, frame.source_size.1.max(1) as f32);
+                        let scale = vec2(data.entity.size.x / width, data.entity.size.y / height);
+                        commands.add_component(entity, Transform2D::from_translation(data.entity.position).with_scale(scale));
+                    }
+                    commands.add_component(entity, sprite);
+                }
+            }
+            callback(&data.entity, entity, commands);
+            commands.add_component(entity, data.entity.clone());
+            children.push(entity);
+        }
+        commands.add_component(level, Children(children.clone()));
+        Some((level, children))
+    }
+}
+
+/// ## Overview
+/// Shows one level of project at a time, level is switched at the start of next frame.
+/// Levels are placed at their position in world of LDtk relative to entity of world, so neighbours line up
+///
+/// ## Example
+///
+/// ```ignore
+/// #[system(for_each)]
+/// fn next_level(world: &mut LdtkWorld, #[resource] input: &InputEvent){
+///     let next = world.current_level().and_then(|level| level.neighbour("e")).map(str::to_string);
+///     if let (true, Some(next)) = (input.is_pressed(Key::Right), next){
+///         world.switch_to(&next);
+///     }
+/// }
+///
+/// let project = LdtkProject::load("assets/world.ldtk").unwrap();
+/// Stray::new()
+///     .push((Transform2D::ZERO, LdtkWorld::new(project).with_level("Level_0")))
+///     .add_system(next_level_system())
+///     .build()
+///     .run();
+/// ```
+pub struct LdtkWorld{
+    project: Arc<LdtkProject>,
+    current: Option<String>,
+    requested: Option<String>,
+    // Level and its children which are removed on switch
+    spawned: Vec<Entity>,
+}
+
+impl LdtkWorld{
+    pub fn new(project: LdtkProject) -> Self{
+        Self { project: Arc::new(project), current: None, requested: None, spawned: vec![] }
+    }
+
+    /// Level which is spawned first
+    pub fn with_level(mut self, identifier: &str) -> Self{
+        self.switch_to(identifier);
+        self
+    }
+
+    pub fn project(&self) -> &LdtkProject{
+        &self.project
+    }
+
+    pub fn current_level(&self) -> Option<&LdtkLevel>{
+        self.current.as_deref().and_then(|identifier| self.project.level(identifier))
+    }
+
+    /// Requests level, returns false if project doesn't have it
+    pub fn switch_to(&mut self, identifier: &str) -> bool{
+        if self.project.level(identifier).is_none(){
+            return false;
+        }
+        self.requested = Some(identifier.to_string());
+        true
+    }
+
+    /// Removes current level and spawns requested one as child of world entity, returns true if level was switched
+    pub fn apply(&mut self, world: Entity, commands: &mut CommandBuffer) -> bool{
+        let identifier = match self.requested.take(){
+            Some(identifier) => identifier,
+            None => return false
+        };
+        for entity in self.spawned.drain(..){
+            commands.remove(entity);
+        }
+        let position = match self.project.level(&identifier){
+            Some(level) => level.world_position,
+            None => return false
+        };
+        let project = self.project.clone();
+        if let Some((level, children)) = project.spawn_level_with(commands, &identifier, Transform2D::from_translation(position), |_, _, _| {}){
+            commands.add_component(level, Parent(world));
+            commands.add_component(world, Children(vec![level]));
+            self.spawned.push(level);
+            self.spawned.extend(children);
+        }
+        self.current = Some(identifier);
+        true
+    }
+}
+
+// Atlas of tileset with frame for every rectangle used by project
+struct TileFrames{
+    atlas: TextureAtlas,
+    grid_size: u32,
+    frames: HashMap<(u32,u32,u32,u32), usize>,
+}
+
+impl TileFrames{
+    fn frame(&mut self, rect: (u32,u32,u32,u32)) -> usize{
+        let atlas = &mut self.atlas;
+        *self.frames.entry(rect).or_insert_with(|| atlas.add_frame(None, AtlasFrame::new(rect.0, rect.1, rect.2, rect.3)))
+    }
+}
+
+fn parse_level(level: LdtkLevel, layers: &[Value], value_names: &HashMap<i64, HashMap<i32, String>>, tilesets: &mut HashMap<i64, TileFrames>) -> LevelData{
+    let mut data = LevelData { level, layers: vec![], entities: vec![] };
+    // LDtk lists layers from top to bottom
+    for (render_layer, layer) in layers.iter().rev().enumerate(){
+        let render_layer = render_layer as i32;
+        let name = text(layer, "__identifier").to_string();
+        let grid_size = int(layer, "__gridSize").max(1) as u32;
+        let size = (int(layer, "__cWid").max(0) as u32, int(layer, "__cHei").max(0) as u32);
+        let offset = vec2(int(layer, "__pxTotalOffsetX") as f32, -int(layer, "__pxTotalOffsetY") as f32);
+        let opacity = number(layer, "__opacity", 1.0);
+        let visible = layer["visible"].as_bool().unwrap_or(true);
+        match text(layer, "__type"){
+            "Entities" => {
+                for instance in layer["entityInstances"].as_array().into_iter().flatten(){
+                    let pixel = pair(&instance["px"]);
+                    let sprite = instance["__tile"].as_object().and_then(|tile| {
+                        let uid = tile.get("tilesetUid")?.as_i64()?;
+                        tilesets.get_mut(&uid).map(|tileset| (uid, tileset.frame(rect(&instance["__tile"]))))
+                    });
+                    let pivot = &instance["__pivot"];
+                    data.entities.push(EntityData {
+                        entity: LdtkEntity {
+                            identifier: text(instance, "__identifier").to_string(),
+                            iid: text(instance, "iid").to_string(),
+                            tags: instance["__tags"].as_array().into_iter().flatten().filter_map(|tag| tag.as_str().map(str::to_string)).collect(),
+                            level: data.level.identifier.clone(),
+                            layer: name.clone(),
+                            grid: pair(&instance["__grid"]),
+                            position: vec2(pixel.0 as f32, -pixel.1 as f32) + offset,
+                            size: vec2(int(instance, "width") as f32, int(instance, "height") as f32),
+                            pivot: vec2(pivot[0].as_f64().unwrap_or(0.0) as f32, pivot[1].as_f64().unwrap_or(0.0) as f32),
+                            fields: fields(&instance["fieldInstances"]),
+                        },
+                        render_layer,
+                        sprite: sprite.filter(|_| visible),
+                    });
+                }
+            }
+            kind => {
+                let uid = layer["__tilesetDefUid"].as_i64();
+                let grid_tiles = if kind == "Tiles" { &layer["gridTiles"] } else { &layer["autoLayerTiles"] };
+                let mut tiles = vec![];
+                if let Some(tileset) = uid.and_then(|uid| tilesets.get_mut(&uid)){
+                    for tile in grid_tiles.as_array().into_iter().flatten(){
+                        let (x, y) = pair(&tile["px"]);
+                        let (src_x, src_y) = pair(&tile["src"]);
+                        let flip = int(tile, "f");
+                        tiles.push(PlacedTile {
+                            cell: (x.max(0) as u32 / grid_size, y.max(0) as u32 / grid_size),
+                            frame: tileset.frame((src_x.max(0) as u32, src_y.max(0) as u32, tileset.grid_size, tileset.grid_size)),
+                            flip_x: flip & 1 != 0,
+                            flip_y: flip & 2 != 0,
+                            alpha: number(tile, "a", 1.0) * opacity,
+                        });
+                    }
+                }
+                let int_grid = layer["intGridCsv"].as_array().filter(|_| kind == "IntGrid").map(|values| LdtkIntGrid {
+                    name: name.clone(),
+                    grid_size: grid_size as f32,
+                    size,
+                    values: values.iter().map(|value| value.as_i64().unwrap_or(0) as i32).chain(std::iter::repeat(0))
+                        .take((size.0 * size.1) as usize).collect(),
+                    names: value_names.get(&int(layer, "layerDefUid")).cloned().unwrap_or_default(),
+                });
+                data.layers.push(LayerData {
+                    name,
+                    render_layer,
+                    visible,
+                    offset,
+                    grid_size: grid_size as f32,
+                    size,
+                    tileset: uid,
+                    tiles,
+                    int_grid,
+                });
+            }
+        }
+    }
+    data
+}
+
+/// Tilemap with one layer for every tile stacked in the same cell
+fn layer_tilemap(layer: &LayerData, atlas: TextureAtlas) -> Tilemap{
+    let mut tilemap = Tilemap::new(atlas, layer.size, Vec2::splat(layer.grid_size));
+    let mut stacks = vec![];
+    for placed in layer.tiles.iter(){
+        let tile = Tile::new(placed.frame)
+            .with_flip(placed.flip_x, placed.flip_y)
+            .with_tint(StrayColor::WHITE.with_alpha(placed.alpha));
+        let free = stacks.iter().copied().find(|index| tilemap.get(*index, placed.cell.0, placed.cell.1).is_none());
+        let index = match free{
+            Some(index) => index,
+            None => {
+                let name = if stacks.is_empty() { layer.name.clone() } else { format!("{}_{}", layer.name, stacks.len()) };
+                let index = tilemap.add_layer(&name, layer.render_layer);
+                if let Some(tile_layer) = tilemap.layer_mut(index){
+                    tile_layer.visible = layer.visible;
+                }
+                stacks.push(index);
+                index
+            }
+        };
+        tilemap.set(index, placed.cell.0, placed.cell.1, Some(tile));
+    }
+    tilemap
+}
+
+fn fields(list: &Value) -> LdtkFields{
+    LdtkFields(list.as_array().into_iter().flatten()
+        .map(|field| (text(field, "__identifier").to_string(), field_value(text(field, "__type"), &field["__value"])))
+        .collect())
+}
+
+fn field_value(kind: &str, value: &Value) -> LdtkValue{
+    if value.is_null(){
+        return LdtkValue::Null;
+    }
+    if let Some(item) = kind.strip_prefix("Array<").and_then(|kind| kind.strip_suffix('>')){
+        return LdtkValue::Array(value.as_array().into_iter().flatten().map(|value| field_value(item, value)).collect());
+    }
+    match kind{
+        "Int" => LdtkValue::Int(value.as_i64().unwrap_or(0)),
+        "Float" => LdtkValue::Float(value.as_f64().unwrap_or(0.0)),
+        "Bool" => LdtkValue::Bool(value.as_bool().unwrap_or(false)),
+        "Color" => value.as_str().and_then(|color| StrayColor::hex(color).ok()).map(LdtkValue::Color).unwrap_or(LdtkValue::Null),
+        "Point" => LdtkValue::Point(int(value, "cx") as i32, int(value, "cy") as i32),
+        "EntityRef" => LdtkValue::EntityRef(text(value, "entityIid").to_string()),
+        "Tile" => LdtkValue::Tile(int(value, "tilesetUid"), rect(value)),
+        kind if kind.starts_with("LocalEnum.") || kind.starts_with("ExternEnum.") => LdtkValue::Enum(value.as_str().unwrap_or("").to_string()),
+        _ => LdtkValue::String(value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()))
+    }
+}
+
+fn int(value: &Value, name: &str) -> i64{
+    value[name].as_i64().unwrap_or(0)
+}
+
+fn number(value: &Value, name: &str, default: f32) -> f32{
+    value[name].as_f64().map(|number| number as f32).unwrap_or(default)
+}
+
+fn text<'a>(value: &'a Value, name: &str) -> &'a str{
+    value[name].as_str().unwrap_or("")
+}
+
+// [x, y] arrays of LDtk
+fn pair(value: &Value) -> (i32,i32){
+    (value[0].as_i64().unwrap_or(0) as i32, value[1].as_i64().unwrap_or(0) as i32)
+}
+
+fn rect(value: &Value) -> (u32,u32,u32,u32){
+    let get = |name: &str| int(value, name).max(0) as u32;
+    (get("x"), get("y"), get("w"), get("h"))
+}
@@ -1,8 +1,39 @@
 mod components;
 mod types;
 mod engine_data;
+mod hierarchy;
+mod background;
+mod light;
+mod font;
+mod bmfont;
+mod text;
+mod rich_text;
+mod time;
+mod animation;
+mod tilemap;
+mod tiled;
+mod tiled_parse;
+mod ldtk;
+mod autotile;
+mod grid;
+mod shape;
+mod sdf;
 
 pub use engine_data::*;
 pub use types::*;
 pub use components::*;
-
+pub use hierarchy::*;
+pub use background::*;
+pub use light::*;
+pub use font::*;
+pub use text::*;
+pub use rich_text::*;
+pub use time::*;
+pub use animation::*;
+pub use tilemap::*;
+pub use tiled::*;
+pub use ldtk::*;
+pub use autotile::*;
+pub use grid::*;
+pub use shape::*;
+pub use sdf::*;
@@ -0,0 +1,196 @@
+use glam::{vec2, Vec2};
+
+use stray_material::StrayColor;
+
+use crate::types::Transform2D;
+
+/// Max number of lights which are drawn in one frame, the rest is ignored
+pub const MAX_LIGHTS: usize = 64;
+
+/// ## Overview
+/// Light which shines in all directions from position of its transform.
+/// Lights modulate sprites and textured draws, ``AmbientLight`` is added to them
+///
+/// ## Example
+///
+/// ```ignore
+/// Stray::new()
+///     .insert(AmbientLight::new(StrayColor::hex("#1a1a40").unwrap(), 1.0))
+///     .push((Transform2D::ZERO, PointLight2D::new(StrayColor::ORANGE, 300.0).with_shadows(true)))
+///     .build()
+///     .run();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PointLight2D{
+    pub color: StrayColor,
+    pub intensity: f32,
+    // Distance in pixels where light fades out
+    pub radius: f32,
+    // Height above the scene in pixels, it's used with normal maps
+    pub height: f32,
+    // Size of light source in pixels, bigger source makes softer shadows
+    pub source_radius: f32,
+    pub cast_shadows: bool,
+}
+
+impl PointLight2D{
+    pub fn new(color: StrayColor, radius: f32) -> Self{
+        Self { color, intensity: 1.0, radius, height: 50.0, source_radius: 8.0, cast_shadows: false }
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self{
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_shadows(mut self, cast_shadows: bool) -> Self{
+        self.cast_shadows = cast_shadows;
+        self
+    }
+
+    pub fn to_raw(&self, transform: &Transform2D) -> RawLight{
+        let [r, g, b, _] = self.color.to_linear();
+        RawLight {
+            position: [transform.position.x, transform.position.y, self.height, self.radius],
+            color: [r * self.intensity, g * self.intensity, b * self.intensity, self.source_radius],
+            // Cone which covers everything
+            direction: [1.0, 0.0, -1.0, -2.0],
+            flags: [self.cast_shadows as u32 as f32, 0.0, 0.0, 0.0]
+        }
+    }
+}
+
+/// ## Overview
+/// Light which shines in a cone, cone points in direction of x axis of its transform.
+/// Angles are in radians and are measured from middle of cone to its edge
+///
+/// ## Example
+///
+/// ```ignore
+/// let flashlight = SpotLight2D::new(StrayColor::WHITE, 400.0, deg2rad(20.0), deg2rad(30.0));
+/// Stray::new()
+///     .push((Transform2D::ZERO.with_rotation(deg2rad(90.0)), flashlight))
+///     .build()
+///     .run();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SpotLight2D{
+    pub color: StrayColor,
+    pub intensity: f32,
+    pub radius: f32,
+    pub height: f32,
+    pub source_radius: f32,
+    pub cast_shadows: bool,
+    // Full intensity inside of inner angle, it fades out to outer angle
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+impl SpotLight2D{
+    pub fn new(color: StrayColor, radius: f32, inner_angle: f32, outer_angle: f32) -> Self{
+        Self { color, intensity: 1.0, radius, height: 50.0, source_radius: 8.0, cast_shadows: false, inner_angle, outer_angle }
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self{
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_shadows(mut self, cast_shadows: bool) -> Self{
+        self.cast_shadows = cast_shadows;
+        self
+    }
+
+    pub fn to_raw(&self, transform: &Transform2D) -> RawLight{
+        let [r, g, b, _] = self.color.to_linear();
+        let direction = transform.right();
+        RawLight {
+            position: [transform.position.x, transform.position.y, self.height, self.radius],
+            color: [r * self.intensity, g * self.intensity, b * self.intensity, self.source_radius],
+            // Outer cosine has to be lower, otherwise edge of cone is undefined in shader
+            direction: [direction.x, direction.y, self.inner_angle.cos(), self.outer_angle.cos().min(self.inner_angle.cos() - 0.0001)],
+            flags: [self.cast_shadows as u32 as f32, 0.0, 0.0, 0.0]
+        }
+    }
+}
+
+/// ## Overview
+/// Polygon which blocks light of lights with shadows, points are in pixels relative to transform
+///
+/// ## Example
+///
+/// ```ignore
+/// let wall = LightOccluder2D::rectangle(200.0, 20.0);
+/// Stray::new()
+///     .push((Transform2D::new(0.0, -100.0, 0.0, 1.0), wall))
+///     .build()
+///     .run();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LightOccluder2D{
+    pub points: Vec<Vec2>,
+    // Last point is connected to first one
+    pub closed: bool,
+}
+
+impl LightOccluder2D{
+    pub fn new(points: Vec<Vec2>, closed: bool) -> Self{
+        Self { points, closed }
+    }
+
+    /// Rectangle centered on transform
+    pub fn rectangle(width: f32, height: f32) -> Self{
+        let (x, y) = (width / 2.0, height / 2.0);
+        Self::new(vec![vec2(-x, -y), vec2(x, -y), vec2(x, y), vec2(-x, y)], true)
+    }
+
+    /// Edges in world space as ``[x1, y1, x2, y2]``
+    pub fn edges(&self, transform: &Transform2D) -> Vec<[f32;4]>{
+        let points: Vec<Vec2> = self.points.iter().map(|point| transform.transform_point(*point)).collect();
+        let mut edges: Vec<[f32;4]> = points.windows(2).map(|pair| [pair[0].x, pair[0].y, pair[1].x, pair[1].y]).collect();
+        if self.closed && points.len() > 2{
+            let (first, last) = (points[0], points[points.len() - 1]);
+            edges.push([last.x, last.y, first.x, first.y]);
+        }
+        edges
+    }
+}
+
+/// Light which is added to all lit objects, default is white light with intensity 1,
+/// so objects look the same as without lighting
+#[derive(Debug, Clone, Copy)]
+pub struct AmbientLight{
+    pub color: StrayColor,
+    pub intensity: f32,
+}
+
+impl AmbientLight{
+    pub fn new(color: StrayColor, intensity: f32) -> Self{
+        Self { color, intensity }
+    }
+
+    pub fn to_raw(&self) -> [f32;4]{
+        let [r, g, b, _] = self.color.to_linear();
+        [r * self.intensity, g * self.intensity, b * self.intensity, 1.0]
+    }
+}
+
+impl Default for AmbientLight{
+    fn default() -> Self {
+        Self::new(StrayColor::WHITE, 1.0)
+    }
+}
+
+/// Light in layout of shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RawLight{
+    // x, y, height, radius
+    pub position: [f32;4],
+    // Linear color multiplied by intensity, source radius
+    pub color: [f32;4],
+    // Direction of cone, cosines of inner and outer angle
+    pub direction: [f32;4],
+    // Casts shadows
+    pub flags: [f32;4],
+}
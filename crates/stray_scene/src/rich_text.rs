@@ -0,0 +1,610 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::{vec2, Vec2};
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::{StrayColor, StandardMaterial};
+use stray_texture::{StrayTexture, RenderTargets};
+
+use crate::engine_data::{TextureVertex, RawVertex, RenderObject, StrayVertexBuffer};
+use crate::font::{Font, FontMetrics};
+use crate::text::{TextAlign, wrap_items, line_width};
+use crate::types::Transform2D;
+
+/// Animation of characters in span
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextEffect{
+    #[default]
+    None,
+    // Characters move up and down one after another, amplitude is in pixels and frequency in waves per second
+    Wave{ amplitude: f32, frequency: f32 },
+    // Characters jitter randomly, amount is in pixels
+    Shake{ amount: f32 },
+}
+
+impl TextEffect{
+    /// Offset of character with given index at time in seconds, y points down
+    pub fn offset(&self, index: usize, time: f32) -> Vec2{
+        match self{
+            Self::None => Vec2::ZERO,
+            Self::Wave { amplitude, frequency } => {
+                vec2(0.0, -amplitude * (time * frequency * std::f32::consts::TAU - index as f32 * 0.6).sin())
+            }
+            Self::Shake { amount } => {
+                // New random offset 20 times per second
+                let seed = (index as u32).wrapping_mul(7919) ^ ((time * 20.0) as u32).wrapping_mul(104729);
+                vec2(noise(seed), noise(seed ^ 0x5bd1e995)) * *amount
+            }
+        }
+    }
+}
+
+/// Hash of seed mapped to ``[-1, 1]``
+fn noise(seed: u32) -> f32{
+    let mut x = seed;
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+/// Style of span, ``None`` values are taken from ``RichText2D``
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextStyle{
+    pub color: Option<StrayColor>,
+    pub size: Option<f32>,
+    // Name of font registered by ``RichText2D::with_font``
+    pub font: Option<String>,
+    pub effect: TextEffect,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpanContent{
+    Text(String),
+    // Name of sprite registered by ``RichText2D::with_sprite``, sprite is as tall as size of font
+    Sprite(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSpan{
+    pub content: SpanContent,
+    pub style: TextStyle,
+}
+
+/// ## Overview
+/// Parses markup into spans. Tags are in square brackets and are closed by tag with slash,
+/// ``[[`` is written as ``[``. Tags which aren't closed last until end of text.
+///
+/// - ``[color=#ff0000]text[/color]``
+/// - ``[size=32]text[/size]``
+/// - ``[font=bold]text[/font]``
+/// - ``[wave]text[/wave]`` or ``[wave=8]`` with amplitude in pixels
+/// - ``[shake]text[/shake]`` or ``[shake=4]`` with amount in pixels
+/// - ``[sprite=coin]`` inline sprite, it doesn't have closing tag
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+///
+/// let spans = parse_markup("Hi [color=#ff0000]Bob[/color]!").unwrap();
+/// assert_eq!(spans.len(), 3);
+/// assert_eq!(spans[1].content, SpanContent::Text("Bob".to_string()));
+/// assert!(spans[1].style.color.is_some());
+///
+/// assert!(parse_markup("[size=12]text[/color]").is_err());
+/// ```
+pub fn parse_markup(markup: &str) -> Result<Vec<TextSpan>, &'static str>{
+    let mut spans = vec![];
+    let mut style = TextStyle::default();
+    // Opened tags with style which was used before them
+    let mut opened: Vec<(&str, TextStyle)> = vec![];
+    let mut text = String::new();
+    let mut rest = markup;
+    while let Some(start) = rest.find('['){
+        text.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix('['){
+            text.push('[');
+            rest = after;
+            continue;
+        }
+        let end = rest.find(']').ok_or("Tag in markup isn't closed by ]")?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        if !text.is_empty(){
+            spans.push(TextSpan { content: SpanContent::Text(std::mem::take(&mut text)), style: style.clone() });
+        }
+        if let Some(name) = tag.strip_prefix('/'){
+            match opened.pop(){
+                Some((opened_name, previous)) if opened_name == name.trim() => style = previous,
+                _ => return Err("Closing tag in markup doesn't match last opened tag")
+            }
+            continue;
+        }
+        let (name, value) = match tag.split_once('='){
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (tag.trim(), None)
+        };
+        let parse_number = |default: f32| -> Result<f32, &'static str>{
+            match value{
+                Some(value) => value.parse().map_err(|_| "Value of tag in markup isn't a number"),
+                None => Ok(default)
+            }
+        };
+        let previous = style.clone();
+        match name{
+            "color" => style.color = Some(StrayColor::hex(value.ok_or("Color tag needs value")?)?),
+            "size" => style.size = Some(parse_number(0.0).and_then(|size| if size > 0.0 { Ok(size) } else { Err("Size tag needs positive value") })?),
+            "font" => style.font = Some(value.ok_or("Font tag needs value")?.to_string()),
+            "wave" => style.effect = TextEffect::Wave { amplitude: parse_number(4.0)?, frequency: 1.0 },
+            "shake" => style.effect = TextEffect::Shake { amount: parse_number(2.0)? },
+            "sprite" => {
+                let sprite = value.ok_or("Sprite tag needs value")?.to_string();
+                spans.push(TextSpan { content: SpanContent::Sprite(sprite), style: style.clone() });
+                continue;
+            }
+            _ => return Err("Unknown tag in markup")
+        }
+        opened.push((name, previous));
+    }
+    text.push_str(rest);
+    if !text.is_empty(){
+        spans.push(TextSpan { content: SpanContent::Text(text), style });
+    }
+    Ok(spans)
+}
+
+/// Texture which quad of rich text samples
+#[derive(Clone)]
+pub enum QuadSource{
+    Glyph(Font),
+    Sprite(String),
+}
+
+impl QuadSource{
+    fn same(&self, other: &QuadSource) -> bool{
+        match (self, other){
+            (Self::Glyph(a), Self::Glyph(b)) => a.ptr_eq(b),
+            (Self::Sprite(a), Self::Sprite(b)) => a == b,
+            _ => false
+        }
+    }
+}
+
+/// Glyph or sprite placed by layout, position is top left corner in pixels
+/// relative to top left corner of text block, y points down
+#[derive(Clone)]
+pub struct RichQuad{
+    pub source: QuadSource,
+    pub position: Vec2,
+    pub size: Vec2,
+    // Rectangle in font atlas, sprites use whole texture
+    pub atlas_rect: (u32,u32,u32,u32),
+    pub color: StrayColor,
+    pub effect: TextEffect,
+    // Index of character, used by effects and reveal
+    pub index: usize,
+    pub line: usize,
+}
+
+/// Result of rich text layout, ``size`` is size of whole text block in pixels
+#[derive(Clone, Default)]
+pub struct RichTextLayout{
+    pub quads: Vec<RichQuad>,
+    pub line_widths: Vec<f32>,
+    pub size: Vec2,
+}
+
+enum ItemKind{
+    Glyph(char, Font),
+    Sprite(String, Vec2),
+}
+
+/// Character or sprite with resolved style
+struct RichItem{
+    kind: ItemKind,
+    size: f32,
+    color: StrayColor,
+    effect: TextEffect,
+    index: usize,
+}
+
+impl RichItem{
+    fn advance(&self) -> f32{
+        match &self.kind{
+            ItemKind::Glyph(character, font) => font.advance(*character, self.size),
+            ItemKind::Sprite(_, size) => size.x,
+        }
+    }
+
+    fn kerning(&self, next: &RichItem) -> f32{
+        match (&self.kind, &next.kind){
+            (ItemKind::Glyph(left, font), ItemKind::Glyph(right, next_font)) if font.ptr_eq(next_font) && self.size == next.size => {
+                font.kerning(*left, *right, self.size)
+            }
+            _ => 0.0
+        }
+    }
+
+    fn is_whitespace(&self) -> bool{
+        matches!(self.kind, ItemKind::Glyph(character, _) if character.is_whitespace())
+    }
+
+    fn metrics(&self) -> FontMetrics{
+        match &self.kind{
+            ItemKind::Glyph(_, font) => font.metrics(self.size),
+            ItemKind::Sprite(_, size) => FontMetrics { ascent: size.y, descent: 0.0, line_gap: 0.0 },
+        }
+    }
+}
+
+/// ## Overview
+/// Text with styled spans which are parsed from markup, see ``parse_markup``.
+/// Fonts and sprites used by markup are registered by name. Number of drawn characters
+/// can be limited by ``visible_characters``, ``Typewriter`` component reveals characters over time
+///
+/// ## Example
+///
+/// ```ignore
+/// let font = Font::new(include_bytes!("fonts/Cantarell-Regular.ttf")).unwrap();
+/// let bold = Font::new(include_bytes!("fonts/Cantarell-Bold.ttf")).unwrap();
+/// let text = RichText2D::new("[color=#ffcc00]Bob[/color]: I found [font=bold]5[/font] [sprite=coin] [wave]coins![/wave]", font, 24.0)
+///     .unwrap()
+///     .with_font("bold", bold)
+///     .with_sprite("coin", Sprite::new(include_bytes!("coin.png"), 0).texture)
+///     .with_bounds(vec2(300.0, 100.0));
+/// Stray::new()
+///     .push((Transform2D::ZERO, text, Typewriter::new(20.0)))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct RichText2D{
+    pub spans: Vec<TextSpan>,
+    // Font of spans without font tag
+    pub font: Font,
+    pub fonts: HashMap<String, Font>,
+    pub sprites: HashMap<String, StrayTexture>,
+    pub size: f32,
+    pub color: StrayColor,
+    pub align: TextAlign,
+    pub bounds: Option<Vec2>,
+    pub line_spacing: f32,
+    pub layer: i32,
+    pub pivot: Vec2,
+    // Number of drawn characters, sprites and new lines count as one character, None draws all of them
+    pub visible_characters: Option<usize>,
+}
+
+impl RichText2D{
+    pub fn new(markup: &str, font: Font, size: f32) -> Result<Self, &'static str>{
+        Ok(Self {
+            spans: parse_markup(markup)?,
+            font,
+            fonts: HashMap::new(),
+            sprites: HashMap::new(),
+            size,
+            color: StrayColor::WHITE,
+            align: TextAlign::Left,
+            bounds: None,
+            line_spacing: 1.0,
+            layer: 0,
+            pivot: vec2(0.5, 0.5),
+            visible_characters: None
+        })
+    }
+
+    pub fn with_font(mut self, name: &str, font: Font) -> Self{
+        self.fonts.insert(name.to_string(), font);
+        self
+    }
+
+    pub fn with_sprite(mut self, name: &str, texture: StrayTexture) -> Self{
+        self.sprites.insert(name.to_string(), texture);
+        self
+    }
+
+    pub fn with_color(mut self, color: StrayColor) -> Self{
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self{
+        self.align = align;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Vec2) -> Self{
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self{
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self{
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self{
+        self.pivot = pivot;
+        self
+    }
+
+    /// Replaces spans with new markup, spans are kept when markup is invalid
+    pub fn set_markup(&mut self, markup: &str) -> Result<(), &'static str>{
+        self.spans = parse_markup(markup)?;
+        Ok(())
+    }
+
+    /// Number of characters which can be revealed
+    pub fn character_count(&self) -> usize{
+        self.spans.iter()
+            .map(|span| match &span.content{
+                SpanContent::Text(text) => text.chars().count(),
+                SpanContent::Sprite(_) => 1,
+            })
+            .sum()
+    }
+
+    /// Text without markup, sprites are left out
+    pub fn plain_text(&self) -> String{
+        self.spans.iter()
+            .filter_map(|span| match &span.content{
+                SpanContent::Text(text) => Some(text.as_str()),
+                SpanContent::Sprite(_) => None,
+            })
+            .collect()
+    }
+
+    /// Size of laid out text in pixels, hidden characters are taken into account,
+    /// so text doesn't change its size while it's revealed
+    pub fn measure(&self) -> Vec2{
+        self.layout().size
+    }
+
+    fn items(&self) -> Vec<RichItem>{
+        let mut items = vec![];
+        for span in self.spans.iter(){
+            let size = span.style.size.unwrap_or(self.size);
+            let color = span.style.color.unwrap_or(self.color);
+            let font = span.style.font.as_ref()
+                .and_then(|name| self.fonts.get(name))
+                .unwrap_or(&self.font);
+            match &span.content{
+                SpanContent::Text(text) => {
+                    for character in text.chars(){
+                        items.push(RichItem { kind: ItemKind::Glyph(character, font.clone()), size, color, effect: span.style.effect, index: items.len() });
+                    }
+                }
+                SpanContent::Sprite(name) => {
+                    // Missing sprite takes no space, but is counted as character
+                    let sprite_size = self.sprites.get(name)
+                        .map(|texture| vec2(size * texture.dimensions.0 as f32 / texture.dimensions.1.max(1) as f32, size))
+                        .unwrap_or(Vec2::ZERO);
+                    items.push(RichItem { kind: ItemKind::Sprite(name.clone(), sprite_size), size, color, effect: span.style.effect, index: items.len() });
+                }
+            }
+        }
+        items
+    }
+
+    /// Places glyphs and sprites, lines are as tall as their biggest item
+    pub fn layout(&self) -> RichTextLayout{
+        let items = self.items();
+        let max_width = self.bounds.map(|bounds| bounds.x);
+        // Items of lines with their pen positions and width of line
+        let mut lines: Vec<(Vec<(usize, f32)>, f32)> = vec![];
+        let mut paragraph_start = 0;
+        for i in 0..=items.len(){
+            let is_end = i == items.len() || matches!(items[i].kind, ItemKind::Glyph('\n', _));
+            if !is_end{
+                continue;
+            }
+            let paragraph = &items[paragraph_start..i];
+            for line in wrap_items(paragraph, max_width, RichItem::advance, RichItem::kerning, RichItem::is_whitespace){
+                let width = line_width(paragraph, &line, RichItem::advance, RichItem::is_whitespace);
+                lines.push((line.into_iter().map(|(j, x)| (paragraph_start + j, x)).collect(), width));
+            }
+            paragraph_start = i + 1;
+        }
+        // Top of line and its metrics, lines which don't fit into bounds are left out
+        let default_metrics = self.font.metrics(self.size);
+        let mut line_metrics: Vec<(f32, FontMetrics)> = vec![];
+        let mut top = 0.0;
+        for (line, _) in lines.iter(){
+            let metrics = line.iter()
+                .map(|(i, _)| items[*i].metrics())
+                .reduce(|a, b| FontMetrics { ascent: a.ascent.max(b.ascent), descent: a.descent.min(b.descent), line_gap: a.line_gap.max(b.line_gap) })
+                .unwrap_or(default_metrics);
+            let height = metrics.line_height() * self.line_spacing;
+            if self.bounds.map(|bounds| top + height > bounds.y).unwrap_or(false){
+                break;
+            }
+            line_metrics.push((top, metrics));
+            top += height;
+        }
+        lines.truncate(line_metrics.len());
+        let line_widths: Vec<f32> = lines.iter().map(|(_, width)| *width).collect();
+        let size = match self.bounds{
+            Some(bounds) => bounds,
+            None => vec2(line_widths.iter().cloned().fold(0.0, f32::max), top)
+        };
+        let visible = self.visible_characters.unwrap_or(usize::MAX);
+        let mut quads = vec![];
+        for (i, ((line, width), (top, metrics))) in lines.iter().zip(line_metrics.iter()).enumerate(){
+            let offset = match self.align{
+                TextAlign::Left => 0.0,
+                TextAlign::Center => (size.x - width) / 2.0,
+                TextAlign::Right => size.x - width,
+            };
+            let baseline = top + metrics.ascent;
+            for (j, x) in line.iter(){
+                let item = &items[*j];
+                if item.index >= visible{
+                    continue;
+                }
+                let (source, position, quad_size, atlas_rect) = match &item.kind{
+                    ItemKind::Glyph(character, font) => {
+                        let glyph = font.glyph(*character, item.size);
+                        let position = vec2(offset + x + glyph.offset.x, baseline - glyph.offset.y - glyph.size.y);
+                        (QuadSource::Glyph(font.clone()), position, glyph.size, glyph.atlas_rect)
+                    }
+                    ItemKind::Sprite(name, sprite_size) => {
+                        let position = vec2(offset + x, baseline - sprite_size.y);
+                        (QuadSource::Sprite(name.clone()), position, *sprite_size, (0, 0, 1, 1))
+                    }
+                };
+                if quad_size.x == 0.0 || quad_size.y == 0.0{
+                    continue;
+                }
+                quads.push(RichQuad { source, position, size: quad_size, atlas_rect, color: item.color, effect: item.effect, index: item.index, line: i });
+            }
+        }
+        RichTextLayout { quads, line_widths, size }
+    }
+
+    /// Quads grouped by texture they sample, ``time`` in seconds drives effects
+    pub fn vertices(&self, time: f32) -> Vec<(QuadSource, Vec<TextureVertex>)>{
+        let layout = self.layout();
+        let origin = self.pivot * layout.size;
+        let mut groups: Vec<(QuadSource, Vec<TextureVertex>)> = vec![];
+        for quad in layout.quads.iter(){
+            // Sprites sample whole texture, glyph rectangles are divided by size of atlas
+            let (uv, uv_size) = match &quad.source{
+                QuadSource::Glyph(font) => {
+                    let atlas_size = font.atlas_size();
+                    let atlas_size = vec2(atlas_size.0 as f32, atlas_size.1 as f32);
+                    let (x, y, width, height) = quad.atlas_rect;
+                    (vec2(x as f32, y as f32) / atlas_size, vec2(width as f32, height as f32) / atlas_size)
+                }
+                QuadSource::Sprite(_) => (Vec2::ZERO, Vec2::ONE)
+            };
+            let material = StandardMaterial::new(quad.color);
+            let top_left = quad.position + quad.effect.offset(quad.index, time) - origin;
+            let corner = |u: f32, v: f32| {
+                let position = top_left + vec2(u, v) * quad.size;
+                let mut vertex = TextureVertex::new(position.x, -position.y, self.layer, uv.x + u * uv_size.x, uv.y + v * uv_size.y);
+                vertex.material = material;
+                vertex
+            };
+            let vertices = [
+                corner(0.0, 1.0),
+                corner(1.0, 1.0),
+                corner(0.0, 0.0),
+                corner(0.0, 0.0),
+                corner(1.0, 1.0),
+                corner(1.0, 0.0),
+            ];
+            match groups.iter_mut().find(|(source, _)| source.same(&quad.source)){
+                Some((_, group)) => group.extend(vertices),
+                None => groups.push((quad.source.clone(), vertices.to_vec()))
+            }
+        }
+        groups
+    }
+
+    /// Creates one render object for each font and sprite which is used
+    pub fn to_render_objects(&self,
+        device: &Device,
+        queue: &Queue,
+        layout: &BindGroupLayout,
+        render_targets: &RenderTargets,
+        transform: &Transform2D,
+        time: f32
+    ) -> Vec<RenderObject>{
+        let mut render_objects = vec![];
+        for (source, vertices_data) in self.vertices(time){
+            let (bind_group, source_target) = match &source{
+                QuadSource::Glyph(font) => (Some(font.bind_group(device, queue, layout)), None),
+                QuadSource::Sprite(name) => {
+                    let texture = &self.sprites[name];
+                    (texture.bind_group(device, queue, layout, render_targets), texture.target())
+                }
+            };
+            let bind_group = match bind_group{
+                Some(bind_group) => bind_group,
+                None => continue
+            };
+            let vertices: Vec<RawVertex> = vertices_data.iter().map(|x| x.to_raw(transform)).collect();
+            let vertex_buffer = device.create_buffer_init(
+                &wgpu::util::BufferInitDescriptor {
+                    label: Some("Rich Text Vertex Buffer"),
+                    contents: bytemuck::cast_slice(vertices.as_slice()),
+                    usage: BufferUsages::VERTEX,
+                }
+            );
+            render_objects.push(RenderObject {
+                type_id: 1,
+                vertex: Some(StrayVertexBuffer(Some(Arc::new(vertex_buffer)), vertices_data.len() as u32)),
+                index: None,
+                bind_group: Some(Arc::new(bind_group)),
+                source_targets: source_target.into_iter().collect(),
+                layer: self.layer,
+                y: transform.position.y,
+                material: None,
+                material_bind_group: None,
+                normal_bind_group: None,
+            });
+        }
+        render_objects
+    }
+}
+
+/// ## Overview
+/// Reveals characters of ``RichText2D`` on the same entity over time.
+/// Systems can pause it, change its speed, skip to the end or restart it
+///
+/// ## Example
+///
+/// ```ignore
+/// #[system(for_each)]
+/// fn skip_dialogue(typewriter: &mut Typewriter, text: &RichText2D, #[resource] input: &InputEvent){
+///     if input.is_pressed(Key::Space) && !typewriter.is_finished(text){
+///         typewriter.skip();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Typewriter{
+    // Characters per second
+    pub speed: f32,
+    // Number of revealed characters
+    pub progress: f32,
+    pub paused: bool,
+}
+
+impl Typewriter{
+    pub fn new(speed: f32) -> Self{
+        Self { speed, progress: 0.0, paused: false }
+    }
+
+    /// Reveals whole text
+    pub fn skip(&mut self){
+        self.progress = f32::MAX;
+    }
+
+    pub fn restart(&mut self){
+        self.progress = 0.0;
+    }
+
+    pub fn is_finished(&self, text: &RichText2D) -> bool{
+        self.progress >= text.character_count() as f32
+    }
+
+    /// Moves progress by time and limits characters of text
+    pub fn update(&mut self, text: &mut RichText2D, delta: f32){
+        if !self.paused{
+            self.progress += self.speed * delta;
+        }
+        self.progress = self.progress.min(text.character_count() as f32);
+        text.visible_characters = Some(self.progress as usize);
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use glam::{vec2, Vec2};
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::StrayColor;
+
+use crate::engine_data::{RawSdfVertex, RenderObject, StrayVertexBuffer, StrayIndexBuffer};
+use crate::types::{Transform2D, layer_depth};
+
+/// Shape drawn by ``SdfShape``, shapes are centered at origin of transform
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SdfShapeKind{
+    Circle{ radius: f32 },
+    RoundedBox{ size: Vec2, corner_radius: f32 },
+    // Horizontal capsule, length is distance between centers of its caps
+    Capsule{ length: f32, radius: f32 },
+    // Circle outline, thickness is centered on radius
+    Ring{ radius: f32, thickness: f32 },
+}
+
+impl SdfShapeKind{
+    /// Half of size of box around shape
+    pub fn half_size(&self) -> Vec2{
+        match *self{
+            SdfShapeKind::Circle { radius } => Vec2::splat(radius),
+            SdfShapeKind::RoundedBox { size, .. } => size.abs() / 2.0,
+            SdfShapeKind::Capsule { length, radius } => vec2(length.abs() / 2.0 + radius, radius),
+            SdfShapeKind::Ring { radius, thickness } => Vec2::splat(radius + thickness.abs() / 2.0),
+        }
+    }
+
+    // Kind and parameters in layout of sdf.wgsl
+    fn to_raw(self) -> [f32;4]{
+        match self{
+            SdfShapeKind::Circle { radius } => [0.0, radius, 0.0, 0.0],
+            SdfShapeKind::RoundedBox { size, corner_radius } => {
+                let half = size.abs() / 2.0;
+                [1.0, half.x, half.y, corner_radius.max(0.0).min(half.x.min(half.y))]
+            }
+            SdfShapeKind::Capsule { length, radius } => [2.0, length.abs() / 2.0, 0.0, radius],
+            SdfShapeKind::Ring { radius, thickness } => [3.0, radius, 0.0, thickness.abs() / 2.0],
+        }
+    }
+}
+
+/// Shadow under ``SdfShape``
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfShadow{
+    pub color: StrayColor,
+    // Offset from shape in its local space, in pixels
+    pub offset: Vec2,
+    // Distance over which shadow fades out, in pixels
+    pub blur: f32,
+}
+
+// Everything which changes quad of shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SdfParams{
+    kind: SdfShapeKind,
+    color: StrayColor,
+    outline: Option<(StrayColor, f32)>,
+    shadow: Option<SdfShadow>,
+    layer: i32,
+    transform: Transform2D,
+}
+
+/// ## Overview
+/// Shape drawn as single quad whose pixels are colored by distance to edge of shape. Edges are
+/// smoothed by one pixel on screen, so shape looks clean at any zoom without MSAA.
+/// Outline is drawn outside of edge of shape and shadow is drawn under it
+///
+/// ## Example
+///
+/// ```ignore
+/// Stray::new()
+///     .push((
+///         Transform2D::ZERO,
+///         SdfShape::rounded_box(vec2(200.0, 60.0), 12.0)
+///             .with_color(StrayColor::BLUE)
+///             .with_outline(StrayColor::WHITE, 2.0)
+///             .with_shadow(StrayColor::BLACK.with_alpha(0.5), vec2(4.0, -4.0), 8.0)
+///     ))
+///     .push((Transform2D::new(0.0, 100.0, 0.0, 1.0), SdfShape::ring(30.0, 6.0).with_color(StrayColor::ORANGE)))
+///     .build()
+///     .run();
+/// ```
+pub struct SdfShape{
+    pub kind: SdfShapeKind,
+    pub color: StrayColor,
+    // Color and width of outline in pixels
+    pub outline: Option<(StrayColor, f32)>,
+    pub shadow: Option<SdfShadow>,
+    pub layer: i32,
+    // Parameters of quad in buffers
+    built: Option<(SdfParams, Arc<Buffer>, Arc<Buffer>)>,
+}
+
+impl SdfShape{
+    /// White shape without outline and shadow
+    pub fn new(kind: SdfShapeKind) -> Self{
+        Self { kind, color: StrayColor::WHITE, outline: None, shadow: None, layer: 0, built: None }
+    }
+
+    pub fn circle(radius: f32) -> Self{
+        Self::new(SdfShapeKind::Circle { radius })
+    }
+
+    pub fn rounded_box(size: Vec2, corner_radius: f32) -> Self{
+        Self::new(SdfShapeKind::RoundedBox { size, corner_radius })
+    }
+
+    pub fn capsule(length: f32, radius: f32) -> Self{
+        Self::new(SdfShapeKind::Capsule { length, radius })
+    }
+
+    pub fn ring(radius: f32, thickness: f32) -> Self{
+        Self::new(SdfShapeKind::Ring { radius, thickness })
+    }
+
+    pub fn with_color(mut self, color: StrayColor) -> Self{
+        self.color = color;
+        self
+    }
+
+    pub fn with_outline(mut self, color: StrayColor, width: f32) -> Self{
+        self.outline = Some((color, width));
+        self
+    }
+
+    pub fn with_shadow(mut self, color: StrayColor, offset: Vec2, blur: f32) -> Self{
+        self.shadow = Some(SdfShadow { color, offset, blur });
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self{
+        self.layer = layer;
+        self
+    }
+
+    /// ## Overview
+    /// Half of size of quad, it covers shape with its outline, shadow and one pixel for smoothing
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stray_scene::*;
+    /// use stray_material::StrayColor;
+    /// use glam::vec2;
+    ///
+    /// let dot = SdfShape::circle(10.0).with_outline(StrayColor::BLACK, 2.0);
+    /// assert_eq!(dot.quad_half_size(), vec2(13.0, 13.0));
+    /// ```
+    pub fn quad_half_size(&self) -> Vec2{
+        let outline = self.outline.map(|(_, width)| width.max(0.0)).unwrap_or(0.0);
+        let shadow = self.shadow
+            .map(|shadow| shadow.offset.abs() + Vec2::splat(shadow.blur.abs()))
+            .unwrap_or(Vec2::ZERO);
+        self.kind.half_size() + Vec2::splat(outline + 1.0) + shadow
+    }
+
+    /// Draws shape with SDF pipeline, buffers are created again only when shape or transform changes
+    pub fn to_render_object(&mut self, device: &Device, transform: &Transform2D) -> RenderObject{
+        let params = SdfParams {
+            kind: self.kind,
+            color: self.color,
+            outline: self.outline,
+            shadow: self.shadow,
+            layer: self.layer,
+            transform: *transform,
+        };
+        let (vertex, index) = match &self.built{
+            Some((built, vertex, index)) if *built == params => (vertex.clone(), index.clone()),
+            _ => {
+                let (vertex, index) = self.build_quad(device, transform);
+                let (vertex, index) = (Arc::new(vertex), Arc::new(index));
+                self.built = Some((params, vertex.clone(), index.clone()));
+                (vertex, index)
+            }
+        };
+        RenderObject {
+            type_id: 3,
+            vertex: Some(StrayVertexBuffer(Some(vertex), 4)),
+            index: Some(StrayIndexBuffer(Some(index), 6)),
+            bind_group: None,
+            source_targets: vec![],
+            layer: self.layer,
+            y: transform.position.y,
+            material: None,
+            material_bind_group: None,
+            normal_bind_group: None,
+        }
+    }
+
+    fn build_quad(&self, device: &Device, transform: &Transform2D) -> (Buffer, Buffer){
+        let half = self.quad_half_size();
+        let (outline_color, outline_width) = self.outline.unwrap_or((StrayColor::TRANSPARENT, 0.0));
+        let shadow = self.shadow.unwrap_or(SdfShadow { color: StrayColor::TRANSPARENT, offset: Vec2::ZERO, blur: 0.0 });
+        let corners = [vec2(-half.x, -half.y), vec2(half.x, -half.y), vec2(half.x, half.y), vec2(-half.x, half.y)];
+        let vertices = corners.map(|corner| RawSdfVertex {
+            position: [corner.x, corner.y, layer_depth(self.layer)],
+            color: self.color.to_linear(),
+            outline_color: outline_color.to_linear(),
+            shadow_color: shadow.color.to_linear(),
+            shape: self.kind.to_raw(),
+            style: [outline_width.max(0.0), shadow.blur.abs(), shadow.offset.x, shadow.offset.y],
+            model: transform.to_raw(),
+        });
+        let indices: [u16;6] = [0, 1, 2, 0, 2, 3];
+        let vertex = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SDF Shape Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: BufferUsages::VERTEX,
+        });
+        let index = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SDF Shape Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: BufferUsages::INDEX,
+        });
+        (vertex, index)
+    }
+}
@@ -0,0 +1,535 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use glam::{vec2, Vec2};
+use lyon::math::{point, vector, Angle, Box2D, Point};
+use lyon::path::{Path, Polygon, Winding, builder::BorderRadii};
+use lyon::tessellation::{
+    BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator, StrokeVertex, VertexBuffers,
+};
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::{StrayColor, StandardMaterial};
+
+use crate::engine_data::{TextureVertex, RawVertex, RenderObject, StrayVertexBuffer, StrayIndexBuffer};
+use crate::types::Transform2D;
+
+/// Segment of ``ShapePath``
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand{
+    // Starts new subpath
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    // Control point and end
+    QuadraticTo(Vec2, Vec2),
+    // Two control points and end
+    CubicTo(Vec2, Vec2, Vec2),
+    // Connects end of subpath to its start
+    Close,
+}
+
+/// ## Overview
+/// Path of lines and bezier curves, it can have several subpaths
+///
+/// ## Example
+///
+/// ```
+/// use glam::vec2;
+/// use stray_material::StrayColor;
+/// use stray_scene::*;
+///
+/// let heart = ShapePath::new()
+///     .move_to(vec2(0.0, -40.0))
+///     .cubic_to(vec2(-60.0, 0.0), vec2(-30.0, 50.0), vec2(0.0, 20.0))
+///     .cubic_to(vec2(30.0, 50.0), vec2(60.0, 0.0), vec2(0.0, -40.0))
+///     .close();
+/// let shape = Shape2D::path(heart).with_fill(StrayColor::RED);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShapePath{
+    pub commands: Vec<PathCommand>,
+}
+
+impl ShapePath{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    pub fn move_to(mut self, to: Vec2) -> Self{
+        self.commands.push(PathCommand::MoveTo(to));
+        self
+    }
+
+    pub fn line_to(mut self, to: Vec2) -> Self{
+        self.commands.push(PathCommand::LineTo(to));
+        self
+    }
+
+    pub fn quadratic_to(mut self, control: Vec2, to: Vec2) -> Self{
+        self.commands.push(PathCommand::QuadraticTo(control, to));
+        self
+    }
+
+    pub fn cubic_to(mut self, first: Vec2, second: Vec2, to: Vec2) -> Self{
+        self.commands.push(PathCommand::CubicTo(first, second, to));
+        self
+    }
+
+    pub fn close(mut self) -> Self{
+        self.commands.push(PathCommand::Close);
+        self
+    }
+}
+
+/// Geometry of ``Shape2D``, shapes are centered at origin of transform
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeKind{
+    Circle{ radius: f32 },
+    Ellipse{ radii: Vec2 },
+    // Rectangle with rounded corners when radius isn't 0
+    Rectangle{ size: Vec2, corner_radius: f32 },
+    // First corner points up
+    RegularPolygon{ sides: u32, radius: f32 },
+    Polygon{ points: Vec<Vec2> },
+    // Open line through points, it's only stroked
+    Polyline{ points: Vec<Vec2> },
+    Path(ShapePath),
+}
+
+/// How corners of stroke are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin{
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// How ends of open stroke are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap{
+    // Line ends at its end point
+    #[default]
+    Butt,
+    // Line goes half of width over its end point
+    Square,
+    Round,
+}
+
+/// Outline of shape
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke{
+    pub color: StrayColor,
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+}
+
+impl Stroke{
+    pub fn new(color: StrayColor, width: f32) -> Self{
+        Self { color, width, join: LineJoin::Miter, cap: LineCap::Butt }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self{
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self{
+        self.cap = cap;
+        self
+    }
+}
+
+// Everything which changes triangles of shape
+#[derive(Debug, Clone, PartialEq)]
+struct ShapeParams{
+    kind: ShapeKind,
+    fill: Option<StrayColor>,
+    stroke: Option<Stroke>,
+    layer: i32,
+    tolerance: f32,
+}
+
+struct ShapeMesh{
+    transform: Transform2D,
+    vertex: Arc<Buffer>,
+    vertex_count: u32,
+    index: Arc<Buffer>,
+    index_count: u32,
+}
+
+/// ## Overview
+/// Vector shape which is filled and stroked. Shape is tessellated only when its parameters change
+/// and its buffers are created again only when its transform changes
+///
+/// ## Example
+///
+/// ```ignore
+/// Stray::new()
+///     .push((Transform2D::ZERO, Shape2D::circle(50.0).with_fill(StrayColor::ORANGE)))
+///     .push((
+///         Transform2D::new(150.0, 0.0, 0.0, 1.0),
+///         Shape2D::rounded_rectangle(vec2(120.0, 80.0), 16.0)
+///             .with_fill(StrayColor::BLUE)
+///             .with_stroke(Stroke::new(StrayColor::WHITE, 4.0).with_join(LineJoin::Round))
+///     ))
+///     .build()
+///     .run();
+/// ```
+pub struct Shape2D{
+    pub kind: ShapeKind,
+    pub fill: Option<StrayColor>,
+    pub stroke: Option<Stroke>,
+    pub layer: i32,
+    // Maximum distance between curve and its segments in pixels
+    pub tolerance: f32,
+    // Parameters of last tessellation with its vertices and indices
+    tessellated: Option<(ShapeParams, Vec<TextureVertex>, Vec<u16>)>,
+    mesh: Option<ShapeMesh>,
+}
+
+impl Shape2D{
+    /// Filled white shape, polylines are stroked with white line of width 1 instead
+    pub fn new(kind: ShapeKind) -> Self{
+        let (fill, stroke) = match kind{
+            ShapeKind::Polyline{..} => (None, Some(Stroke::new(StrayColor::WHITE, 1.0))),
+            _ => (Some(StrayColor::WHITE), None)
+        };
+        Self { kind, fill, stroke, layer: 0, tolerance: 0.1, tessellated: None, mesh: None }
+    }
+
+    pub fn circle(radius: f32) -> Self{
+        Self::new(ShapeKind::Circle { radius })
+    }
+
+    pub fn ellipse(radii: Vec2) -> Self{
+        Self::new(ShapeKind::Ellipse { radii })
+    }
+
+    pub fn rectangle(size: Vec2) -> Self{
+        Self::new(ShapeKind::Rectangle { size, corner_radius: 0.0 })
+    }
+
+    pub fn rounded_rectangle(size: Vec2, corner_radius: f32) -> Self{
+        Self::new(ShapeKind::Rectangle { size, corner_radius })
+    }
+
+    pub fn regular_polygon(sides: u32, radius: f32) -> Self{
+        Self::new(ShapeKind::RegularPolygon { sides, radius })
+    }
+
+    pub fn polygon(points: Vec<Vec2>) -> Self{
+        Self::new(ShapeKind::Polygon { points })
+    }
+
+    pub fn polyline(points: Vec<Vec2>) -> Self{
+        Self::new(ShapeKind::Polyline { points })
+    }
+
+    pub fn path(path: ShapePath) -> Self{
+        Self::new(ShapeKind::Path(path))
+    }
+
+    pub fn with_fill(mut self, color: StrayColor) -> Self{
+        self.fill = Some(color);
+        self
+    }
+
+    /// Shape is only stroked
+    pub fn without_fill(mut self) -> Self{
+        self.fill = None;
+        self
+    }
+
+    pub fn with_stroke(mut self, stroke: Stroke) -> Self{
+        self.stroke = Some(stroke);
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self{
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self{
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// ## Overview
+    /// Triangles of fill and stroke, stroke is drawn over fill. Triangles are counter-clockwise.
+    /// Shape has no triangles when fill and stroke together have more vertices than ``u16`` indices can address
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stray_scene::*;
+    /// use glam::vec2;
+    ///
+    /// let mut square = Shape2D::rectangle(vec2(10.0, 10.0));
+    /// let (vertices, indices) = square.geometry();
+    /// assert_eq!((vertices.len(), indices.len()), (4, 6));
+    /// ```
+    pub fn geometry(&mut self) -> (&[TextureVertex], &[u16]){
+        let params = ShapeParams {
+            kind: self.kind.clone(),
+            fill: self.fill,
+            stroke: self.stroke,
+            layer: self.layer,
+            tolerance: self.tolerance,
+        };
+        if self.tessellated.as_ref().map(|(tessellated, _, _)| tessellated) != Some(&params){
+            let (vertices, indices) = tessellate(&params);
+            self.tessellated = Some((params, vertices, indices));
+            self.mesh = None;
+        }
+        match &self.tessellated{
+            Some((_, vertices, indices)) => (vertices, indices),
+            None => (&[], &[])
+        }
+    }
+
+    /// Draws shape with shape pipeline, ``None`` when shape has no triangles
+    pub fn to_render_object(&mut self, device: &Device, transform: &Transform2D) -> Option<RenderObject>{
+        self.geometry();
+        let (_, vertices, indices) = self.tessellated.as_ref()?;
+        if indices.is_empty(){
+            return None;
+        }
+        if self.mesh.as_ref().map(|mesh| mesh.transform) != Some(*transform){
+            let raw: Vec<RawVertex> = vertices.iter().map(|vertex| vertex.to_raw(transform)).collect();
+            let vertex = device.create_buffer_init(&util::BufferInitDescriptor {
+                label: Some("Shape Vertex Buffer"),
+                contents: bytemuck::cast_slice(raw.as_slice()),
+                usage: BufferUsages::VERTEX,
+            });
+            let index = device.create_buffer_init(&util::BufferInitDescriptor {
+                label: Some("Shape Index Buffer"),
+                contents: bytemuck::cast_slice(indices),
+                usage: BufferUsages::INDEX,
+            });
+            self.mesh = Some(ShapeMesh {
+                transform: *transform,
+                vertex: Arc::new(vertex),
+                vertex_count: raw.len() as u32,
+                index: Arc::new(index),
+                index_count: indices.len() as u32,
+            });
+        }
+        let mesh = self.mesh.as_ref()?;
+        Some(RenderObject {
+            type_id: 0,
+            vertex: Some(StrayVertexBuffer(Some(mesh.vertex.clone()), mesh.vertex_count)),
+            index: Some(StrayIndexBuffer(Some(mesh.index.clone()), mesh.index_count)),
+            bind_group: None,
+            source_targets: vec![],
+            layer: self.layer,
+            y: transform.position.y,
+            material: None,
+            material_bind_group: None,
+            normal_bind_group: None,
+        })
+    }
+}
+
+fn lyon_point(point: Vec2) -> Point{
+    lyon::math::point(point.x, point.y)
+}
+
+fn build_path(kind: &ShapeKind) -> Path{
+    let mut builder = Path::builder();
+    let center = point(0.0, 0.0);
+    match kind{
+        ShapeKind::Circle { radius } => builder.add_circle(center, *radius, Winding::Positive),
+        ShapeKind::Ellipse { radii } => builder.add_ellipse(center, vector(radii.x, radii.y), Angle::radians(0.0), Winding::Positive),
+        ShapeKind::Rectangle { size, corner_radius } => {
+            let rectangle = Box2D::new(point(-size.x / 2.0, -size.y / 2.0), point(size.x / 2.0, size.y / 2.0));
+            // Radius can't be bigger than half of shorter side
+            let radius = corner_radius.max(0.0).min(size.x.abs().min(size.y.abs()) / 2.0);
+            if radius > 0.0{
+                builder.add_rounded_rectangle(&rectangle, &BorderRadii::new(radius), Winding::Positive);
+            }
+            else{
+                builder.add_rectangle(&rectangle, Winding::Positive);
+            }
+        }
+        ShapeKind::RegularPolygon { sides, radius } => {
+            let points: Vec<Point> = regular_polygon_points(*sides, *radius).into_iter().map(lyon_point).collect();
+            builder.add_polygon(Polygon { points: &points, closed: true });
+        }
+        ShapeKind::Polygon { points } | ShapeKind::Polyline { points } => {
+            if points.len() >= 2{
+                let points: Vec<Point> = points.iter().map(|point| lyon_point(*point)).collect();
+                let closed = matches!(kind, ShapeKind::Polygon{..});
+                builder.add_polygon(Polygon { points: &points, closed });
+            }
+        }
+        ShapeKind::Path(path) => {
+            let mut open = false;
+            for command in path.commands.iter(){
+                match *command{
+                    PathCommand::MoveTo(to) => {
+                        if open{
+                            builder.end(false);
+                        }
+                        builder.begin(lyon_point(to));
+                        open = true;
+                    }
+                    // Segments without start begin at origin
+                    _ if !open && *command != PathCommand::Close => {
+                        builder.begin(center);
+                        open = true;
+                    }
+                    _ => {}
+                }
+                match *command{
+                    PathCommand::LineTo(to) => { builder.line_to(lyon_point(to)); }
+                    PathCommand::QuadraticTo(control, to) => { builder.quadratic_bezier_to(lyon_point(control), lyon_point(to)); }
+                    PathCommand::CubicTo(first, second, to) => { builder.cubic_bezier_to(lyon_point(first), lyon_point(second), lyon_point(to)); }
+                    PathCommand::Close if open => {
+                        builder.end(true);
+                        open = false;
+                    }
+                    _ => {}
+                }
+            }
+            if open{
+                builder.end(false);
+            }
+        }
+    }
+    builder.build()
+}
+
+fn tessellate(params: &ShapeParams) -> (Vec<TextureVertex>, Vec<u16>){
+    let path = build_path(&params.kind);
+    let tolerance = params.tolerance.max(0.001);
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    let mut too_many_vertices = false;
+    let mut append = |buffers: VertexBuffers<[f32;2], u16>, color: StrayColor| {
+        // Fill and stroke share u16 index buffer
+        let first = match u16::try_from(vertices.len()){
+            Ok(first) if buffers.vertices.len() <= u16::MAX as usize + 1 - first as usize => first,
+            _ => {
+                too_many_vertices = true;
+                return;
+            }
+        };
+        vertices.extend(buffers.vertices.iter().map(|position| {
+            let mut vertex = TextureVertex::new(position[0], position[1], params.layer, 0.0, 0.0);
+            vertex.material = StandardMaterial::new(color);
+            vertex
+        }));
+        // Shape pipeline culls clockwise triangles
+        for triangle in buffers.indices.chunks_exact(3){
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| Vec2::from(buffers.vertices[index as usize]));
+            let counter_clockwise = (b - a).perp_dot(c - a) >= 0.0;
+            let order = if counter_clockwise { [0, 1, 2] } else { [0, 2, 1] };
+            indices.extend(order.map(|corner| first + triangle[corner]));
+        }
+    };
+    let closed = !matches!(params.kind, ShapeKind::Polyline{..});
+    if let (Some(color), true) = (params.fill, closed){
+        let mut buffers = VertexBuffers::new();
+        let result = FillTessellator::new().tessellate_path(
+            &path,
+            &FillOptions::tolerance(tolerance),
+            &mut BuffersBuilder::new(&mut buffers, |vertex: FillVertex| vertex.position().to_array())
+        );
+        if result.is_ok(){
+            append(buffers, color);
+        }
+    }
+    if let Some(stroke) = params.stroke.filter(|stroke| stroke.width > 0.0){
+        let join = match stroke.join{
+            LineJoin::Miter => lyon::tessellation::LineJoin::Miter,
+            LineJoin::Round => lyon::tessellation::LineJoin::Round,
+            LineJoin::Bevel => lyon::tessellation::LineJoin::Bevel
+        };
+        let cap = match stroke.cap{
+            LineCap::Butt => lyon::tessellation::LineCap::Butt,
+            LineCap::Square => lyon::tessellation::LineCap::Square,
+            LineCap::Round => lyon::tessellation::LineCap::Round
+        };
+        let options = StrokeOptions::tolerance(tolerance)
+            .with_line_width(stroke.width)
+            .with_line_join(join)
+            .with_line_cap(cap);
+        let mut buffers = VertexBuffers::new();
+        let result = StrokeTessellator::new().tessellate_path(
+            &path,
+            &options,
+            &mut BuffersBuilder::new(&mut buffers, |vertex: StrokeVertex| vertex.position().to_array())
+        );
+        if result.is_ok(){
+            append(buffers, stroke.color);
+        }
+    }
+    if too_many_vertices{
+        return (vec![], vec![]);
+    }
+    (vertices, indices)
+}
+
+/// Corners of regular polygon centered at origin, first corner points up
+pub fn regular_polygon_points(sides: u32, radius: f32) -> Vec<Vec2>{
+    let sides = sides.max(3);
+    (0..sides).map(|side| {
+        let angle = PI / 2.0 + 2.0 * PI * side as f32 / sides as f32;
+        vec2(angle.cos(), angle.sin()) * radius
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn assert_counter_clockwise(vertices: &[TextureVertex], indices: &[u16]){
+        assert!(!indices.is_empty());
+        for triangle in indices.chunks_exact(3){
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| vertices[index as usize].position());
+            assert!((b - a).perp_dot(c - a) >= 0.0, "{:?} {:?} {:?} is clockwise", a, b, c);
+        }
+    }
+
+    #[test]
+    fn fill_and_stroke_triangles_are_counter_clockwise(){
+        let stroke = Stroke::new(StrayColor::BLACK, 4.0);
+        let kinds = [
+            ShapeKind::Circle{radius: 50.0},
+            ShapeKind::Rectangle{size: vec2(100.0, 40.0), corner_radius: 10.0},
+            // Clockwise outline
+            ShapeKind::Polygon{points: vec![vec2(0.0, 0.0), vec2(0.0, 50.0), vec2(50.0, 50.0), vec2(50.0, 0.0)]},
+            ShapeKind::Path(ShapePath::new().move_to(vec2(0.0, 0.0)).cubic_to(vec2(50.0, 100.0), vec2(100.0, -100.0), vec2(150.0, 0.0)).close()),
+        ];
+        for kind in kinds{
+            let mut fill = Shape2D::new(kind.clone());
+            let (vertices, indices) = fill.geometry();
+            assert_counter_clockwise(vertices, indices);
+
+            let mut stroke = Shape2D::new(kind).without_fill().with_stroke(stroke.with_join(LineJoin::Round));
+            let (vertices, indices) = stroke.geometry();
+            assert_counter_clockwise(vertices, indices);
+        }
+        let mut polyline = Shape2D::polyline(vec![vec2(0.0, 0.0), vec2(50.0, 50.0), vec2(100.0, 0.0)])
+            .with_stroke(stroke.with_cap(LineCap::Round));
+        let (vertices, indices) = polyline.geometry();
+        assert_counter_clockwise(vertices, indices);
+    }
+
+    #[test]
+    fn shape_with_too_many_vertices_has_no_triangles(){
+        // Fill and stroke fit into u16 indices on their own, but not together
+        let circle = || Shape2D::circle(100000.0).with_tolerance(0.001);
+        let fill_vertices = circle().geometry().0.len();
+        let stroke_vertices = circle().without_fill().with_stroke(Stroke::new(StrayColor::BLACK, 2.0)).geometry().0.len();
+        assert!(fill_vertices > 0 && stroke_vertices > 0);
+        assert!(fill_vertices + stroke_vertices > u16::MAX as usize + 1);
+
+        let mut circle = circle().with_stroke(Stroke::new(StrayColor::BLACK, 2.0));
+        let (vertices, indices) = circle.geometry();
+        assert!(vertices.is_empty() && indices.is_empty());
+    }
+}
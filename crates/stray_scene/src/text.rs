@@ -0,0 +1,303 @@
+use std::sync::Arc;
+
+use glam::{vec2, Vec2};
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::{StrayColor, StandardMaterial};
+
+use crate::engine_data::{TextureVertex, RawVertex, RenderObject, StrayVertexBuffer};
+use crate::font::Font;
+use crate::types::Transform2D;
+
+/// Horizontal alignment of lines inside of text block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign{
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Glyph placed by layout, position is top left corner in pixels
+/// relative to top left corner of text block, y points down
+#[derive(Debug, Clone, Copy)]
+pub struct PositionedGlyph{
+    pub character: char,
+    pub position: Vec2,
+    pub size: Vec2,
+    pub atlas_rect: (u32,u32,u32,u32),
+    pub line: usize,
+}
+
+/// Result of text layout, ``size`` is size of whole text block in pixels
+#[derive(Debug, Clone, Default)]
+pub struct TextLayout{
+    pub glyphs: Vec<PositionedGlyph>,
+    pub line_widths: Vec<f32>,
+    pub size: Vec2,
+}
+
+/// ## Overview
+/// Text drawn with TTF or OTF font. Text is wrapped to width of ``bounds`` and lines
+/// which don't fit into its height are not drawn. All glyphs of text are drawn at once
+/// with texture pipeline, so text is lit like sprites
+///
+/// ## Example
+///
+/// ```ignore
+/// let font = Font::new(include_bytes!("fonts/Cantarell-Regular.ttf")).unwrap();
+/// let text = Text2D::new("Hello world", font, 32.0)
+///     .with_color(StrayColor::YELLOW)
+///     .with_bounds(vec2(200.0, 100.0))
+///     .with_align(TextAlign::Center);
+/// Stray::new()
+///     .push((Transform2D::ZERO, text))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct Text2D{
+    pub text: String,
+    pub font: Font,
+    // Size of font in pixels
+    pub size: f32,
+    pub color: StrayColor,
+    pub align: TextAlign,
+    // Width and height of text block, None means text isn't wrapped
+    pub bounds: Option<Vec2>,
+    // Multiplier of distance between lines
+    pub line_spacing: f32,
+    pub layer: i32,
+    // Point of text block which is placed at position of transform,
+    // (0,0) is top left corner and (1,1) is bottom right corner
+    pub pivot: Vec2,
+}
+
+impl Text2D{
+    pub fn new(text: &str, font: Font, size: f32) -> Self{
+        Self {
+            text: text.to_string(),
+            font,
+            size,
+            color: StrayColor::WHITE,
+            align: TextAlign::Left,
+            bounds: None,
+            line_spacing: 1.0,
+            layer: 0,
+            pivot: vec2(0.5, 0.5)
+        }
+    }
+
+    pub fn with_color(mut self, color: StrayColor) -> Self{
+        self.color = color;
+        self
+    }
+
+    pub fn with_align(mut self, align: TextAlign) -> Self{
+        self.align = align;
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Vec2) -> Self{
+        self.bounds = Some(bounds);
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self{
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self{
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Vec2) -> Self{
+        self.pivot = pivot;
+        self
+    }
+
+    pub fn set_text(&mut self, text: &str){
+        self.text.clear();
+        self.text.push_str(text);
+    }
+
+    /// Size of laid out text in pixels, wrapping is taken into account
+    pub fn measure(&self) -> Vec2{
+        self.layout().size
+    }
+
+    /// Number of lines after wrapping
+    pub fn line_count(&self) -> usize{
+        self.layout().line_widths.len()
+    }
+
+    /// Places glyphs of text, glyphs which aren't in atlas yet are rasterized
+    pub fn layout(&self) -> TextLayout{
+        let max_width = self.bounds.map(|bounds| bounds.x);
+        let advance = |character: &char| self.font.advance(*character, self.size);
+        let kerning = |left: &char, right: &char| self.font.kerning(*left, *right, self.size);
+        let is_whitespace = |character: &char| character.is_whitespace();
+        // Characters of lines with their pen positions and width of line
+        let mut lines: Vec<(Vec<(char, f32)>, f32)> = vec![];
+        for paragraph in self.text.split('\n'){
+            let characters: Vec<char> = paragraph.chars().collect();
+            for line in wrap_items(&characters, max_width, advance, kerning, is_whitespace){
+                let width = line_width(&characters, &line, advance, is_whitespace);
+                lines.push((line.into_iter().map(|(i, x)| (characters[i], x)).collect(), width));
+            }
+        }
+        let metrics = self.font.metrics(self.size);
+        let line_height = metrics.line_height() * self.line_spacing;
+        let line_count = match self.bounds{
+            Some(bounds) => lines.len().min((bounds.y / line_height).floor().max(0.0) as usize),
+            None => lines.len()
+        };
+        lines.truncate(line_count);
+        let line_widths: Vec<f32> = lines.iter().map(|(_, width)| *width).collect();
+        let size = match self.bounds{
+            Some(bounds) => bounds,
+            None => vec2(line_widths.iter().cloned().fold(0.0, f32::max), line_height * line_count as f32)
+        };
+        let mut glyphs = vec![];
+        for (i, (line, _)) in lines.iter().enumerate(){
+            let offset = match self.align{
+                TextAlign::Left => 0.0,
+                TextAlign::Center => (size.x - line_widths[i]) / 2.0,
+                TextAlign::Right => size.x - line_widths[i],
+            };
+            let baseline = metrics.ascent + line_height * i as f32;
+            for (character, x) in line.iter(){
+                let glyph = self.font.glyph(*character, self.size);
+                if glyph.size.x == 0.0 || glyph.size.y == 0.0{
+                    continue;
+                }
+                glyphs.push(PositionedGlyph {
+                    character: *character,
+                    position: vec2(offset + x + glyph.offset.x, baseline - glyph.offset.y - glyph.size.y),
+                    size: glyph.size,
+                    atlas_rect: glyph.atlas_rect,
+                    line: i,
+                });
+            }
+        }
+        TextLayout { glyphs, line_widths, size }
+    }
+
+    /// Quads of glyphs in pixels relative to transform, atlas has to be read
+    /// after layout, because it can grow while glyphs are rasterized
+    pub fn vertices(&self) -> Vec<TextureVertex>{
+        let layout = self.layout();
+        let atlas_size = self.font.atlas_size();
+        let atlas_size = vec2(atlas_size.0 as f32, atlas_size.1 as f32);
+        let origin = self.pivot * layout.size;
+        let material = StandardMaterial::new(self.color);
+        let mut vertices = Vec::with_capacity(layout.glyphs.len() * 6);
+        for glyph in layout.glyphs.iter(){
+            let (x, y, width, height) = glyph.atlas_rect;
+            let uv = vec2(x as f32, y as f32) / atlas_size;
+            let uv_size = vec2(width as f32, height as f32) / atlas_size;
+            let corner = |u: f32, v: f32| {
+                let position = glyph.position + vec2(u, v) * glyph.size - origin;
+                let mut vertex = TextureVertex::new(position.x, -position.y, self.layer, uv.x + u * uv_size.x, uv.y + v * uv_size.y);
+                vertex.material = material;
+                vertex
+            };
+            vertices.extend([
+                corner(0.0, 1.0),
+                corner(1.0, 1.0),
+                corner(0.0, 0.0),
+                corner(0.0, 0.0),
+                corner(1.0, 1.0),
+                corner(1.0, 0.0),
+            ]);
+        }
+        vertices
+    }
+
+    /// Creates render object with all glyphs, returns ``None`` if there is nothing to draw
+    pub fn to_render_object(&self,
+        device: &Device,
+        queue: &Queue,
+        layout: &BindGroupLayout,
+        transform: &Transform2D
+    ) -> Option<RenderObject>{
+        let vertices_data = self.vertices();
+        if vertices_data.is_empty(){
+            return None;
+        }
+        let vertices: Vec<RawVertex> = vertices_data.iter().map(|x| x.to_raw(transform)).collect();
+        let vertex_buffer = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("Text Vertex Buffer"),
+                contents: bytemuck::cast_slice(vertices.as_slice()),
+                usage: BufferUsages::VERTEX,
+            }
+        );
+        Some(RenderObject {
+            type_id: 1,
+            vertex: Some(StrayVertexBuffer(Some(Arc::new(vertex_buffer)), vertices_data.len() as u32)),
+            index: None,
+            bind_group: Some(Arc::new(self.font.bind_group(device, queue, layout))),
+            source_targets: vec![],
+            layer: self.layer,
+            y: transform.position.y,
+            material: None,
+            material_bind_group: None,
+            normal_bind_group: None,
+        })
+    }
+}
+
+/// Splits items of paragraph into lines which fit into width, lines are broken after whitespace
+/// and items longer than width are broken between items.
+/// Returns indices of items in lines with their pen positions
+pub(crate) fn wrap_items<T>(
+    items: &[T],
+    max_width: Option<f32>,
+    advance: impl Fn(&T) -> f32,
+    kerning: impl Fn(&T, &T) -> f32,
+    is_whitespace: impl Fn(&T) -> bool
+) -> Vec<Vec<(usize, f32)>>{
+    let mut lines = vec![];
+    let mut line: Vec<(usize, f32)> = vec![];
+    let mut x = 0.0;
+    // Index in line after last whitespace
+    let mut last_break: Option<usize> = None;
+    for (i, item) in items.iter().enumerate(){
+        if let Some((previous, _)) = line.last(){
+            x += kerning(&items[*previous], item);
+        }
+        let item_advance = advance(item);
+        let overflows = max_width.map(|max_width| x + item_advance > max_width).unwrap_or(false);
+        if overflows && !is_whitespace(item) && line.iter().any(|(j, _)| !is_whitespace(&items[*j])){
+            let rest = match last_break{
+                Some(index) => line.split_off(index),
+                None => vec![]
+            };
+            lines.push(std::mem::take(&mut line));
+            // Items after break start new line
+            let start = rest.first().map(|(_, x)| *x).unwrap_or(0.0);
+            line = rest.into_iter().map(|(j, x)| (j, x - start)).collect();
+            x = line.last().map(|(j, x)| x + advance(&items[*j])).unwrap_or(0.0);
+            last_break = None;
+        }
+        line.push((i, x));
+        x += item_advance;
+        if is_whitespace(item){
+            last_break = Some(line.len());
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Width of line without trailing whitespace
+pub(crate) fn line_width<T>(items: &[T], line: &[(usize, f32)], advance: impl Fn(&T) -> f32, is_whitespace: impl Fn(&T) -> bool) -> f32{
+    line.iter()
+        .rev()
+        .find(|(i, _)| !is_whitespace(&items[*i]))
+        .map(|(i, x)| x + advance(&items[*i]))
+        .unwrap_or(0.0)
+}
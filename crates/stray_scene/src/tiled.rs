@@ -0,0 +1,454 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use glam::{vec2, Vec2};
+use image::{load_from_memory, RgbaImage, GenericImage};
+use legion::{Entity, systems::CommandBuffer};
+
+use stray_material::StrayColor;
+use stray_texture::{StrayTexture, TextureAtlas, AtlasFrame, AnimationClip, TextureSampling};
+
+use crate::components::AtlasSprite;
+use crate::hierarchy::{Parent, Children};
+use crate::tilemap::{Tilemap, Tile, TilemapOrientation};
+use crate::tiled_parse::{RawMap, RawLayerKind, RawObject, parse_tmx, parse_tmj};
+use crate::types::Transform2D;
+
+const FLIPPED_X: u32 = 0x80000000;
+const FLIPPED_Y: u32 = 0x40000000;
+const FLIPPED_DIAGONAL: u32 = 0x20000000;
+// Rotation of hexagonal tiles isn't supported, it's masked out with flags
+const GID_MASK: u32 = 0x0FFFFFFF;
+
+/// Custom property of Tiled
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledProperty{
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Color(StrayColor),
+    // Path relative to file which has property
+    File(String),
+    // Id of object, 0 is no object
+    Object(u32),
+    Class(TiledProperties),
+}
+
+/// ## Overview
+/// Custom properties of map, layer or object. Objects are spawned with their properties as component
+///
+/// ## Example
+///
+/// ```
+/// use legion::*;
+/// use stray_scene::*;
+///
+/// #[system(for_each)]
+/// fn open_doors(object: &TiledObject, properties: &TiledProperties){
+///     if object.class == "door" && properties.get_bool("locked") == Some(false){
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TiledProperties(pub HashMap<String, TiledProperty>);
+
+impl TiledProperties{
+    pub fn get(&self, name: &str) -> Option<&TiledProperty>{
+        self.0.get(name)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool>{
+        match self.get(name)?{
+            TiledProperty::Bool(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn get_int(&self, name: &str) -> Option<i64>{
+        match self.get(name)?{
+            TiledProperty::Int(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    /// Floats and ints are both numbers
+    pub fn get_float(&self, name: &str) -> Option<f64>{
+        match self.get(name)?{
+            TiledProperty::Float(value) => Some(*value),
+            TiledProperty::Int(value) => Some(*value as f64),
+            _ => None
+        }
+    }
+
+    /// Strings and paths of files are both text
+    pub fn get_str(&self, name: &str) -> Option<&str>{
+        match self.get(name)?{
+            TiledProperty::String(value) | TiledProperty::File(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn get_color(&self, name: &str) -> Option<StrayColor>{
+        match self.get(name)?{
+            TiledProperty::Color(value) => Some(*value),
+            _ => None
+        }
+    }
+}
+
+/// Shape of object, points are relative to position of object
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledShape{
+    Rectangle,
+    Ellipse,
+    Point,
+    Polygon(Vec<Vec2>),
+    Polyline(Vec<Vec2>),
+    Text(String),
+}
+
+/// ## Overview
+/// Object of object layer. Values are converted from Tiled, positions are relative to tilemap with y pointing up
+/// and rotation is in radians counterclockwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct TiledObject{
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    // Name of object layer
+    pub layer: String,
+    // Render layer of sprite of tile object
+    pub render_layer: i32,
+    pub position: Vec2,
+    // Size in pixels of Tiled
+    pub size: Vec2,
+    pub rotation: f32,
+    pub visible: bool,
+    pub shape: TiledShape,
+    // Frame of tile object
+    pub tile: Option<Tile>,
+    pub properties: TiledProperties,
+}
+
+/// ## Overview
+/// Map of Tiled loaded from ``.tmx`` or ``.tmj``. Tile layers become layers of one ``Tilemap`` whose tileset
+/// is made from all tilesets of map, animated tiles are animated by tilemap. Layers are drawn on render layers
+/// in order of Tiled starting from 0. Orthogonal and isometric maps are supported, infinite maps are cut to their tiles
+///
+/// ## Example
+///
+/// ```
+/// use legion::{*, systems::CommandBuffer};
+/// use stray_scene::*;
+///
+/// struct Enemy;
+///
+/// #[system]
+/// fn load_level(commands: &mut CommandBuffer){
+///     let map = TiledMap::load("assets/level.tmx").unwrap();
+///     // Objects are children of tilemap with TiledObject and TiledProperties components
+///     map.spawn_with(commands, Transform2D::ZERO, |object, entity, commands| {
+///         if object.class == "enemy"{
+///             commands.add_component(entity, Enemy);
+///         }
+///     });
+/// }
+/// ```
+pub struct TiledMap{
+    pub orientation: TilemapOrientation,
+    // Number of tiles of map
+    pub size: (u32,u32),
+    pub tile_size: Vec2,
+    pub background: Option<StrayColor>,
+    pub properties: TiledProperties,
+    // Properties of layers by name
+    pub layer_properties: HashMap<String, TiledProperties>,
+    pub tilemap: Tilemap,
+    pub objects: Vec<TiledObject>,
+}
+
+impl TiledMap{
+    /// Reads map and files which it uses from disk, format is chosen by extension
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, &'static str>{
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|_| "Map file can't be read")?;
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let files = move |file: &str| std::fs::read(directory.join(file)).ok();
+        match path.extension().and_then(|extension| extension.to_str()){
+            Some("tmj") | Some("json") => Self::from_tmj(&text, files),
+            _ => Self::from_tmx(&text, files)
+        }
+    }
+
+    /// ## Overview
+    /// Parses TMX, ``files`` returns content of tilesets and images by path relative to map
+    ///
+    /// ## Example
+    ///
+    /// ```ignore
+    /// let map = TiledMap::from_tmx(include_str!("level.tmx"), |path| match path{
+    ///     "tiles.png" => Some(include_bytes!("tiles.png").to_vec()),
+    ///     _ => None
+    /// }).unwrap();
+    /// ```
+    pub fn from_tmx(xml: &str, files: impl Fn(&str) -> Option<Vec<u8>>) -> Result<Self, &'static str>{
+        Self::build(parse_tmx(xml, &files)?, &files)
+    }
+
+    /// Parses TMJ, ``files`` returns content of tilesets and images by path relative to map
+    pub fn from_tmj(json: &str, files: impl Fn(&str) -> Option<Vec<u8>>) -> Result<Self, &'static str>{
+        Self::build(parse_tmj(json, &files)?, &files)
+    }
+
+    fn build(raw: RawMap, files: &dyn Fn(&str) -> Option<Vec<u8>>) -> Result<Self, &'static str>{
+        let orientation = match raw.orientation.as_str(){
+            "orthogonal" => TilemapOrientation::Orthogonal,
+            "isometric" => TilemapOrientation::Isometric,
+            _ => return Err("Only orthogonal and isometric maps are supported")
+        };
+        if raw.tile_size.0 == 0 || raw.tile_size.1 == 0{
+            return Err("Map doesn't have size of tiles");
+        }
+        let (atlas, gids) = tileset_atlas(&raw, files)?;
+
+        // Infinite maps are cut to chunks which have tiles
+        let chunks = raw.layers.iter().filter_map(|layer| match &layer.kind{
+            RawLayerKind::Tiles(chunks) => Some(chunks),
+            _ => None
+        }).flatten();
+        let (mut min, mut max) = ((i32::MAX, i32::MAX), (i32::MIN, i32::MIN));
+        for chunk in chunks{
+            min = (min.0.min(chunk.position.0), min.1.min(chunk.position.1));
+            max = (max.0.max(chunk.position.0 + chunk.size.0 as i32), max.1.max(chunk.position.1 + chunk.size.1 as i32));
+        }
+        if min.0 > max.0{
+            (min, max) = ((0, 0), (raw.size.0 as i32, raw.size.1 as i32));
+        }
+        let size = ((max.0 - min.0) as u32, (max.1 - min.1) as u32);
+        let tile_size = vec2(raw.tile_size.0 as f32, raw.tile_size.1 as f32);
+
+        let mut tilemap = Tilemap::new(atlas, size, tile_size)
+            .with_orientation(orientation)
+            .with_frame_scale(Vec2::ONE);
+        let tile = |gid: u32, tint: StrayColor| {
+            gids.get(&(gid & GID_MASK)).map(|&index| Tile {
+                index,
+                flip_x: gid & FLIPPED_X != 0,
+                flip_y: gid & FLIPPED_Y != 0,
+                flip_diagonal: gid & FLIPPED_DIAGONAL != 0,
+                tint,
+            })
+        };
+        // Objects of infinite maps are moved with tiles
+        let origin = match orientation{
+            TilemapOrientation::Orthogonal => vec2(min.0 as f32 * tile_size.x, min.1 as f32 * tile_size.y),
+            TilemapOrientation::Isometric => vec2(min.0 as f32, min.1 as f32) * tile_size.y
+        };
+        let to_local = |point: Vec2| {
+            let point = point - origin;
+            match orientation{
+                TilemapOrientation::Orthogonal => vec2(point.x, -point.y),
+                TilemapOrientation::Isometric => {
+                    let (x, y) = (point.x / tile_size.y, point.y / tile_size.y);
+                    vec2((x - y) * tile_size.x / 2.0, -(x + y) * tile_size.y / 2.0)
+                }
+            }
+        };
+
+        let mut objects = vec![];
+        let mut layer_properties = HashMap::new();
+        for (render_layer, layer) in raw.layers.into_iter().enumerate(){
+            let render_layer = render_layer as i32;
+            let tint = layer.tint.unwrap_or(StrayColor::WHITE);
+            let tint = tint.with_alpha(tint.a * layer.opacity);
+            let offset = vec2(layer.offset.x, -layer.offset.y);
+            layer_properties.insert(layer.name.clone(), layer.properties);
+            match layer.kind{
+                RawLayerKind::Tiles(chunks) => {
+                    let index = tilemap.add_layer(&layer.name, render_layer);
+                    if let Some(tile_layer) = tilemap.layer_mut(index){
+                        tile_layer.visible = layer.visible;
+                    }
+                    tilemap.set_layer_offset(index, offset);
+                    for chunk in chunks{
+                        for (i, gid) in chunk.gids.iter().enumerate().filter(|(_, gid)| **gid != 0){
+                            let x = chunk.position.0 + (i as u32 % chunk.size.0.max(1)) as i32 - min.0;
+                            let y = chunk.position.1 + (i as u32 / chunk.size.0.max(1)) as i32 - min.1;
+                            if x >= 0 && y >= 0{
+                                tilemap.set(index, x as u32, y as u32, tile(*gid, tint));
+                            }
+                        }
+                    }
+                }
+                RawLayerKind::Objects(raw_objects) => {
+                    for object in raw_objects{
+                        let RawObject { id, name, class, position, size, rotation, gid, visible, shape, properties } = object;
+                        let local = |point: Vec2| to_local(position + point) - to_local(position);
+                        let shape = match shape{
+                            TiledShape::Polygon(points) => TiledShape::Polygon(points.into_iter().map(local).collect()),
+                            TiledShape::Polyline(points) => TiledShape::Polyline(points.into_iter().map(local).collect()),
+                            shape => shape
+                        };
+                        objects.push(TiledObject {
+                            id,
+                            name,
+                            class,
+                            layer: layer.name.clone(),
+                            render_layer,
+                            position: to_local(position) + offset,
+                            size,
+                            rotation: -rotation.to_radians(),
+                            visible: visible && layer.visible,
+                            shape,
+                            tile: gid.and_then(|gid| tile(gid, StrayColor::WHITE.with_alpha(layer.opacity))),
+                            properties,
+                        });
+                    }
+                }
+            }
+        }
+
+        for tileset in raw.tilesets.iter(){
+            for raw_tile in tileset.tiles.iter().filter(|tile| !tile.animation.is_empty()){
+                let base = match gids.get(&(tileset.first_gid + raw_tile.id)){
+                    Some(base) => *base,
+                    None => continue
+                };
+                let frames: Vec<(usize, f32)> = raw_tile.animation.iter()
+                    .filter_map(|(id, duration)| gids.get(&(tileset.first_gid + id)).map(|frame| (*frame, *duration)))
+                    .collect();
+                let clip = AnimationClip::new(
+                    &format!("tile_{}", base),
+                    frames.iter().map(|(frame, _)| *frame).collect(),
+                    frames.iter().map(|(_, duration)| *duration).collect()
+                );
+                tilemap.add_animation(base, clip);
+            }
+        }
+
+        Ok(Self {
+            orientation,
+            size,
+            tile_size,
+            background: raw.background,
+            properties: raw.properties,
+            layer_properties,
+            tilemap,
+            objects,
+        })
+    }
+
+    /// Same as ``spawn_with`` without callback
+    pub fn spawn(self, commands: &mut CommandBuffer, transform: Transform2D) -> Entity{
+        self.spawn_with(commands, transform, |_, _, _| {})
+    }
+
+    /// ## Overview
+    /// Pushes tilemap with properties of map and every object as its child. Objects have ``TiledObject`` and
+    /// ``TiledProperties``, visible tile objects have ``AtlasSprite``. Callback is called for every object,
+    /// so components of game can be added from class and properties. Returns entity of tilemap
+    pub fn spawn_with(self, commands: &mut CommandBuffer, transform: Transform2D, mut callback: impl FnMut(&TiledObject, Entity, &mut CommandBuffer)) -> Entity{
+        let atlas = self.tilemap.tileset.clone();
+        let pivot = match self.orientation{
+            TilemapOrientation::Orthogonal => vec2(0.0, 1.0),
+            TilemapOrientation::Isometric => vec2(0.5, 1.0)
+        };
+        let map = commands.push((transform, self.tilemap, self.properties));
+        let mut children = vec![];
+        for object in self.objects{
+            let mut object_transform = Transform2D::from_translation(object.position).with_rotation(object.rotation);
+            let entity = commands.push((Parent(map), object.properties.clone()));
+            if let Some(tile) = object.tile{
+                if let Some(frame) = atlas.frame(tile.index){
+                    // Tile objects are scaled to their size in Tiled
+                    let (width, height) = (frame.source_size.0.max(1) as f32, frame.source_size.1.max(1) as f32);
+                    if object.size.x > 0.0 && object.size.y > 0.0{
+                        object_transform.scale = vec2(object.size.x / width, object.size.y / height);
+                    }
+                    if object.visible{
+                        let sprite = AtlasSprite::new(atlas.clone(), tile.index, object.render_layer)
+                            .with_pivot(pivot)
+                            .with_flip(tile.flip_x, tile.flip_y);
+                        commands.add_component(entity, sprite);
+                    }
+                }
+            }
+            commands.add_component(entity, object_transform);
+            callback(&object, entity, commands);
+            commands.add_component(entity, object);
+            children.push(entity);
+        }
+        commands.add_component(map, Children(children));
+        map
+    }
+}
+
+/// Texture with images of all tilesets stacked vertically and index of frame for every global id
+fn tileset_atlas(raw: &RawMap, files: &dyn Fn(&str) -> Option<Vec<u8>>) -> Result<(TextureAtlas, HashMap<u32, usize>), &'static str>{
+    let load = |path: &str| -> Result<RgbaImage, &'static str>{
+        let bytes = files(path).ok_or("Image of tileset can't be read")?;
+        Ok(load_from_memory(&bytes).map_err(|_| "Image of tileset can't be decoded")?.to_rgba8())
+    };
+    let mut images: Vec<RgbaImage> = vec![];
+    let mut frames: Vec<AtlasFrame> = vec![];
+    let mut gids = HashMap::new();
+    let mut height = 0;
+    for tileset in raw.tilesets.iter(){
+        match &tileset.image{
+            Some(path) => {
+                let image = load(path)?;
+                let (tile_width, tile_height) = tileset.tile_size;
+                if tile_width == 0 || tile_height == 0{
+                    return Err("Tileset doesn't have size of tiles");
+                }
+                let columns = match tileset.columns{
+                    0 => (image.width().saturating_sub(2 * tileset.margin) + tileset.spacing) / (tile_width + tileset.spacing),
+                    columns => columns
+                }.max(1);
+                let rows = (image.height().saturating_sub(2 * tileset.margin) + tileset.spacing) / (tile_height + tileset.spacing);
+                let count = match tileset.tile_count{
+                    0 => columns * rows,
+                    count => count
+                };
+                for id in 0..count{
+                    let x = tileset.margin + id % columns * (tile_width + tileset.spacing);
+                    let y = tileset.margin + id / columns * (tile_height + tileset.spacing);
+                    if x + tile_width > image.width() || y + tile_height > image.height(){
+                        break;
+                    }
+                    gids.insert(tileset.first_gid + id, frames.len());
+                    frames.push(AtlasFrame::new(x, height + y, tile_width, tile_height));
+                }
+                height += image.height();
+                images.push(image);
+            }
+            None => {
+                for tile in tileset.tiles.iter(){
+                    if let Some(path) = &tile.image{
+                        let image = load(path)?;
+                        gids.insert(tileset.first_gid + tile.id, frames.len());
+                        frames.push(AtlasFrame::new(0, height, image.width(), image.height()));
+                        height += image.height();
+                        images.push(image);
+                    }
+                }
+            }
+        }
+    }
+    let width = images.iter().map(|image| image.width()).max().unwrap_or(1);
+    let mut texture = RgbaImage::new(width, height.max(1));
+    let mut y = 0;
+    for image in images.iter(){
+        texture.copy_from(image, 0, y).map_err(|_| "Tileset image doesn't fit into atlas")?;
+        y += image.height();
+    }
+    let dimensions = texture.dimensions();
+    let mut atlas = TextureAtlas::new(StrayTexture::with(texture, dimensions).with_sampling(TextureSampling::NEAREST));
+    for frame in frames{
+        atlas.add_frame(None, frame);
+    }
+    Ok((atlas, gids))
+}
+
@@ -0,0 +1,573 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use glam::{vec2, Vec2};
+use roxmltree::{Document, Node};
+use serde_json::Value;
+
+use stray_material::StrayColor;
+
+use crate::tiled::{TiledProperty, TiledProperties, TiledShape};
+
+/// Map as it's written in TMX or TMJ, values are in pixels of Tiled where y points down
+pub(crate) struct RawMap{
+    pub orientation: String,
+    pub size: (u32,u32),
+    pub tile_size: (u32,u32),
+    pub background: Option<StrayColor>,
+    pub tilesets: Vec<RawTileset>,
+    pub layers: Vec<RawLayer>,
+    pub properties: TiledProperties,
+}
+
+pub(crate) struct RawTileset{
+    pub first_gid: u32,
+    pub tile_size: (u32,u32),
+    pub spacing: u32,
+    pub margin: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    // Path relative to map, tilesets without image are collections of images
+    pub image: Option<String>,
+    pub tiles: Vec<RawTile>,
+}
+
+pub(crate) struct RawTile{
+    pub id: u32,
+    pub image: Option<String>,
+    // Local id of tile and duration in seconds
+    pub animation: Vec<(u32, f32)>,
+}
+
+pub(crate) struct RawChunk{
+    pub position: (i32,i32),
+    pub size: (u32,u32),
+    pub gids: Vec<u32>,
+}
+
+pub(crate) struct RawObject{
+    pub id: u32,
+    pub name: String,
+    pub class: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    // Degrees clockwise
+    pub rotation: f32,
+    pub gid: Option<u32>,
+    pub visible: bool,
+    pub shape: TiledShape,
+    pub properties: TiledProperties,
+}
+
+pub(crate) enum RawLayerKind{
+    Tiles(Vec<RawChunk>),
+    Objects(Vec<RawObject>),
+}
+
+/// Layers of groups are flattened, so visibility, opacity and offset of groups are already applied
+pub(crate) struct RawLayer{
+    pub name: String,
+    pub visible: bool,
+    pub opacity: f32,
+    pub tint: Option<StrayColor>,
+    pub offset: Vec2,
+    pub kind: RawLayerKind,
+    pub properties: TiledProperties,
+}
+
+/// Group layer whose children are being read
+struct GroupState{
+    visible: bool,
+    opacity: f32,
+    offset: Vec2,
+}
+
+const ROOT_GROUP: GroupState = GroupState { visible: true, opacity: 1.0, offset: Vec2::ZERO };
+
+/// Tiled writes colors as ``#AARRGGBB``
+fn color(hex: &str) -> Result<StrayColor, &'static str>{
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() == 8 && hex.is_ascii(){
+        return StrayColor::hex(&format!("{}{}", &hex[2..], &hex[..2]));
+    }
+    StrayColor::hex(hex)
+}
+
+/// Joins path written in file with directory of that file, ``..`` is resolved when possible
+pub(crate) fn join_path(directory: &str, path: &str) -> String{
+    if path.starts_with('/') || directory.is_empty(){
+        return path.to_string();
+    }
+    let mut parts: Vec<&str> = directory.split('/').filter(|part| !part.is_empty() && *part != ".").collect();
+    for part in path.split('/'){
+        match part{
+            "" | "." => {}
+            ".." if parts.last().map(|last| *last != "..").unwrap_or(false) => {
+                parts.pop();
+            }
+            _ => parts.push(part)
+        }
+    }
+    parts.join("/")
+}
+
+pub(crate) fn directory_of(path: &str) -> &str{
+    path.rsplit_once('/').map(|(directory, _)| directory).unwrap_or("")
+}
+
+fn base64(text: &str) -> Result<Vec<u8>, &'static str>{
+    let mut bytes = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for character in text.bytes(){
+        let value = match character{
+            b'A'..=b'Z' => character - b'A',
+            b'a'..=b'z' => character - b'a' + 26,
+            b'0'..=b'9' => character - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            _ if character.is_ascii_whitespace() => continue,
+            _ => return Err("Tile data has invalid base64")
+        };
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8{
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Global ids of tiles from encoded data of layer
+fn decode_gids(data: &str, encoding: &str, compression: &str) -> Result<Vec<u32>, &'static str>{
+    match encoding{
+        "csv" => data.split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| gid.parse().map_err(|_| "Tile data has invalid CSV"))
+            .collect(),
+        "base64" => {
+            let compressed = base64(data)?;
+            let mut bytes = vec![];
+            match compression{
+                "" => bytes = compressed,
+                "zlib" => {
+                    ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut bytes).map_err(|_| "Tile data can't be decompressed")?;
+                }
+                "gzip" => {
+                    GzDecoder::new(compressed.as_slice()).read_to_end(&mut bytes).map_err(|_| "Tile data can't be decompressed")?;
+                }
+                _ => return Err("Only zlib and gzip compression of tile data is supported")
+            }
+            Ok(bytes.chunks_exact(4).map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]])).collect())
+        }
+        _ => Err("Tile data has unknown encoding")
+    }
+}
+
+fn points(text: &str) -> Vec<Vec2>{
+    text.split_whitespace()
+        .filter_map(|point| point.split_once(','))
+        .filter_map(|(x, y)| Some(vec2(x.parse().ok()?, y.parse().ok()?)))
+        .collect()
+}
+
+fn property(kind: &str, value: &str) -> Result<TiledProperty, &'static str>{
+    Ok(match kind{
+        "int" => TiledProperty::Int(value.parse().map_err(|_| "Property has invalid int")?),
+        "float" => TiledProperty::Float(value.parse().map_err(|_| "Property has invalid float")?),
+        "bool" => TiledProperty::Bool(value == "true"),
+        "color" if value.is_empty() => TiledProperty::Color(StrayColor::TRANSPARENT),
+        "color" => TiledProperty::Color(color(value)?),
+        "file" => TiledProperty::File(value.to_string()),
+        "object" => TiledProperty::Object(value.parse().unwrap_or(0)),
+        _ => TiledProperty::String(value.to_string())
+    })
+}
+
+// TMX
+
+fn attribute<'a>(node: &Node<'a, '_>, name: &str) -> &'a str{
+    node.attribute(name).unwrap_or("")
+}
+
+fn number<T: std::str::FromStr>(node: &Node, name: &str, default: T) -> Result<T, &'static str>{
+    match node.attribute(name){
+        Some(value) => value.trim().parse().map_err(|_| "TMX attribute has invalid number"),
+        None => Ok(default)
+    }
+}
+
+fn child<'a, 'input>(node: &Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>>{
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn xml_properties(node: &Node) -> Result<TiledProperties, &'static str>{
+    let mut properties = HashMap::new();
+    if let Some(list) = child(node, "properties"){
+        for item in list.children().filter(|item| item.has_tag_name("property")){
+            let kind = attribute(&item, "type");
+            let value = if kind == "class"{
+                TiledProperty::Class(xml_properties(&item)?)
+            }
+            else{
+                // Multiline strings are written as text of element
+                let value = item.attribute("value").or(item.text()).unwrap_or("");
+                property(kind, value)?
+            };
+            properties.insert(attribute(&item, "name").to_string(), value);
+        }
+    }
+    Ok(TiledProperties(properties))
+}
+
+pub(crate) fn parse_tmx(xml: &str, files: &dyn Fn(&str) -> Option<Vec<u8>>) -> Result<RawMap, &'static str>{
+    let document = Document::parse(xml).map_err(|_| "TMX isn't valid XML")?;
+    let map = document.root_element();
+    if !map.has_tag_name("map"){
+        return Err("TMX doesn't have map element");
+    }
+    let mut tilesets = vec![];
+    for tileset in map.children().filter(|node| node.has_tag_name("tileset")){
+        let first_gid = number(&tileset, "firstgid", 1)?;
+        match tileset.attribute("source"){
+            Some(source) => {
+                let bytes = files(source).ok_or("External tileset can't be read")?;
+                let text = std::str::from_utf8(&bytes).map_err(|_| "External tileset isn't valid UTF-8")?;
+                tilesets.push(parse_external_tileset(text, first_gid, directory_of(source))?);
+            }
+            None => tilesets.push(xml_tileset(&tileset, first_gid, "")?)
+        }
+    }
+    let mut layers = vec![];
+    xml_layers(&map, &ROOT_GROUP, &mut layers)?;
+    Ok(RawMap {
+        orientation: attribute(&map, "orientation").to_string(),
+        size: (number(&map, "width", 0)?, number(&map, "height", 0)?),
+        tile_size: (number(&map, "tilewidth", 0)?, number(&map, "tileheight", 0)?),
+        background: map.attribute("backgroundcolor").map(color).transpose()?,
+        tilesets,
+        layers,
+        properties: xml_properties(&map)?,
+    })
+}
+
+/// Tileset from ``.tsx`` or ``.tsj`` file, ``directory`` is directory of file relative to map
+fn parse_external_tileset(text: &str, first_gid: u32, directory: &str) -> Result<RawTileset, &'static str>{
+    if text.trim_start().starts_with('<'){
+        let document = Document::parse(text).map_err(|_| "TSX isn't valid XML")?;
+        xml_tileset(&document.root_element(), first_gid, directory)
+    }
+    else{
+        let root: Value = serde_json::from_str(text).map_err(|_| "TSJ can't be parsed")?;
+        json_tileset(&root, first_gid, directory)
+    }
+}
+
+fn xml_tileset(node: &Node, first_gid: u32, directory: &str) -> Result<RawTileset, &'static str>{
+    let mut tiles = vec![];
+    for tile in node.children().filter(|child| child.has_tag_name("tile")){
+        let animation = match child(&tile, "animation"){
+            Some(animation) => animation.children()
+                .filter(|frame| frame.has_tag_name("frame"))
+                .map(|frame| Ok((number(&frame, "tileid", 0)?, number(&frame, "duration", 100.0f32)? / 1000.0)))
+                .collect::<Result<Vec<(u32, f32)>, &'static str>>()?,
+            None => vec![]
+        };
+        tiles.push(RawTile {
+            id: number(&tile, "id", 0)?,
+            image: child(&tile, "image").map(|image| join_path(directory, attribute(&image, "source"))),
+            animation,
+        });
+    }
+    Ok(RawTileset {
+        first_gid,
+        tile_size: (number(node, "tilewidth", 0)?, number(node, "tileheight", 0)?),
+        spacing: number(node, "spacing", 0)?,
+        margin: number(node, "margin", 0)?,
+        columns: number(node, "columns", 0)?,
+        tile_count: number(node, "tilecount", 0)?,
+        image: child(node, "image").map(|image| join_path(directory, attribute(&image, "source"))),
+        tiles,
+    })
+}
+
+fn xml_gids(data: &Node) -> Result<Vec<u32>, &'static str>{
+    match data.attribute("encoding"){
+        Some(encoding) => decode_gids(data.text().unwrap_or(""), encoding, attribute(data, "compression")),
+        // Oldest format has element for every tile
+        None => data.children().filter(|tile| tile.has_tag_name("tile")).map(|tile| number(&tile, "gid", 0)).collect()
+    }
+}
+
+fn xml_layers(node: &Node, group: &GroupState, layers: &mut Vec<RawLayer>) -> Result<(), &'static str>{
+    for layer in node.children().filter(|child| child.is_element()){
+        let visible = group.visible && attribute(&layer, "visible") != "0";
+        let opacity = group.opacity * number(&layer, "opacity", 1.0)?;
+        let offset = group.offset + vec2(number(&layer, "offsetx", 0.0)?, number(&layer, "offsety", 0.0)?);
+        let kind = match layer.tag_name().name(){
+            "layer" => {
+                let data = child(&layer, "data").ok_or("Tile layer doesn't have data")?;
+                let mut chunks = vec![];
+                for chunk in data.children().filter(|chunk| chunk.has_tag_name("chunk")){
+                    chunks.push(RawChunk {
+                        position: (number(&chunk, "x", 0)?, number(&chunk, "y", 0)?),
+                        size: (number(&chunk, "width", 0)?, number(&chunk, "height", 0)?),
+                        gids: xml_gids_of_chunk(&data, &chunk)?,
+                    });
+                }
+                // Finite maps have one chunk with all tiles
+                if chunks.is_empty(){
+                    chunks.push(RawChunk {
+                        position: (0, 0),
+                        size: (number(&layer, "width", 0)?, number(&layer, "height", 0)?),
+                        gids: xml_gids(&data)?,
+                    });
+                }
+                RawLayerKind::Tiles(chunks)
+            }
+            "objectgroup" => {
+                let mut objects = vec![];
+                for object in layer.children().filter(|object| object.has_tag_name("object")){
+                    objects.push(xml_object(&object)?);
+                }
+                RawLayerKind::Objects(objects)
+            }
+            "group" => {
+                xml_layers(&layer, &GroupState { visible, opacity, offset }, layers)?;
+                continue;
+            }
+            // Image layers aren't supported
+            _ => continue
+        };
+        layers.push(RawLayer {
+            name: attribute(&layer, "name").to_string(),
+            visible,
+            opacity,
+            tint: layer.attribute("tintcolor").map(color).transpose()?,
+            offset,
+            kind,
+            properties: xml_properties(&layer)?,
+        });
+    }
+    Ok(())
+}
+
+/// Chunk is encoded like data of its layer
+fn xml_gids_of_chunk(data: &Node, chunk: &Node) -> Result<Vec<u32>, &'static str>{
+    match data.attribute("encoding"){
+        Some(encoding) => decode_gids(chunk.text().unwrap_or(""), encoding, attribute(data, "compression")),
+        None => chunk.children().filter(|tile| tile.has_tag_name("tile")).map(|tile| number(&tile, "gid", 0)).collect()
+    }
+}
+
+fn xml_object(object: &Node) -> Result<RawObject, &'static str>{
+    let shape = if child(object, "ellipse").is_some(){
+        TiledShape::Ellipse
+    }
+    else if child(object, "point").is_some(){
+        TiledShape::Point
+    }
+    else if let Some(polygon) = child(object, "polygon"){
+        TiledShape::Polygon(points(attribute(&polygon, "points")))
+    }
+    else if let Some(polyline) = child(object, "polyline"){
+        TiledShape::Polyline(points(attribute(&polyline, "points")))
+    }
+    else if let Some(text) = child(object, "text"){
+        TiledShape::Text(text.text().unwrap_or("").to_string())
+    }
+    else{
+        TiledShape::Rectangle
+    };
+    Ok(RawObject {
+        id: number(object, "id", 0)?,
+        name: attribute(object, "name").to_string(),
+        // Tiled 1.9 renamed type to class
+        class: object.attribute("class").or(object.attribute("type")).unwrap_or("").to_string(),
+        position: vec2(number(object, "x", 0.0)?, number(object, "y", 0.0)?),
+        size: vec2(number(object, "width", 0.0)?, number(object, "height", 0.0)?),
+        rotation: number(object, "rotation", 0.0)?,
+        gid: object.attribute("gid").map(|gid| gid.parse().map_err(|_| "Object has invalid gid")).transpose()?,
+        visible: attribute(object, "visible") != "0",
+        shape,
+        properties: xml_properties(object)?,
+    })
+}
+
+// TMJ
+
+fn json_u32(value: &Value, name: &str) -> u32{
+    value[name].as_u64().unwrap_or(0) as u32
+}
+
+fn json_f32(value: &Value, name: &str, default: f32) -> f32{
+    value[name].as_f64().map(|number| number as f32).unwrap_or(default)
+}
+
+fn json_str<'a>(value: &'a Value, name: &str) -> &'a str{
+    value[name].as_str().unwrap_or("")
+}
+
+fn json_properties(value: &Value) -> Result<TiledProperties, &'static str>{
+    let mut properties = HashMap::new();
+    for item in value["properties"].as_array().into_iter().flatten(){
+        let property_value = &item["value"];
+        let value = match (json_str(item, "type"), property_value){
+            ("class", Value::Object(members)) => TiledProperty::Class(TiledProperties(members.iter().map(|(name, member)| (name.clone(), json_value(member))).collect())),
+            (kind, Value::String(text)) => property(kind, text)?,
+            (_, other) => json_value(other)
+        };
+        properties.insert(json_str(item, "name").to_string(), value);
+    }
+    Ok(TiledProperties(properties))
+}
+
+/// Members of class properties don't have types in TMJ
+fn json_value(value: &Value) -> TiledProperty{
+    match value{
+        Value::Bool(value) => TiledProperty::Bool(*value),
+        Value::Number(number) if number.is_i64() => TiledProperty::Int(number.as_i64().unwrap_or(0)),
+        Value::Number(number) => TiledProperty::Float(number.as_f64().unwrap_or(0.0)),
+        Value::String(text) => TiledProperty::String(text.clone()),
+        Value::Object(members) => TiledProperty::Class(TiledProperties(members.iter().map(|(name, member)| (name.clone(), json_value(member))).collect())),
+        _ => TiledProperty::String(value.to_string())
+    }
+}
+
+pub(crate) fn parse_tmj(json: &str, files: &dyn Fn(&str) -> Option<Vec<u8>>) -> Result<RawMap, &'static str>{
+    let map: Value = serde_json::from_str(json).map_err(|_| "TMJ can't be parsed")?;
+    let mut tilesets = vec![];
+    for tileset in map["tilesets"].as_array().into_iter().flatten(){
+        let first_gid = json_u32(tileset, "firstgid");
+        match tileset["source"].as_str(){
+            Some(source) => {
+                let bytes = files(source).ok_or("External tileset can't be read")?;
+                let text = std::str::from_utf8(&bytes).map_err(|_| "External tileset isn't valid UTF-8")?;
+                tilesets.push(parse_external_tileset(text, first_gid, directory_of(source))?);
+            }
+            None => tilesets.push(json_tileset(tileset, first_gid, "")?)
+        }
+    }
+    let mut layers = vec![];
+    json_layers(&map["layers"], &ROOT_GROUP, &mut layers)?;
+    Ok(RawMap {
+        orientation: json_str(&map, "orientation").to_string(),
+        size: (json_u32(&map, "width"), json_u32(&map, "height")),
+        tile_size: (json_u32(&map, "tilewidth"), json_u32(&map, "tileheight")),
+        background: map["backgroundcolor"].as_str().map(color).transpose()?,
+        tilesets,
+        layers,
+        properties: json_properties(&map)?,
+    })
+}
+
+fn json_tileset(tileset: &Value, first_gid: u32, directory: &str) -> Result<RawTileset, &'static str>{
+    let tiles = tileset["tiles"].as_array().into_iter().flatten().map(|tile| RawTile {
+        id: json_u32(tile, "id"),
+        image: tile["image"].as_str().map(|image| join_path(directory, image)),
+        animation: tile["animation"].as_array().into_iter().flatten()
+            .map(|frame| (json_u32(frame, "tileid"), json_f32(frame, "duration", 100.0) / 1000.0))
+            .collect(),
+    }).collect();
+    Ok(RawTileset {
+        first_gid,
+        tile_size: (json_u32(tileset, "tilewidth"), json_u32(tileset, "tileheight")),
+        spacing: json_u32(tileset, "spacing"),
+        margin: json_u32(tileset, "margin"),
+        columns: json_u32(tileset, "columns"),
+        tile_count: json_u32(tileset, "tilecount"),
+        image: tileset["image"].as_str().map(|image| join_path(directory, image)),
+        tiles,
+    })
+}
+
+fn json_gids(data: &Value, layer: &Value) -> Result<Vec<u32>, &'static str>{
+    match data{
+        Value::Array(gids) => Ok(gids.iter().map(|gid| gid.as_u64().unwrap_or(0) as u32).collect()),
+        Value::String(text) => decode_gids(text, "base64", json_str(layer, "compression")),
+        _ => Err("Tile layer doesn't have data")
+    }
+}
+
+fn json_layers(list: &Value, group: &GroupState, layers: &mut Vec<RawLayer>) -> Result<(), &'static str>{
+    for layer in list.as_array().into_iter().flatten(){
+        let visible = group.visible && layer["visible"].as_bool().unwrap_or(true);
+        let opacity = group.opacity * json_f32(layer, "opacity", 1.0);
+        let offset = group.offset + vec2(json_f32(layer, "offsetx", 0.0), json_f32(layer, "offsety", 0.0));
+        let kind = match json_str(layer, "type"){
+            "tilelayer" => {
+                let chunks = match layer["chunks"].as_array(){
+                    Some(chunks) => chunks.iter().map(|chunk| Ok(RawChunk {
+                        position: (json_f32(chunk, "x", 0.0) as i32, json_f32(chunk, "y", 0.0) as i32),
+                        size: (json_u32(chunk, "width"), json_u32(chunk, "height")),
+                        gids: json_gids(&chunk["data"], layer)?,
+                    })).collect::<Result<Vec<RawChunk>, &'static str>>()?,
+                    None => vec![RawChunk {
+                        position: (0, 0),
+                        size: (json_u32(layer, "width"), json_u32(layer, "height")),
+                        gids: json_gids(&layer["data"], layer)?,
+                    }]
+                };
+                RawLayerKind::Tiles(chunks)
+            }
+            "objectgroup" => RawLayerKind::Objects(
+                layer["objects"].as_array().into_iter().flatten().map(json_object).collect::<Result<Vec<RawObject>, &'static str>>()?
+            ),
+            "group" => {
+                json_layers(&layer["layers"], &GroupState { visible, opacity, offset }, layers)?;
+                continue;
+            }
+            _ => continue
+        };
+        layers.push(RawLayer {
+            name: json_str(layer, "name").to_string(),
+            visible,
+            opacity,
+            tint: layer["tintcolor"].as_str().map(color).transpose()?,
+            offset,
+            kind,
+            properties: json_properties(layer)?,
+        });
+    }
+    Ok(())
+}
+
+fn json_object(object: &Value) -> Result<RawObject, &'static str>{
+    let json_points = |name: &str| object[name].as_array().into_iter().flatten()
+        .map(|point| vec2(json_f32(point, "x", 0.0), json_f32(point, "y", 0.0)))
+        .collect();
+    let shape = if object["ellipse"].as_bool().unwrap_or(false){
+        TiledShape::Ellipse
+    }
+    else if object["point"].as_bool().unwrap_or(false){
+        TiledShape::Point
+    }
+    else if object["polygon"].is_array(){
+        TiledShape::Polygon(json_points("polygon"))
+    }
+    else if object["polyline"].is_array(){
+        TiledShape::Polyline(json_points("polyline"))
+    }
+    else if object["text"].is_object(){
+        TiledShape::Text(json_str(&object["text"], "text").to_string())
+    }
+    else{
+        TiledShape::Rectangle
+    };
+    Ok(RawObject {
+        id: json_u32(object, "id"),
+        name: json_str(object, "name").to_string(),
+        class: object["class"].as_str().or(object["type"].as_str()).unwrap_or("").to_string(),
+        position: vec2(json_f32(object, "x", 0.0), json_f32(object, "y", 0.0)),
+        size: vec2(json_f32(object, "width", 0.0), json_f32(object, "height", 0.0)),
+        rotation: json_f32(object, "rotation", 0.0),
+        gid: object["gid"].as_u64().map(|gid| gid as u32),
+        visible: object["visible"].as_bool().unwrap_or(true),
+        shape,
+        properties: json_properties(object)?,
+    })
+}
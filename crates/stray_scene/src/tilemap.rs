@@ -0,0 +1,502 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::{vec2, Vec2};
+use wgpu::{*, util::DeviceExt};
+
+use stray_material::{StrayColor, StandardMaterial};
+use stray_texture::{TextureAtlas, RenderTargets, AnimationClip};
+
+use crate::engine_data::{TextureVertex, RawVertex, RenderObject, StrayVertexBuffer, StrayIndexBuffer};
+use crate::types::Transform2D;
+
+/// Number of tiles on each side of chunk, chunk is one mesh
+pub const CHUNK_SIZE: u32 = 32;
+
+/// How cells of tilemap are placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TilemapOrientation{
+    // Rows go down, tile (0,0) is in top left corner
+    #[default]
+    Orthogonal,
+    // Diamond cells, x goes right and down, y goes left and down, tile (0,0) is at top
+    Isometric,
+}
+
+/// ## Overview
+/// Frame of tileset drawn in cell of tilemap. Flags are applied like in Tiled,
+/// first diagonal flip swaps x and y, then tile is flipped horizontally and vertically
+///
+/// ## Example
+///
+/// ```
+/// use stray_scene::*;
+///
+/// let tile = Tile::new(4).with_rotation(1);
+/// assert!(tile.flip_diagonal && tile.flip_x && !tile.flip_y);
+/// assert_eq!(Tile::new(4).with_rotation(2), Tile::new(4).with_flip(true, true));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile{
+    // Index of frame in tileset
+    pub index: usize,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub flip_diagonal: bool,
+    // Color which multiplies texture
+    pub tint: StrayColor,
+}
+
+impl Tile{
+    pub fn new(index: usize) -> Self{
+        Self { index, flip_x: false, flip_y: false, flip_diagonal: false, tint: StrayColor::WHITE }
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self{
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Rotates tile clockwise by quarter turns, it replaces flip flags
+    pub fn with_rotation(mut self, quarter_turns: u32) -> Self{
+        (self.flip_diagonal, self.flip_x, self.flip_y) = match quarter_turns % 4{
+            1 => (true, true, false),
+            2 => (false, true, true),
+            3 => (true, false, true),
+            _ => (false, false, false)
+        };
+        self
+    }
+
+    pub fn with_tint(mut self, tint: StrayColor) -> Self{
+        self.tint = tint;
+        self
+    }
+
+    /// Point of frame which is drawn at ``(u, v)`` of cell, (0,0) is top left corner
+    fn frame_point(&self, u: f32, v: f32) -> (f32,f32){
+        // Flags are undone in reverse order, because point of cell is mapped back to frame
+        let u = if self.flip_x { 1.0 - u } else { u };
+        let v = if self.flip_y { 1.0 - v } else { v };
+        if self.flip_diagonal { (v, u) } else { (u, v) }
+    }
+}
+
+#[derive(Clone)]
+struct ChunkMesh{
+    vertex: Arc<Buffer>,
+    vertex_count: u32,
+    index: Arc<Buffer>,
+    index_count: u32,
+}
+
+/// Mesh of chunk, it's kept until tile in chunk changes
+#[derive(Default)]
+struct TileChunk{
+    dirty: bool,
+    mesh: Option<ChunkMesh>,
+}
+
+/// Grid of tiles drawn on one render layer
+pub struct TileLayer{
+    pub name: String,
+    pub layer: i32,
+    pub visible: bool,
+    // Offset of tiles in pixels, set with Tilemap::set_layer_offset
+    offset: Vec2,
+    tiles: Vec<Option<Tile>>,
+    chunks: Vec<TileChunk>,
+}
+
+impl TileLayer{
+    pub fn offset(&self) -> Vec2{
+        self.offset
+    }
+}
+
+/// ## Overview
+/// Grid of tiles from one tileset with multiple layers. Layers are split into chunks of ``CHUNK_SIZE`` tiles,
+/// each chunk is one mesh which is built again only when its tiles or transform of tilemap change.
+///
+/// Position of transform is top left corner of orthogonal map or top corner of isometric map.
+/// Frames of tileset are stretched over cells, unless ``frame_scale`` is set.
+/// Tiles can be animated, animated tile shows frames of its clip in place of its index
+///
+/// ## Example
+///
+/// ```ignore
+/// let tileset = TextureAtlas::from_grid(Sprite::new(include_bytes!("tiles.png"), 0).texture, (16, 16), (0, 0), (0, 0));
+/// let mut map = Tilemap::new(tileset, (64, 64), vec2(32.0, 32.0)).with_layer("ground", 0).with_layer("decor", 1);
+/// map.fill(0, Some(Tile::new(0)));
+/// map.set(1, 3, 4, Some(Tile::new(7).with_rotation(1).with_tint(StrayColor::RED)));
+/// Stray::new()
+///     .push((Transform2D::ZERO, map))
+///     .build()
+///     .run();
+/// ```
+pub struct Tilemap{
+    pub tileset: TextureAtlas,
+    // Size of cell in pixels
+    pub tile_size: Vec2,
+    pub orientation: TilemapOrientation,
+    // Size of texture pixel, tiles keep size of their frames and stand on bottom of cell like in Tiled
+    pub frame_scale: Option<Vec2>,
+    // Number of tiles in row and column
+    size: (u32,u32),
+    layers: Vec<TileLayer>,
+    // Tileset is uploaded only once
+    uploaded: Option<(TextureView, Sampler)>,
+    // Chunks are built again when tilemap is moved
+    built_transform: Option<Transform2D>,
+    // Clips of animated tiles by index of tile
+    animations: HashMap<usize, AnimationClip>,
+    // Frames which animated tiles show now
+    shown_frames: HashMap<usize, usize>,
+    animation_time: f32,
+}
+
+impl Tilemap{
+    /// Tilemap without layers
+    pub fn new(tileset: TextureAtlas, size: (u32,u32), tile_size: Vec2) -> Self{
+        Self {
+            tileset,
+            tile_size,
+            orientation: TilemapOrientation::Orthogonal,
+            frame_scale: None,
+            size,
+            layers: vec![],
+            uploaded: None,
+            built_transform: None,
+            animations: HashMap::new(),
+            shown_frames: HashMap::new(),
+            animation_time: 0.0,
+        }
+    }
+
+    pub fn with_orientation(mut self, orientation: TilemapOrientation) -> Self{
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn with_frame_scale(mut self, frame_scale: Vec2) -> Self{
+        self.frame_scale = Some(frame_scale);
+        self
+    }
+
+    pub fn with_layer(mut self, name: &str, layer: i32) -> Self{
+        self.add_layer(name, layer);
+        self
+    }
+
+    /// Adds empty layer drawn on render layer and returns its index
+    pub fn add_layer(&mut self, name: &str, layer: i32) -> usize{
+        let chunk_count = self.chunk_columns() * self.chunk_rows();
+        self.layers.push(TileLayer {
+            name: name.to_string(),
+            layer,
+            visible: true,
+            offset: Vec2::ZERO,
+            tiles: vec![None; (self.size.0 * self.size.1) as usize],
+            chunks: (0..chunk_count).map(|_| TileChunk::default()).collect(),
+        });
+        self.layers.len() - 1
+    }
+
+    pub fn size(&self) -> (u32,u32){
+        self.size
+    }
+
+    pub fn layers(&self) -> &[TileLayer]{
+        &self.layers
+    }
+
+    /// Layer can be hidden or moved to another render layer, tiles are changed with ``set``
+    pub fn layer_mut(&mut self, layer: usize) -> Option<&mut TileLayer>{
+        self.layers.get_mut(layer)
+    }
+
+    /// Moves tiles of layer by pixels, y points up
+    pub fn set_layer_offset(&mut self, layer: usize, offset: Vec2){
+        if let Some(layer) = self.layers.get_mut(layer){
+            if layer.offset != offset{
+                layer.offset = offset;
+                layer.chunks.iter_mut().for_each(|chunk| chunk.dirty = true);
+            }
+        }
+    }
+
+    pub fn layer_index(&self, name: &str) -> Option<usize>{
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    pub fn get(&self, layer: usize, x: u32, y: u32) -> Option<Tile>{
+        if x >= self.size.0 || y >= self.size.1{
+            return None;
+        }
+        self.layers.get(layer).and_then(|layer| layer.tiles[(y * self.size.0 + x) as usize])
+    }
+
+    /// Places or removes tile, returns false if cell or layer is outside of tilemap
+    pub fn set(&mut self, layer: usize, x: u32, y: u32, tile: Option<Tile>) -> bool{
+        if x >= self.size.0 || y >= self.size.1 || layer >= self.layers.len(){
+            return false;
+        }
+        let chunk = self.chunk_index(x, y);
+        let layer = &mut self.layers[layer];
+        let cell = &mut layer.tiles[(y * self.size.0 + x) as usize];
+        if *cell != tile{
+            *cell = tile;
+            layer.chunks[chunk].dirty = true;
+        }
+        true
+    }
+
+    /// Places tile in every cell of layer
+    pub fn fill(&mut self, layer: usize, tile: Option<Tile>){
+        if let Some(layer) = self.layers.get_mut(layer){
+            layer.tiles.iter_mut().for_each(|cell| *cell = tile);
+            layer.chunks.iter_mut().for_each(|chunk| chunk.dirty = true);
+        }
+    }
+
+    /// Center of cell relative to transform
+    pub fn tile_to_local(&self, x: u32, y: u32) -> Vec2{
+        let (left, top) = self.cell_corner(x, y);
+        vec2(left + self.tile_size.x / 2.0, top - self.tile_size.y / 2.0)
+    }
+
+    /// Cell which contains point relative to transform, ``None`` outside of tilemap
+    pub fn local_to_tile(&self, position: Vec2) -> Option<(u32,u32)>{
+        let (x, y) = match self.orientation{
+            TilemapOrientation::Orthogonal => (position.x / self.tile_size.x, -position.y / self.tile_size.y),
+            TilemapOrientation::Isometric => {
+                let (column, row) = (position.x / (self.tile_size.x / 2.0), -position.y / (self.tile_size.y / 2.0));
+                ((row + column) / 2.0, (row - column) / 2.0)
+            }
+        };
+        let (x, y) = (x.floor(), y.floor());
+        if x < 0.0 || y < 0.0 || x >= self.size.0 as f32 || y >= self.size.1 as f32{
+            return None;
+        }
+        Some((x as u32, y as u32))
+    }
+
+    /// Top left corner of rectangle around cell
+    fn cell_corner(&self, x: u32, y: u32) -> (f32,f32){
+        let (x, y) = (x as f32, y as f32);
+        match self.orientation{
+            TilemapOrientation::Orthogonal => (x * self.tile_size.x, -y * self.tile_size.y),
+            TilemapOrientation::Isometric => ((x - y - 1.0) * self.tile_size.x / 2.0, -(x + y) * self.tile_size.y / 2.0)
+        }
+    }
+
+    /// ## Overview
+    /// Animates tile, every cell with tile of this index shows frames of clip.
+    /// Frames of clip are indices of tileset
+    pub fn add_animation(&mut self, tile: usize, clip: AnimationClip){
+        if let Some(&frame) = clip.frames.first(){
+            self.shown_frames.insert(tile, frame);
+        }
+        self.animations.insert(tile, clip);
+        self.mark_tile_dirty(tile);
+    }
+
+    /// Advances animated tiles, chunks are built again only when shown frame changes
+    pub fn update(&mut self, delta: f32){
+        if self.animations.is_empty(){
+            return;
+        }
+        self.animation_time += delta;
+        let mut changed = vec![];
+        for (&tile, clip) in self.animations.iter(){
+            let duration = clip.duration();
+            if clip.frames.is_empty() || duration <= 0.0{
+                continue;
+            }
+            let mut time = self.animation_time % duration;
+            let mut position = 0;
+            while position + 1 < clip.frames.len() && time >= clip.frame_duration(position){
+                time -= clip.frame_duration(position);
+                position += 1;
+            }
+            let frame = clip.frames[position];
+            if self.shown_frames.insert(tile, frame) != Some(frame){
+                changed.push(tile);
+            }
+        }
+        for tile in changed{
+            self.mark_tile_dirty(tile);
+        }
+    }
+
+    fn mark_tile_dirty(&mut self, tile: usize){
+        let width = self.size.0;
+        let chunk_columns = self.chunk_columns();
+        for layer in self.layers.iter_mut(){
+            for (cell, placed) in layer.tiles.iter().enumerate(){
+                if placed.map(|placed| placed.index) == Some(tile){
+                    let (x, y) = (cell as u32 % width, cell as u32 / width);
+                    layer.chunks[(y / CHUNK_SIZE) as usize * chunk_columns + (x / CHUNK_SIZE) as usize].dirty = true;
+                }
+            }
+        }
+    }
+
+    fn chunk_columns(&self) -> usize{
+        self.size.0.div_ceil(CHUNK_SIZE) as usize
+    }
+
+    fn chunk_rows(&self) -> usize{
+        self.size.1.div_ceil(CHUNK_SIZE) as usize
+    }
+
+    fn chunk_index(&self, x: u32, y: u32) -> usize{
+        (y / CHUNK_SIZE) as usize * self.chunk_columns() + (x / CHUNK_SIZE) as usize
+    }
+
+    /// Vertices and indices of chunk, tiles whose frames aren't in tileset are skipped
+    fn chunk_vertices(&self, layer: &TileLayer, chunk: usize, texture_size: (u32,u32)) -> (Vec<TextureVertex>, Vec<u16>){
+        let (chunk_x, chunk_y) = ((chunk % self.chunk_columns()) as u32 * CHUNK_SIZE, (chunk / self.chunk_columns()) as u32 * CHUNK_SIZE);
+        let mut vertices = vec![];
+        let mut indices = vec![];
+        for y in chunk_y..(chunk_y + CHUNK_SIZE).min(self.size.1){
+            for x in chunk_x..(chunk_x + CHUNK_SIZE).min(self.size.0){
+                let tile = match layer.tiles[(y * self.size.0 + x) as usize]{
+                    Some(tile) => tile,
+                    None => continue
+                };
+                let index = self.shown_frames.get(&tile.index).copied().unwrap_or(tile.index);
+                let frame = match self.tileset.frame(index){
+                    Some(frame) => frame,
+                    None => continue
+                };
+                let first = vertices.len() as u16;
+                let (left, top, size) = self.tile_rect(x, y, &tile, frame.source_size);
+                let (left, top) = (left + layer.offset.x, top + layer.offset.y);
+                for (u, v) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]{
+                    let (frame_u, frame_v) = tile.frame_point(u, v);
+                    let (tex_x, tex_y) = frame.uv(frame_u, frame_v, texture_size);
+                    let mut vertex = TextureVertex::new(left + u * size.x, top - v * size.y, layer.layer, tex_x, tex_y);
+                    vertex.material = StandardMaterial::new(tile.tint);
+                    vertices.push(vertex);
+                }
+                indices.extend_from_slice(&[first, first + 2, first + 1, first, first + 3, first + 2]);
+            }
+        }
+        (vertices, indices)
+    }
+
+    /// Top left corner and size of quad of tile
+    fn tile_rect(&self, x: u32, y: u32, tile: &Tile, frame_size: (u32,u32)) -> (f32, f32, Vec2){
+        let (left, top) = self.cell_corner(x, y);
+        let scale = match self.frame_scale{
+            Some(scale) => scale,
+            None => return (left, top, self.tile_size)
+        };
+        let (width, height) = if tile.flip_diagonal { (frame_size.1, frame_size.0) } else { frame_size };
+        let size = vec2(width as f32, height as f32) * scale;
+        let bottom = top - self.tile_size.y;
+        match self.orientation{
+            TilemapOrientation::Orthogonal => (left, bottom + size.y, size),
+            TilemapOrientation::Isometric => (left + (self.tile_size.x - size.x) / 2.0, bottom + size.y, size)
+        }
+    }
+
+    fn bind_group(&mut self, device: &Device, queue: &Queue, layout: &BindGroupLayout, targets: &RenderTargets) -> Option<BindGroup>{
+        if self.tileset.texture.target().is_some(){
+            return self.tileset.texture.bind_group(device, queue, layout, targets);
+        }
+        if self.uploaded.is_none(){
+            self.uploaded = Some(self.tileset.texture.upload(device, queue));
+        }
+        let (view, sampler) = self.uploaded.as_ref().unwrap();
+        Some(device.create_bind_group(&BindGroupDescriptor {
+            layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                }
+            ],
+            label: Some("tileset_bind_group"),
+        }))
+    }
+
+    /// Tileset is uploaded again next time tilemap is drawn, used after texture of tileset is replaced
+    pub fn reload_tileset(&mut self){
+        self.uploaded = None;
+        self.layers.iter_mut().flat_map(|layer| layer.chunks.iter_mut()).for_each(|chunk| chunk.dirty = true);
+    }
+
+    /// Render object for every chunk of visible layers which has tiles, dirty chunks are built first
+    pub fn to_render_objects(&mut self, device: &Device, queue: &Queue, layout: &BindGroupLayout, targets: &RenderTargets, transform: &Transform2D) -> Vec<RenderObject>{
+        let mut render_objects = vec![];
+        if self.built_transform != Some(*transform){
+            self.built_transform = Some(*transform);
+            self.layers.iter_mut().flat_map(|layer| layer.chunks.iter_mut()).for_each(|chunk| chunk.dirty = true);
+        }
+        let texture_size = self.tileset.texture.size(targets);
+        let source_target = self.tileset.texture.target();
+        // Layers are taken out, so chunks can be built while tilemap is borrowed
+        let mut layers = std::mem::take(&mut self.layers);
+        for layer in layers.iter_mut(){
+            for chunk in 0..layer.chunks.len(){
+                if layer.chunks[chunk].dirty{
+                    let (vertices, indices) = self.chunk_vertices(layer, chunk, texture_size);
+                    layer.chunks[chunk].dirty = false;
+                    layer.chunks[chunk].mesh = if indices.is_empty() { None } else { Some(build_mesh(device, &vertices, &indices, transform)) };
+                }
+            }
+        }
+        self.layers = layers;
+
+        let meshes: Vec<(i32, ChunkMesh)> = self.layers.iter()
+            .filter(|layer| layer.visible)
+            .flat_map(|layer| layer.chunks.iter().filter_map(move |chunk| chunk.mesh.clone().map(|mesh| (layer.layer, mesh))))
+            .collect();
+        for (layer, mesh) in meshes{
+            let bind_group = match self.bind_group(device, queue, layout, targets){
+                Some(bind_group) => bind_group,
+                None => break
+            };
+            render_objects.push(RenderObject {
+                type_id: 1,
+                vertex: Some(StrayVertexBuffer(Some(mesh.vertex), mesh.vertex_count)),
+                index: Some(StrayIndexBuffer(Some(mesh.index), mesh.index_count)),
+                bind_group: Some(Arc::new(bind_group)),
+                source_targets: source_target.into_iter().collect(),
+                layer,
+                y: transform.position.y,
+                material: None,
+                material_bind_group: None,
+                normal_bind_group: None,
+            });
+        }
+        render_objects
+    }
+}
+
+fn build_mesh(device: &Device, vertices: &[TextureVertex], indices: &[u16], transform: &Transform2D) -> ChunkMesh{
+    let vertices: Vec<RawVertex> = vertices.iter().map(|vertex| vertex.to_raw(transform)).collect();
+    let vertex_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+        label: Some("Tilemap Chunk Vertex Buffer"),
+        contents: bytemuck::cast_slice(vertices.as_slice()),
+        usage: BufferUsages::VERTEX,
+    });
+    let index_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+        label: Some("Tilemap Chunk Index Buffer"),
+        contents: bytemuck::cast_slice(indices),
+        usage: BufferUsages::INDEX,
+    });
+    ChunkMesh {
+        vertex: Arc::new(vertex_buffer),
+        vertex_count: vertices.len() as u32,
+        index: Arc::new(index_buffer),
+        index_count: indices.len() as u32,
+    }
+}
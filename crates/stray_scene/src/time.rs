@@ -0,0 +1,46 @@
+use std::time::Instant;
+
+/// ## Overview
+/// Time of frame, it's updated before render schedule, so all systems of one frame see the same values
+///
+/// ## Example
+///
+/// ```
+/// use legion::*;
+/// use stray_scene::*;
+///
+/// #[system(for_each)]
+/// fn rotate(transform: &mut Transform2D, #[resource] time: &Time){
+///     transform.rotation += deg2rad(90.0) * time.delta;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Time{
+    // Seconds since previous frame
+    pub delta: f32,
+    // Seconds since start of app
+    pub elapsed: f32,
+    start: Instant,
+    last: Instant,
+}
+
+impl Time{
+    pub fn new() -> Self{
+        let now = Instant::now();
+        Self { delta: 0.0, elapsed: 0.0, start: now, last: now }
+    }
+
+    /// Starts new frame
+    pub fn update(&mut self){
+        let now = Instant::now();
+        self.delta = (now - self.last).as_secs_f32();
+        self.elapsed = (now - self.start).as_secs_f32();
+        self.last = now;
+    }
+}
+
+impl Default for Time{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,41 +2,179 @@ use std::f32::consts::PI;
 
 use glam::*;
 
+/// ## Overview
+/// Position, rotation and scale of entity.
+/// Rotation is in radians and counter-clockwise, y axis points up
+/// 
+/// ## Example
+/// 
+/// ```
+/// use glam::vec2;
+/// use stray_scene::*;
+///
+/// let mut transform = Transform2D::new(100.0, 0.0, 0.0, 1.0);
+/// transform.translate(vec2(10.0, 0.0));
+/// transform.rotate(deg2rad(90.0));
+/// transform.look_at(vec2(0.0, 0.0));
+/// let point = transform.transform_point(vec2(1.0, 0.0));
+/// ```
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Transform2D{
     pub position: Vec2,
-    pub rotation: i32,
-    pub scale: f32,
+    pub rotation: f32,
+    pub scale: Vec2,
 }
 
 impl Transform2D{
     pub const ZERO: Self = Self{
         position: vec2(0.0,0.0),
-        rotation: 0,
-        scale: 1.0
+        rotation: 0.0,
+        scale: vec2(1.0,1.0)
     };
 
-    pub const fn new(x: i32, y: i32, rotation: i32,scale: f32) -> Self {
-        Self { position: Vec2::new(x as f32, y as f32), rotation: rotation, scale: scale}
+    pub const fn new(x: f32, y: f32, rotation: f32, scale: f32) -> Self {
+        Self { position: Vec2::new(x, y), rotation, scale: Vec2::new(scale, scale)}
+    }
+
+    pub const fn from_translation(position: Vec2) -> Self{
+        Self { position, rotation: 0.0, scale: vec2(1.0,1.0)}
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self{
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec2) -> Self{
+        self.scale = scale;
+        self
+    }
+
+    /// Moves transform by ``delta`` in world space
+    pub fn translate(&mut self, delta: Vec2){
+        self.position += delta;
+    }
+
+    /// Rotates transform by ``angle`` radians
+    pub fn rotate(&mut self, angle: f32){
+        self.rotation += angle;
+    }
+
+    /// Rotates transform so its x axis points at ``target``
+    pub fn look_at(&mut self, target: Vec2){
+        let direction = target - self.position;
+        if direction != Vec2::ZERO{
+            self.rotation = direction.y.atan2(direction.x);
+        }
+    }
+
+    /// Direction of x axis after rotation
+    pub fn right(&self) -> Vec2{
+        Vec2::from_angle(self.rotation)
+    }
+
+    /// Direction of y axis after rotation
+    pub fn up(&self) -> Vec2{
+        Vec2::from_angle(self.rotation).perp()
+    }
+
+    /// Maps point from local space of transform to its parent (or world) space
+    pub fn transform_point(&self, point: Vec2) -> Vec2{
+        self.to_affine2().transform_point2(point)
+    }
+
+    /// Same as ``transform_point`` but ignores position
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2{
+        self.to_affine2().transform_vector2(vector)
+    }
+
+    /// Combines transforms, ``self`` is transform of parent and ``child`` is relative to it.
+    /// Rotated child of parent with non-uniform scale gets skewed, 
+    /// skew can't be stored in ``Transform2D`` so it's lost
+    pub fn mul_transform(&self, child: &Transform2D) -> Self{
+        Self::from_affine2(self.to_affine2() * child.to_affine2())
+    }
+
+    /// Transform which undoes this one, ``t.mul_transform(&t.inverse())`` is identity.
+    /// Zero scale can't be inverted, so inverse of it also has zero scale
+    pub fn inverse(&self) -> Self{
+        if self.scale.x == 0.0 || self.scale.y == 0.0{
+            return Self { position: -self.position, rotation: -self.rotation, scale: Vec2::ZERO };
+        }
+        Self::from_affine2(self.to_affine2().inverse())
+    }
+
+    pub fn to_affine2(&self) -> Affine2{
+        Affine2::from_scale_angle_translation(self.scale, self.rotation, self.position)
+    }
+
+    /// Decomposes affine matrix, skew is lost
+    pub fn from_affine2(affine: Affine2) -> Self{
+        let x_axis = affine.matrix2.x_axis;
+        let scale_x = x_axis.length();
+        let scale_y = if scale_x != 0.0 { affine.matrix2.determinant() / scale_x } else { affine.matrix2.y_axis.length() };
+        Self { 
+            position: affine.translation, 
+            rotation: x_axis.y.atan2(x_axis.x), 
+            scale: vec2(scale_x, scale_y) 
+        }
+    }
+
+    pub fn to_mat3(&self) -> Mat3{
+        Mat3::from(self.to_affine2())
+    }
+
+    pub fn to_mat4(&self) -> Mat4{
+        Mat4::from_scale_rotation_translation(
+            self.scale.extend(1.0), 
+            Quat::from_rotation_z(self.rotation), 
+            self.position.extend(0.0)
+        )
     }
 
     pub fn to_raw(&self) -> [[f32;4];4]{
-        let rotation: Quat = Quat::from_euler(EulerRot::XYZ, 0.0, 0.0, deg2rad(self.rotation as f32));
-        (Mat4::from_translation(
-            vec3(
-                self.position.x, 
-                self.position.y, 
-                0.0
-            )
-        ) * Mat4::from_quat(rotation)).to_cols_array_2d()
+        self.to_mat4().to_cols_array_2d()
+    }
+}
+
+impl Default for Transform2D{
+    fn default() -> Self {
+        Self::ZERO
     }
 }
 
+impl From<Affine2> for Transform2D{
+    fn from(affine: Affine2) -> Self {
+        Self::from_affine2(affine)
+    }
+}
+
+impl From<Transform2D> for Affine2{
+    fn from(transform: Transform2D) -> Self {
+        transform.to_affine2()
+    }
+}
+
+impl From<Transform2D> for Mat3{
+    fn from(transform: Transform2D) -> Self {
+        transform.to_mat3()
+    }
+}
+
+
+/// Highest layer which can be depth tested, layers outside of
+/// ``-MAX_LAYER..=MAX_LAYER`` are clamped
+pub const MAX_LAYER: i32 = 1000;
+
+/// Maps layer to depth in ``[0,1]`` range, higher layer is closer to the screen
+pub fn layer_depth(layer: i32) -> f32{
+    0.5 - layer.clamp(-MAX_LAYER, MAX_LAYER) as f32 / (4 * MAX_LAYER) as f32
+}
 
 pub fn deg2rad(deg: f32) -> f32{
     deg * (PI/180.0)
 }
 
 pub fn rad2deg(rad: f32) -> f32{
-    rad / (PI*180.0)
+    rad * (180.0/PI)
 }
\ No newline at end of file
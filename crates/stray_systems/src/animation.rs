@@ -0,0 +1,28 @@
+use legion::*;
+
+use stray_scene::*;
+
+#[system(for_each)]
+pub fn advance_animators(
+    entity: &Entity,
+    animator: &mut SpriteAnimator,
+    sprite: &mut AtlasSprite,
+    #[resource] time: &Time,
+    #[resource] events: &mut AnimationEvents
+){
+    let clip = match animator.current_clip(){
+        Some(clip) => clip.name.clone(),
+        None => return
+    };
+    for kind in animator.update(time.delta){
+        events.0.push(AnimationEvent { entity: *entity, clip: clip.clone(), kind });
+    }
+    if let Some(frame) = animator.current_frame(){
+        sprite.index = frame;
+    }
+}
+
+#[system(for_each)]
+pub fn animate_tilemaps(tilemap: &mut Tilemap, #[resource] time: &Time){
+    tilemap.update(time.delta);
+}
@@ -0,0 +1,8 @@
+use legion::{*, systems::CommandBuffer};
+
+use stray_scene::*;
+
+#[system(for_each)]
+pub fn switch_ldtk_levels(entity: &Entity, world: &mut LdtkWorld, commands: &mut CommandBuffer){
+    world.apply(*entity, commands);
+}
@@ -1,2 +1,14 @@
 mod render;
-pub use render::*;
\ No newline at end of file
+mod transform;
+mod material;
+mod light;
+mod text;
+mod animation;
+mod ldtk;
+pub use render::*;
+pub use transform::*;
+pub use material::*;
+pub use light::*;
+pub use text::*;
+pub use animation::*;
+pub use ldtk::*;
\ No newline at end of file
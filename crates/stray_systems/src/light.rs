@@ -0,0 +1,36 @@
+use legion::*;
+
+use stray_scene::*;
+
+#[system(for_each)]
+pub fn read_point_lights(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    light: &PointLight2D,
+    #[resource] light_query: &mut LightQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    light_query.0.push(light.to_raw(transform));
+}
+
+#[system(for_each)]
+pub fn read_spot_lights(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    light: &SpotLight2D,
+    #[resource] light_query: &mut LightQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    light_query.0.push(light.to_raw(transform));
+}
+
+#[system(for_each)]
+pub fn read_occluders(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    occluder: &LightOccluder2D,
+    #[resource] light_query: &mut LightQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    light_query.1.extend(occluder.edges(transform));
+}
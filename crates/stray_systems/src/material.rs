@@ -0,0 +1,99 @@
+use std::any::TypeId;
+
+use legion::*;
+use wgpu::*;
+
+use stray_scene::*;
+use stray_material::Material2D;
+use stray_texture::{RenderTargets, StrayTexture};
+use stray_render::{prepare_material_pipeline, create_material_bind_group, RenderSettings};
+
+// Pipeline of material is built before its sprites and draws are read
+#[system]
+pub fn prepare_material_pipelines<M: Material2D>(
+    #[resource] texture_pipeline: &StrayTextureRenderPipeline,
+    #[resource] camera_layout: &StrayCameraLayout,
+    #[resource] device: &EngineData<Device>,
+    #[resource] config: &EngineData<SurfaceConfiguration>,
+    #[resource] render_settings: &RenderSettings,
+    #[resource] material_pipelines: &mut MaterialPipelines,
+){
+    prepare_material_pipeline::<M>(material_pipelines, &device.0, &config.0, camera_layout, &texture_pipeline.1, render_settings);
+}
+
+#[allow(clippy::too_many_arguments)]
+#[system(for_each)]
+pub fn read_material_sprites<M: Material2D>(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    material_sprite: &mut MaterialSprite<M>,
+    #[resource] texture_pipeline: &StrayTextureRenderPipeline,
+    #[resource] device: &EngineData<Device>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
+    #[resource] material_pipelines: &MaterialPipelines,
+    #[resource] render_query: &mut RenderQuery
+){
+    let material_layout = match material_pipelines.0.get(&TypeId::of::<M>()){
+        Some((_, layout)) => layout,
+        None => return
+    };
+    let MaterialSprite{sprite, material, bind_groups} = material_sprite;
+    let cached = bind_groups.get_or_create(&sprite.texture, material, render_targets, || {
+        let bind_group = sprite.texture.bind_group(&device.0, &queue.0, &texture_pipeline.1, render_targets)?;
+        let (material_bind_group, source_targets) = create_material_bind_group(material, &device.0, &queue.0, material_layout, render_targets)?;
+        Some((bind_group, material_bind_group, source_targets))
+    });
+    if let Some((bind_group, material_bind_group, source_targets)) = cached{
+        let dimensions = sprite.texture.size(render_targets);
+        let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+        render_query.0.push(
+            sprite.to_render_object_with(&device.0, transform, dimensions, bind_group)
+                .with_material(TypeId::of::<M>(), material_bind_group, &source_targets)
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[system(for_each)]
+pub fn read_material_draws<M: Material2D>(
+    material_draw: &mut MaterialDraw<M>,
+    global_transform: Option<&GlobalTransform2D>,
+    #[resource] texture_pipeline: &StrayTextureRenderPipeline,
+    #[resource] device: &EngineData<Device>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
+    #[resource] material_pipelines: &MaterialPipelines,
+    #[resource] render_query: &mut RenderQuery
+){
+    let material_layout = match material_pipelines.0.get(&TypeId::of::<M>()){
+        Some((_, layout)) => layout,
+        None => return
+    };
+    let MaterialDraw{draw, material, bind_groups} = material_draw;
+    // Transform of ScreenDraw is relative to entity when it's in hierarchy
+    let local_transform = draw.transform;
+    if let Some(global) = global_transform{
+        draw.transform = global.0.mul_transform(&local_transform);
+    }
+    let white;
+    let texture = match &draw.texture{
+        Some(texture) => texture,
+        None => {
+            white = StrayTexture::white();
+            &white
+        }
+    };
+    let cached = bind_groups.get_or_create(texture, material, render_targets, || {
+        let bind_group = texture.bind_group(&device.0, &queue.0, &texture_pipeline.1, render_targets)?;
+        let (material_bind_group, source_targets) = create_material_bind_group(material, &device.0, &queue.0, material_layout, render_targets)?;
+        Some((bind_group, material_bind_group, source_targets))
+    });
+    if let Some((bind_group, material_bind_group, source_targets)) = cached{
+        render_query.0.push(
+            draw.to_textured_render_object(&device.0, bind_group)
+                .with_material(TypeId::of::<M>(), material_bind_group, &source_targets)
+        );
+    }
+    draw.transform = local_transform;
+}
@@ -1,43 +1,213 @@
-use legion::*;
+use std::sync::Arc;
+
+use legion::{*, world::SubWorld};
 use wgpu::*;
 
 use stray_scene::*;
-use stray_render::render_redraw;
+use stray_texture::RenderTargets;
+use stray_render::{
+    render_redraw, 
+    create_camera_bind_group, 
+    RenderSettings, 
+    StrayDepthBuffers, 
+    StrayMsaaBuffers,
+    PostProcess,
+    StrayPostPipelines,
+    StrayPostTextures
+};
 
-// use smaa::SmaaTarget;
+use smaa::SmaaTarget;
+
+/// Frees gpu textures of render targets which no camera renders into anymore
+#[system]
+#[read_component(Camera2D)]
+pub fn release_render_targets(
+    world: &mut SubWorld,
+    #[resource] render_targets: &mut RenderTargets,
+    #[resource] depth_buffers: &mut StrayDepthBuffers,
+    #[resource] msaa_buffers: &mut StrayMsaaBuffers
+){
+    let used: Vec<u32> = <&Camera2D>::query().iter(world)
+        .filter_map(|camera| camera.target.map(|target| target.id()))
+        .collect();
+    render_targets.retain(&used);
+    depth_buffers.retain(&used);
+    msaa_buffers.retain(&used);
+}
+
+#[allow(clippy::too_many_arguments)]
+#[system(for_each)]
+pub fn read_cameras(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    camera: &Camera2D,
+    #[resource] device: &EngineData<Device>,
+    #[resource] config: &EngineData<SurfaceConfiguration>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] camera_layout: &StrayCameraLayout,
+    #[resource] texture_pipeline: &StrayTextureRenderPipeline,
+    #[resource] render_targets: &mut RenderTargets,
+    #[resource] camera_query: &mut CameraQuery
+) {
+    if let Some(target) = &camera.target{
+        render_targets.prepare(&device.0, config.0.format, target);
+    }
+    let target_size = camera.target_size([config.0.width, config.0.height]);
+    let background = camera.background.as_ref().map(|background| {
+        background.to_render_background(&device.0, &queue.0, &texture_pipeline.1, render_targets, target_size)
+    });
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    let view_projection = camera.view_projection(transform, [config.0.width, config.0.height]);
+    camera_query.0.push(RenderCamera{
+        target: camera.target.map(|target| target.id()),
+        order: camera.order,
+        bind_group: create_camera_bind_group(&device.0, camera_layout, view_projection.to_cols_array_2d()),
+        background,
+    });
+}
 
 #[system(for_each)]
 pub fn read_geometry(
     draw_component: &mut ScreenDraw, 
+    global_transform: Option<&GlobalTransform2D>,
+    #[resource] pipeline: &StrayTextureRenderPipeline,
     #[resource] device: &EngineData<Device>,
-    #[resource] config: &EngineData<SurfaceConfiguration>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
     #[resource] render_query: &mut RenderQuery
 ) {
-    render_query.0.push(draw_component.to_render_object(&device.0, &config.0));
+    // Transform of ScreenDraw is relative to entity when it's in hierarchy
+    let local_transform = draw_component.transform;
+    if let Some(global) = global_transform{
+        draw_component.transform = global.0.mul_transform(&local_transform);
+    }
+    match &draw_component.texture{
+        Some(texture) => {
+            if let Some(bind_group) = texture.bind_group(&device.0, &queue.0, &pipeline.1, render_targets){
+                render_query.0.push(draw_component.to_textured_render_object(&device.0, Arc::new(bind_group)));
+            }
+        }
+        None => render_query.0.push(draw_component.to_render_object(&device.0))
+    }
+    draw_component.transform = local_transform;
+}
+
+#[system(for_each)]
+pub fn read_shapes(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    shape: &mut Shape2D,
+    #[resource] device: &EngineData<Device>,
+    #[resource] render_query: &mut RenderQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    if let Some(render_object) = shape.to_render_object(&device.0, transform){
+        render_query.0.push(render_object);
+    }
+}
+
+#[system(for_each)]
+pub fn read_sdf_shapes(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    shape: &mut SdfShape,
+    #[resource] device: &EngineData<Device>,
+    #[resource] render_query: &mut RenderQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    render_query.0.push(shape.to_render_object(&device.0, transform));
 }
 
+#[allow(clippy::too_many_arguments)]
 #[system(for_each)]
 pub fn read_sprites(
     transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
     sprite: &Sprite,
     #[resource] pipeline: &StrayTextureRenderPipeline,
     #[resource] device: &EngineData<Device>,
-    #[resource] config: &EngineData<SurfaceConfiguration>,
     #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
+    #[resource] render_query: &mut RenderQuery
+){
+    if let Some(bind_group) = sprite.texture.bind_group(&device.0, &queue.0, &pipeline.1, render_targets){
+        let dimensions = sprite.texture.size(render_targets);
+        let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+        let render_object = sprite.to_render_object_with(&device.0, transform, dimensions, Arc::new(bind_group));
+        let normal_bind_group = sprite.normal_map.as_ref()
+            .and_then(|normal_map| normal_map.bind_group(&device.0, &queue.0, &pipeline.1, render_targets));
+        match normal_bind_group{
+            Some(normal_bind_group) => render_query.0.push(render_object.with_normal_map(normal_bind_group)),
+            None => render_query.0.push(render_object)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[system(for_each)]
+pub fn read_atlas_sprites(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    sprite: &AtlasSprite,
+    #[resource] pipeline: &StrayTextureRenderPipeline,
+    #[resource] device: &EngineData<Device>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
+    #[resource] render_query: &mut RenderQuery
+){
+    if sprite.frame().is_none(){
+        return;
+    }
+    if let Some(bind_group) = sprite.atlas.texture.bind_group(&device.0, &queue.0, &pipeline.1, render_targets){
+        let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+        if let Some(render_object) = sprite.to_render_object_with(&device.0, transform, bind_group){
+            render_query.0.push(render_object);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[system(for_each)]
+pub fn read_tilemaps(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    tilemap: &mut Tilemap,
+    #[resource] pipeline: &StrayTextureRenderPipeline,
+    #[resource] device: &EngineData<Device>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
     #[resource] render_query: &mut RenderQuery
 ){
-    render_query.0.push(sprite.to_render_object(&device.0, &config.0, &queue.0, &pipeline.1, transform));
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    render_query.0.extend(tilemap.to_render_objects(&device.0, &queue.0, &pipeline.1, render_targets, transform));
 }
 
+#[allow(clippy::too_many_arguments)]
 #[system]
 pub fn redraw(
     #[resource] surface: &EngineData<Surface>, 
     #[resource] device: &EngineData<Device>,
     #[resource] shape_pipeline: &StrayShapeRenderPipeline,
+    #[resource] sdf_pipeline: &StraySdfRenderPipeline,
     #[resource] texture_pipeline: &StrayTextureRenderPipeline,
+    #[resource] background_pipeline: &StrayBackgroundRenderPipeline,
+    #[resource] material_pipelines: &MaterialPipelines,
+    #[resource] camera_layout: &StrayCameraLayout,
     #[resource] queue: &EngineData<Queue>,
+    #[resource] background: &Background,
     #[resource] render_query: &mut RenderQuery,
-   // #[resource] smaa_target: &mut EngineData<SmaaTarget>
+    #[resource] camera_query: &mut CameraQuery,
+    #[resource] render_targets: &RenderTargets,
+    #[resource] render_settings: &RenderSettings,
+    #[resource] depth_buffers: &mut StrayDepthBuffers,
+    #[resource] msaa_buffers: &mut StrayMsaaBuffers,
+    #[resource] smaa_target: &mut EngineData<SmaaTarget>,
+    #[resource] post_process: &PostProcess,
+    #[resource] post_pipelines: &mut StrayPostPipelines,
+    #[resource] post_textures: &mut StrayPostTextures,
+    #[resource] lighting: &StrayLighting,
+    #[resource] ambient_light: &AmbientLight,
+    #[resource] light_query: &mut LightQuery,
 ){
-    render_redraw(&surface.0, &device.0, &shape_pipeline, &texture_pipeline, &queue.0, render_query)
+    render_redraw(&surface.0, &device.0, shape_pipeline, sdf_pipeline, texture_pipeline, background_pipeline, material_pipelines, camera_layout, &queue.0, background, render_query, camera_query, render_targets, render_settings, depth_buffers, msaa_buffers, &mut smaa_target.0, post_process, post_pipelines, post_textures, lighting, ambient_light, light_query)
 }
\ No newline at end of file
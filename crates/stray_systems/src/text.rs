@@ -0,0 +1,48 @@
+use legion::*;
+use wgpu::*;
+
+use stray_scene::*;
+use stray_texture::RenderTargets;
+
+#[system(for_each)]
+pub fn advance_typewriters(
+    text: &mut RichText2D,
+    typewriter: &mut Typewriter,
+    #[resource] time: &Time
+){
+    typewriter.update(text, time.delta);
+}
+
+#[system(for_each)]
+pub fn read_text(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    text: &Text2D,
+    #[resource] pipeline: &StrayTextureRenderPipeline,
+    #[resource] device: &EngineData<Device>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_query: &mut RenderQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    if let Some(render_object) = text.to_render_object(&device.0, &queue.0, &pipeline.1, transform){
+        render_query.0.push(render_object);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+#[system(for_each)]
+pub fn read_rich_text(
+    transform: &Transform2D,
+    global_transform: Option<&GlobalTransform2D>,
+    text: &RichText2D,
+    #[resource] pipeline: &StrayTextureRenderPipeline,
+    #[resource] device: &EngineData<Device>,
+    #[resource] queue: &EngineData<Queue>,
+    #[resource] render_targets: &RenderTargets,
+    #[resource] time: &Time,
+    #[resource] render_query: &mut RenderQuery
+){
+    let transform = global_transform.map(|global| &global.0).unwrap_or(transform);
+    let render_objects = text.to_render_objects(&device.0, &queue.0, &pipeline.1, render_targets, transform, time.elapsed);
+    render_query.0.extend(render_objects);
+}
@@ -0,0 +1,86 @@
+use legion::{
+    *,
+    world::SubWorld,
+    systems::CommandBuffer,
+};
+
+use stray_scene::*;
+
+/// Computes ``GlobalTransform2D`` of every child in hierarchy, starting from root entities
+#[system]
+#[read_component(Transform2D)]
+#[read_component(Children)]
+#[read_component(Parent)]
+#[write_component(GlobalTransform2D)]
+pub fn propagate_transforms(
+    world: &mut SubWorld,
+    cmd: &mut CommandBuffer
+){
+    let roots: Vec<(Transform2D, Vec<Entity>)> = <(&Transform2D, &Children, Option<&Parent>)>::query()
+        .iter(world)
+        .filter(|(_, _, parent)| parent.is_none())
+        .map(|(transform, children, _)| (*transform, children.0.clone()))
+        .collect();
+    for (transform, children) in roots{
+        for child in children{
+            propagate(world, cmd, child, &transform);
+        }
+    }
+}
+
+fn propagate(world: &mut SubWorld, cmd: &mut CommandBuffer, entity: Entity, parent_global: &Transform2D){
+    let (local, children) = match world.entry_ref(entity){
+        Ok(entry) => (
+            entry.get_component::<Transform2D>().ok().copied(),
+            entry.get_component::<Children>().ok().cloned()
+        ),
+        Err(_) => return
+    };
+    let global = parent_global.mul_transform(&local.unwrap_or(Transform2D::ZERO));
+    if let Ok(mut entry) = world.entry_mut(entity){
+        match entry.get_component_mut::<GlobalTransform2D>(){
+            Ok(global_transform) => global_transform.0 = global,
+            Err(_) => cmd.add_component(entity, GlobalTransform2D(global))
+        }
+    }
+    for child in children.unwrap_or_default().0{
+        propagate(world, cmd, child, &global);
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    // Render schedule flushes after propagation, so readers see global transforms on the first frame
+    #[test]
+    fn global_transforms_exist_before_objects_are_read(){
+        let mut world = World::default();
+        let root = world.push((Transform2D::new(100.0, 0.0, 0.0, 2.0),));
+        let child = world.push((Transform2D::new(10.0, 0.0, 0.0, 1.0), Parent(root)));
+        let grandchild = world.push((Transform2D::new(0.0, 5.0, 0.0, 1.0), Parent(child)));
+        world.entry(root).unwrap().add_component(Children(vec![child]));
+        world.entry(child).unwrap().add_component(Children(vec![grandchild]));
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let reader_seen = seen.clone();
+        let mut schedule = Schedule::builder()
+            .add_system(propagate_transforms_system())
+            .flush()
+            .add_thread_local_fn(move |world: &mut World, _| {
+                let mut seen = reader_seen.lock().unwrap();
+                for entity in [child, grandchild]{
+                    let global = world.entry(entity).unwrap().get_component::<GlobalTransform2D>().map(|global| global.0.position).ok();
+                    seen.push(global);
+                }
+            })
+            .build();
+        schedule.execute(&mut world, &mut Resources::default());
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0], Some(Transform2D::new(120.0, 0.0, 0.0, 1.0).position));
+        assert_eq!(seen[1], Some(Transform2D::new(120.0, 10.0, 0.0, 1.0).position));
+    }
+}
@@ -0,0 +1,75 @@
+use std::io::Cursor;
+
+use image::{AnimationDecoder, ImageFormat, RgbaImage, Frame, guess_format, load_from_memory};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+
+use crate::{TextureAtlas, AnimationClip};
+
+/// ## Overview
+/// Frames of animated GIF or APNG packed into atlas with duration of each frame in seconds.
+/// Other images are decoded as animation with one frame
+///
+/// ## Example
+///
+/// ```ignore
+/// let (sprite, animator) = AtlasSprite::animated(include_bytes!("fire.gif"), 0).unwrap();
+/// Stray::new()
+///     .push((Transform2D::ZERO, sprite, animator))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct AnimatedTexture{
+    pub atlas: TextureAtlas,
+    pub durations: Vec<f32>,
+}
+
+impl AnimatedTexture{
+    /// Name of clip which plays all frames
+    pub const CLIP: &'static str = "default";
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str>{
+        let frames = match guess_format(bytes){
+            Ok(ImageFormat::Gif) => {
+                let decoder = GifDecoder::new(Cursor::new(bytes)).map_err(|_| "GIF can't be decoded")?;
+                decoder.into_frames().collect_frames().map_err(|_| "Frame of GIF can't be decoded")?
+            }
+            Ok(ImageFormat::Png) => {
+                let decoder = PngDecoder::new(Cursor::new(bytes)).map_err(|_| "PNG can't be decoded")?;
+                if decoder.is_apng(){
+                    decoder.apng().into_frames().collect_frames().map_err(|_| "Frame of APNG can't be decoded")?
+                }
+                else{
+                    vec![still_frame(bytes)?]
+                }
+            }
+            _ => vec![still_frame(bytes)?]
+        };
+        if frames.is_empty(){
+            return Err("Animation doesn't have frames");
+        }
+        let durations = frames.iter().map(|frame| {
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            let milliseconds = numerator as f32 / denominator.max(1) as f32;
+            // Browsers play frames of 10 ms or less at 100 ms and files rely on it
+            if milliseconds <= 10.0 { 0.1 } else { milliseconds / 1000.0 }
+        }).collect();
+        let images: Vec<RgbaImage> = frames.into_iter().map(|frame| frame.into_buffer()).collect();
+        Ok(Self { atlas: TextureAtlas::pack(&images, 1), durations })
+    }
+
+    pub fn frame_count(&self) -> usize{
+        self.durations.len()
+    }
+
+    /// Looping clip of all frames named ``AnimatedTexture::CLIP``
+    pub fn clip(&self) -> AnimationClip{
+        AnimationClip::new(Self::CLIP, (0..self.durations.len()).collect(), self.durations.clone())
+    }
+}
+
+fn still_frame(bytes: &[u8]) -> Result<Frame, &'static str>{
+    let image = load_from_memory(bytes).map_err(|_| "Image can't be decoded")?;
+    Ok(Frame::new(image.to_rgba8()))
+}
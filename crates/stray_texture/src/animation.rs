@@ -0,0 +1,85 @@
+use crate::TextureAtlas;
+
+/// What happens when animation reaches its last frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationMode{
+    #[default]
+    Loop,
+    // Plays forward and backward
+    PingPong,
+    // Stops on last frame
+    Once,
+}
+
+/// ## Overview
+/// Sequence of atlas frames with duration of each frame in seconds.
+/// Frames can be marked by names, animator emits event when marked frame is shown
+///
+/// ## Example
+///
+/// ```
+/// use stray_texture::*;
+///
+/// let attack = AnimationClip::from_fps("attack", vec![4, 5, 6, 7], 12.0)
+///     .with_mode(AnimationMode::Once)
+///     .with_marker(2, "hit");
+/// assert_eq!(attack.frame_count(), 4);
+/// assert!((attack.duration() - 4.0 / 12.0).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip{
+    pub name: String,
+    // Indices of frames in atlas
+    pub frames: Vec<usize>,
+    // Seconds of each frame, last duration is used for frames without duration
+    pub durations: Vec<f32>,
+    pub mode: AnimationMode,
+    // Position of frame in clip and name of marker
+    pub markers: Vec<(usize, String)>,
+}
+
+impl AnimationClip{
+    pub fn new(name: &str, frames: Vec<usize>, durations: Vec<f32>) -> Self{
+        Self { name: name.to_string(), frames, durations, mode: AnimationMode::Loop, markers: vec![] }
+    }
+
+    /// All frames have the same duration
+    pub fn from_fps(name: &str, frames: Vec<usize>, fps: f32) -> Self{
+        Self::new(name, frames, vec![1.0 / fps])
+    }
+
+    /// Frames of atlas whose names start with prefix, see ``TextureAtlas::indices_with_prefix``
+    pub fn from_prefix(name: &str, atlas: &TextureAtlas, prefix: &str, fps: f32) -> Self{
+        Self::from_fps(name, atlas.indices_with_prefix(prefix), fps)
+    }
+
+    pub fn with_mode(mut self, mode: AnimationMode) -> Self{
+        self.mode = mode;
+        self
+    }
+
+    /// Marks frame at position in clip, not index in atlas
+    pub fn with_marker(mut self, frame: usize, name: &str) -> Self{
+        self.markers.push((frame, name.to_string()));
+        self
+    }
+
+    pub fn frame_count(&self) -> usize{
+        self.frames.len()
+    }
+
+    /// Duration of frame at position in clip
+    pub fn frame_duration(&self, frame: usize) -> f32{
+        self.durations.get(frame).or(self.durations.last()).copied().unwrap_or(0.1)
+    }
+
+    /// Duration of one pass through all frames
+    pub fn duration(&self) -> f32{
+        (0..self.frames.len()).map(|frame| self.frame_duration(frame)).sum()
+    }
+
+    /// Names of markers on frame at position in clip
+    pub fn markers_at(&self, frame: usize) -> impl Iterator<Item = &str>{
+        self.markers.iter().filter(move |(marked, _)| *marked == frame).map(|(_, name)| name.as_str())
+    }
+}
@@ -0,0 +1,536 @@
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
+use image::{RgbaImage, Rgba};
+
+use crate::{TextureAtlas, AtlasFrame, AnimationClip, AnimationMode, TextureSampling};
+
+const LAYER_VISIBLE: u16 = 1;
+const LAYER_BACKGROUND: u16 = 8;
+const LAYER_REFERENCE: u16 = 64;
+const PALETTE_SIZE: usize = 256;
+
+/// ## Overview
+/// Slice of Aseprite sprite in one key, key is used from its frame until next key of slice.
+/// Rectangles are ``(x, y, width, height)`` in pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsepriteSlice{
+    pub name: String,
+    pub frame: usize,
+    // Rectangle in canvas
+    pub bounds: (i32,i32,u32,u32),
+    // Center of 9-slice relative to bounds
+    pub center: Option<(i32,i32,u32,u32)>,
+    // Pivot relative to bounds
+    pub pivot: Option<(i32,i32)>,
+}
+
+/// ## Overview
+/// Sprite imported from ``.ase`` or ``.aseprite`` file. Visible layers of every frame are flattened and frames
+/// are packed into atlas, so frame of file has the same index in atlas. Tags become animation clips and
+/// first key of every slice becomes named frame of atlas with pivot of slice.
+///
+/// Layers are blended as normal layers and tilemap layers are skipped
+///
+/// ## Example
+///
+/// ```ignore
+/// let player = Aseprite::from_bytes(include_bytes!("player.aseprite")).unwrap();
+/// let animator = SpriteAnimator::new()
+///     .with_clips(player.clips.clone())
+///     .playing("run");
+/// Stray::new()
+///     .push((Transform2D::ZERO, AtlasSprite::new(player.atlas, 0, 0), animator))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct Aseprite{
+    pub atlas: TextureAtlas,
+    // Size of canvas
+    pub size: (u32,u32),
+    // Seconds of each frame
+    pub durations: Vec<f32>,
+    pub clips: Vec<AnimationClip>,
+    pub slices: Vec<AsepriteSlice>,
+}
+
+impl Aseprite{
+    /// Parses file, atlas is sampled without filtering like pixel art is drawn in Aseprite
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str>{
+        let file = parse(bytes)?;
+        let images: Vec<RgbaImage> = (0..file.frames.len()).map(|frame| file.flatten(frame)).collect();
+        let mut atlas = TextureAtlas::pack(&images, 1);
+        atlas.texture = atlas.texture.with_sampling(TextureSampling::NEAREST);
+
+        let durations: Vec<f32> = file.frames.iter().map(|frame| frame.duration).collect();
+        let mut clips: Vec<AnimationClip> = file.tags.iter().map(|tag| tag.clip(&durations)).collect();
+        if clips.is_empty() && !durations.is_empty(){
+            clips.push(AnimationClip::new("default", (0..durations.len()).collect(), durations.clone()));
+        }
+
+        let mut named = vec![];
+        for slice in file.slices.iter(){
+            if named.contains(&slice.name){
+                continue;
+            }
+            named.push(slice.name.clone());
+            if let Some(frame) = atlas.frame(slice.frame).copied(){
+                atlas.add_frame(Some(&slice.name), slice_frame(slice, &frame));
+            }
+        }
+        Ok(Self { atlas, size: file.size, durations, clips, slices: file.slices })
+    }
+
+    pub fn clip(&self, name: &str) -> Option<&AnimationClip>{
+        self.clips.iter().find(|clip| clip.name == name)
+    }
+
+    /// Key of slice which is used on frame
+    pub fn slice(&self, name: &str, frame: usize) -> Option<&AsepriteSlice>{
+        self.slices.iter()
+            .filter(|slice| slice.name == name && slice.frame <= frame)
+            .max_by_key(|slice| slice.frame)
+    }
+}
+
+/// Region of slice in packed frame, parts of slice which were trimmed with frame stay outside of region
+fn slice_frame(slice: &AsepriteSlice, frame: &AtlasFrame) -> AtlasFrame{
+    let (x, y, width, height) = slice.bounds;
+    let (frame_left, frame_top) = (frame.offset.0 as i32, frame.offset.1 as i32);
+    let left = x.max(frame_left);
+    let top = y.max(frame_top);
+    let right = (x + width as i32).min(frame_left + frame.size.0 as i32).max(left);
+    let bottom = (y + height as i32).min(frame_top + frame.size.1 as i32).max(top);
+    AtlasFrame {
+        position: ((frame.position.0 as i32 + left - frame_left) as u32, (frame.position.1 as i32 + top - frame_top) as u32),
+        size: ((right - left) as u32, (bottom - top) as u32),
+        rotated: false,
+        source_size: (width, height),
+        offset: ((left - x) as u32, (top - y) as u32),
+        pivot: slice.pivot.map(|(pivot_x, pivot_y)| (pivot_x as f32 / width.max(1) as f32, pivot_y as f32 / height.max(1) as f32)),
+    }
+}
+
+struct Layer{
+    flags: u16,
+    opacity: u8,
+    // Layer isn't drawn when it or one of its groups is hidden
+    visible: bool,
+}
+
+#[derive(Clone)]
+struct Cel{
+    layer: usize,
+    position: (i32,i32),
+    opacity: u8,
+    z_index: i16,
+    image: RgbaImage,
+}
+
+struct Frame{
+    duration: f32,
+    cels: Vec<Cel>,
+}
+
+struct Tag{
+    name: String,
+    from: usize,
+    to: usize,
+    direction: u8,
+    repeat: u16,
+}
+
+impl Tag{
+    fn clip(&self, durations: &[f32]) -> AnimationClip{
+        // Frames past the end of file are dropped, so tag of file without frames gives empty clip
+        let mut frames: Vec<usize> = (self.from..=self.to).take_while(|&frame| frame < durations.len()).collect();
+        // Reverse and ping-pong reverse
+        if self.direction == 1 || self.direction == 3{
+            frames.reverse();
+        }
+        let frame_durations = frames.iter().map(|&frame| durations[frame]).collect();
+        let mode = if self.repeat == 1{
+            AnimationMode::Once
+        }
+        else if self.direction >= 2{
+            AnimationMode::PingPong
+        }
+        else{
+            AnimationMode::Loop
+        };
+        AnimationClip::new(&self.name, frames, frame_durations).with_mode(mode)
+    }
+}
+
+struct AsepriteFile{
+    size: (u32,u32),
+    // Layer opacity is used only since Aseprite 1.1
+    layer_opacity: bool,
+    layers: Vec<Layer>,
+    frames: Vec<Frame>,
+    tags: Vec<Tag>,
+    slices: Vec<AsepriteSlice>,
+}
+
+impl AsepriteFile{
+    /// Cels of visible layers drawn from bottom to top
+    fn flatten(&self, frame: usize) -> RgbaImage{
+        let mut canvas = RgbaImage::new(self.size.0, self.size.1);
+        let mut cels: Vec<&Cel> = self.frames[frame].cels.iter()
+            .filter(|cel| self.layers.get(cel.layer).map(|layer| layer.visible).unwrap_or(false))
+            .collect();
+        // Z-index moves cel between layers, Aseprite sorts cels in the same way
+        cels.sort_by_key(|cel| (cel.layer as i64 + cel.z_index as i64, cel.z_index));
+        for cel in cels{
+            let layer_opacity = if self.layer_opacity { self.layers[cel.layer].opacity } else { 255 };
+            let opacity = cel.opacity as f32 / 255.0 * layer_opacity as f32 / 255.0;
+            for (x, y, pixel) in cel.image.enumerate_pixels(){
+                let (canvas_x, canvas_y) = (cel.position.0 + x as i32, cel.position.1 + y as i32);
+                if canvas_x < 0 || canvas_y < 0 || canvas_x >= self.size.0 as i32 || canvas_y >= self.size.1 as i32{
+                    continue;
+                }
+                let below = canvas.get_pixel_mut(canvas_x as u32, canvas_y as u32);
+                *below = blend(*below, *pixel, opacity);
+            }
+        }
+        canvas
+    }
+}
+
+/// Draws pixel over another pixel, colors aren't premultiplied by alpha
+fn blend(below: Rgba<u8>, above: Rgba<u8>, opacity: f32) -> Rgba<u8>{
+    let above_alpha = above.0[3] as f32 / 255.0 * opacity;
+    let below_alpha = below.0[3] as f32 / 255.0;
+    let alpha = above_alpha + below_alpha * (1.0 - above_alpha);
+    if alpha <= 0.0{
+        return Rgba([0, 0, 0, 0]);
+    }
+    let channel = |i: usize| {
+        ((above.0[i] as f32 * above_alpha + below.0[i] as f32 * below_alpha * (1.0 - above_alpha)) / alpha).round() as u8
+    };
+    Rgba([channel(0), channel(1), channel(2), (alpha * 255.0).round() as u8])
+}
+
+/// Little endian reader of file, reading past end is an error
+struct Reader<'a>{
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a>{
+    fn new(bytes: &'a [u8]) -> Self{
+        Self { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], &'static str>{
+        let bytes = self.bytes.get(self.position..self.position + count).ok_or("Aseprite file is truncated")?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn rest(&mut self) -> &'a [u8]{
+        let bytes = &self.bytes[self.position.min(self.bytes.len())..];
+        self.position = self.bytes.len();
+        bytes
+    }
+
+    fn u8(&mut self) -> Result<u8, &'static str>{
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, &'static str>{
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn i16(&mut self) -> Result<i16, &'static str>{
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> Result<u32, &'static str>{
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, &'static str>{
+        Ok(self.u32()? as i32)
+    }
+
+    fn string(&mut self) -> Result<String, &'static str>{
+        let length = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+}
+
+/// Header values which are needed to read pixels of cels
+struct PixelFormat{
+    color_depth: u16,
+    transparent_index: u8,
+    palette: Vec<Rgba<u8>>,
+}
+
+impl PixelFormat{
+    fn bytes_per_pixel(&self) -> usize{
+        (self.color_depth / 8) as usize
+    }
+
+    fn to_image(&self, width: u32, height: u32, pixels: &[u8], background: bool) -> Result<RgbaImage, &'static str>{
+        let size = width as usize * height as usize * self.bytes_per_pixel();
+        let pixels = pixels.get(..size).ok_or("Aseprite cel has less pixels than its size")?;
+        let mut image = RgbaImage::new(width, height);
+        for (pixel, bytes) in image.pixels_mut().zip(pixels.chunks_exact(self.bytes_per_pixel())){
+            *pixel = match self.color_depth{
+                32 => Rgba([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                16 => Rgba([bytes[0], bytes[0], bytes[0], bytes[1]]),
+                // Transparent index is opaque color in background layer
+                _ if bytes[0] == self.transparent_index && !background => Rgba([0, 0, 0, 0]),
+                _ => self.palette.get(bytes[0] as usize).copied().unwrap_or(Rgba([0, 0, 0, 0]))
+            };
+        }
+        Ok(image)
+    }
+}
+
+fn parse(bytes: &[u8]) -> Result<AsepriteFile, &'static str>{
+    let mut reader = Reader::new(bytes);
+    let header = reader.take(128)?;
+    let mut header = Reader::new(header);
+    header.u32()?;
+    if header.u16()? != 0xA5E0{
+        return Err("File isn't Aseprite sprite");
+    }
+    let frame_count = header.u16()? as usize;
+    let size = (header.u16()? as u32, header.u16()? as u32);
+    let color_depth = header.u16()?;
+    if color_depth != 32 && color_depth != 16 && color_depth != 8{
+        return Err("Aseprite sprite has unknown color depth");
+    }
+    let flags = header.u32()?;
+    // Deprecated speed is used by frames without duration
+    let speed = header.u16()?;
+    header.take(8)?;
+    let transparent_index = header.u8()?;
+
+    let mut format = PixelFormat { color_depth, transparent_index, palette: vec![] };
+    let mut file = AsepriteFile { size, layer_opacity: flags & 1 != 0, layers: vec![], frames: vec![], tags: vec![], slices: vec![] };
+    // Visibility of groups by their child level
+    let mut groups: Vec<bool> = vec![];
+
+    for _ in 0..frame_count{
+        let frame_start = reader.position;
+        let frame_size = reader.u32()? as usize;
+        if reader.u16()? != 0xF1FA{
+            return Err("Aseprite frame has invalid magic number");
+        }
+        let old_chunk_count = reader.u16()? as u32;
+        let mut duration = reader.u16()?;
+        if duration == 0{
+            duration = speed;
+        }
+        reader.take(2)?;
+        let chunk_count = match reader.u32()?{
+            0 => old_chunk_count,
+            count => count
+        };
+        let mut frame = Frame { duration: duration as f32 / 1000.0, cels: vec![] };
+
+        for _ in 0..chunk_count{
+            let chunk_size = reader.u32()? as usize;
+            let chunk_type = reader.u16()?;
+            let mut chunk = Reader::new(reader.take(chunk_size.checked_sub(6).ok_or("Aseprite chunk has invalid size")?)?);
+            match chunk_type{
+                // Old palette
+                0x0004 if format.palette.is_empty() => {
+                    let packets = chunk.u16()?;
+                    let mut index = 0;
+                    for _ in 0..packets{
+                        index += chunk.u8()? as usize;
+                        let count = match chunk.u8()? { 0 => 256, count => count as usize };
+                        for _ in 0..count{
+                            let color = chunk.take(3)?;
+                            set_color(&mut format.palette, index, Rgba([color[0], color[1], color[2], 255]))?;
+                            index += 1;
+                        }
+                    }
+                }
+                // Layer
+                0x2004 => {
+                    let flags = chunk.u16()?;
+                    let kind = chunk.u16()?;
+                    let level = chunk.u16()? as usize;
+                    chunk.take(6)?;
+                    let opacity = chunk.u8()?;
+                    let visible = flags & LAYER_VISIBLE != 0 && flags & LAYER_REFERENCE == 0
+                        && groups.iter().take(level).all(|visible| *visible);
+                    if kind == 1{
+                        groups.truncate(level);
+                        groups.push(visible);
+                    }
+                    // Groups and tilemaps don't have pixels of their own
+                    file.layers.push(Layer { flags, opacity, visible: visible && kind == 0 });
+                }
+                // Cel
+                0x2005 => {
+                    let layer = chunk.u16()? as usize;
+                    let position = (chunk.i16()? as i32, chunk.i16()? as i32);
+                    let opacity = chunk.u8()?;
+                    let cel_type = chunk.u16()?;
+                    let z_index = chunk.i16()?;
+                    chunk.take(5)?;
+                    let background = file.layers.get(layer).map(|layer| layer.flags & LAYER_BACKGROUND != 0).unwrap_or(false);
+                    let image = match cel_type{
+                        0 => {
+                            let (width, height) = (chunk.u16()? as u32, chunk.u16()? as u32);
+                            format.to_image(width, height, chunk.rest(), background)?
+                        }
+                        1 => {
+                            let linked = chunk.u16()? as usize;
+                            let cel = file.frames.get(linked)
+                                .and_then(|frame| frame.cels.iter().find(|cel| cel.layer == layer))
+                                .ok_or("Aseprite cel is linked to missing cel")?;
+                            // Linked cels share image, position and opacity
+                            frame.cels.push(Cel { z_index, ..cel.clone() });
+                            continue;
+                        }
+                        2 => {
+                            let (width, height) = (chunk.u16()? as u32, chunk.u16()? as u32);
+                            let mut pixels = vec![];
+                            ZlibDecoder::new(chunk.rest()).read_to_end(&mut pixels).map_err(|_| "Aseprite cel can't be decompressed")?;
+                            format.to_image(width, height, &pixels, background)?
+                        }
+                        _ => continue
+                    };
+                    frame.cels.push(Cel { layer, position, opacity, z_index, image });
+                }
+                // Tags
+                0x2018 => {
+                    let count = chunk.u16()?;
+                    chunk.take(8)?;
+                    for _ in 0..count{
+                        let from = chunk.u16()? as usize;
+                        let to = chunk.u16()? as usize;
+                        let direction = chunk.u8()?;
+                        let repeat = chunk.u16()?;
+                        chunk.take(10)?;
+                        let name = chunk.string()?;
+                        file.tags.push(Tag { name, from, to, direction, repeat });
+                    }
+                }
+                // Palette
+                0x2019 => {
+                    chunk.u32()?;
+                    let first = chunk.u32()? as usize;
+                    let last = chunk.u32()? as usize;
+                    if last < first || last >= PALETTE_SIZE{
+                        return Err("Aseprite palette has invalid range of colors");
+                    }
+                    chunk.take(8)?;
+                    for index in first..=last{
+                        let has_name = chunk.u16()? & 1 != 0;
+                        let color = chunk.take(4)?;
+                        set_color(&mut format.palette, index, Rgba([color[0], color[1], color[2], color[3]]))?;
+                        if has_name{
+                            chunk.string()?;
+                        }
+                    }
+                }
+                // Slice
+                0x2022 => {
+                    let key_count = chunk.u32()?;
+                    let flags = chunk.u32()?;
+                    chunk.u32()?;
+                    let name = chunk.string()?;
+                    for _ in 0..key_count{
+                        let frame = chunk.u32()? as usize;
+                        let bounds = (chunk.i32()?, chunk.i32()?, chunk.u32()?, chunk.u32()?);
+                        let center = if flags & 1 != 0 { Some((chunk.i32()?, chunk.i32()?, chunk.u32()?, chunk.u32()?)) } else { None };
+                        let pivot = if flags & 2 != 0 { Some((chunk.i32()?, chunk.i32()?)) } else { None };
+                        file.slices.push(AsepriteSlice { name: name.clone(), frame, bounds, center, pivot });
+                    }
+                }
+                _ => {}
+            }
+        }
+        file.frames.push(frame);
+        // Frame size is trusted over chunks, unknown data at end of frame is skipped
+        reader.position = frame_start + frame_size.max(reader.position - frame_start);
+    }
+    Ok(file)
+}
+
+// Indexed pixels are one byte, so palette can't have more colors than that
+fn set_color(palette: &mut Vec<Rgba<u8>>, index: usize, color: Rgba<u8>) -> Result<(), &'static str>{
+    if index >= PALETTE_SIZE{
+        return Err("Aseprite palette has more than 256 colors");
+    }
+    if palette.len() <= index{
+        palette.resize(index + 1, Rgba([0, 0, 0, 0]));
+    }
+    palette[index] = color;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    // Indexed sprite with one frame which has only given chunk
+    fn sprite(chunk_type: u16, chunk: &[u8]) -> Vec<u8>{
+        let mut header = vec![0; 128];
+        header[4..6].copy_from_slice(&0xA5E0u16.to_le_bytes());
+        header[6..8].copy_from_slice(&1u16.to_le_bytes());
+        header[8..10].copy_from_slice(&1u16.to_le_bytes());
+        header[10..12].copy_from_slice(&1u16.to_le_bytes());
+        header[12..14].copy_from_slice(&8u16.to_le_bytes());
+        let mut frame = vec![];
+        frame.extend_from_slice(&(16 + 6 + chunk.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&0xF1FAu16.to_le_bytes());
+        frame.extend_from_slice(&1u16.to_le_bytes());
+        frame.extend_from_slice(&[0; 4]);
+        frame.extend_from_slice(&1u32.to_le_bytes());
+        frame.extend_from_slice(&(6 + chunk.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&chunk_type.to_le_bytes());
+        frame.extend_from_slice(chunk);
+        [header, frame].concat()
+    }
+
+    fn palette_chunk(first: u32, last: u32, colors: usize) -> Vec<u8>{
+        let mut chunk = vec![];
+        for value in [last.wrapping_add(1), first, last]{
+            chunk.extend_from_slice(&value.to_le_bytes());
+        }
+        chunk.extend_from_slice(&[0; 8]);
+        for i in 0..colors{
+            chunk.extend_from_slice(&[0, 0, i as u8, 0, 0, 255]);
+        }
+        chunk
+    }
+
+    #[test]
+    fn palette_is_read(){
+        let file = parse(&sprite(0x2019, &palette_chunk(254, 255, 2))).unwrap();
+        assert_eq!(file.frames.len(), 1);
+        let file = parse(&sprite(0x0004, &[1, 0, 0, 1, 1, 2, 3])).unwrap();
+        assert_eq!(file.frames.len(), 1);
+    }
+
+    #[test]
+    fn palette_with_invalid_range_is_rejected(){
+        assert!(parse(&sprite(0x2019, &palette_chunk(5, 3, 0))).is_err());
+        assert!(parse(&sprite(0x2019, &palette_chunk(0, 256, 257))).is_err());
+        assert!(parse(&sprite(0x2019, &palette_chunk(0, u32::MAX, 1))).is_err());
+        // Old palette which skips past the last index
+        assert!(parse(&sprite(0x0004, &[1, 0, 255, 2, 0, 0, 0, 0, 0, 0])).is_err());
+    }
+
+    #[test]
+    fn tag_without_frames_gives_empty_clip(){
+        let tag = Tag{name: "walk".to_string(), from: 0, to: 3, direction: 2, repeat: 0};
+        let clip = tag.clip(&[]);
+        assert!(clip.frames.is_empty());
+        assert!(clip.durations.is_empty());
+        let clip = tag.clip(&[0.1, 0.2]);
+        assert_eq!(clip.frames, vec![0, 1]);
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use image::{RgbaImage, GenericImage, GenericImageView};
+use serde_json::Value;
+
+use crate::StrayTexture;
+
+/// ## Overview
+/// Sub-rectangle of texture atlas. Packers can trim transparent borders of frames,
+/// ``source_size`` is size of frame before trimming and ``offset`` is position of trimmed
+/// rectangle in it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasFrame{
+    // Top left corner in texture in pixels
+    pub position: (u32,u32),
+    // Size of trimmed frame, it's not swapped when frame is rotated
+    pub size: (u32,u32),
+    // Frame is stored rotated by 90 degrees clockwise in texture
+    pub rotated: bool,
+    pub source_size: (u32,u32),
+    pub offset: (u32,u32),
+    // (0,0) is top left corner and (1,1) is bottom right corner of untrimmed frame
+    pub pivot: Option<(f32,f32)>,
+}
+
+impl AtlasFrame{
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self{
+        Self { position: (x, y), size: (width, height), rotated: false, source_size: (width, height), offset: (0, 0), pivot: None }
+    }
+
+    /// ## Overview
+    /// Texture coordinates of point of frame, ``(u, v)`` is in ``[0, 1]``
+    /// where (0,0) is top left corner of trimmed frame
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use stray_texture::*;
+    ///
+    /// let frame = AtlasFrame::new(32, 0, 32, 16);
+    /// assert_eq!(frame.uv(0.0, 0.0, (64, 64)), (0.5, 0.0));
+    /// assert_eq!(frame.uv(1.0, 1.0, (64, 64)), (1.0, 0.25));
+    ///
+    /// // Rotated frame is 16 pixels wide and 32 pixels tall in texture
+    /// let mut rotated = AtlasFrame::new(0, 0, 32, 16);
+    /// rotated.rotated = true;
+    /// assert_eq!(rotated.uv(0.0, 0.0, (64, 64)), (0.25, 0.0));
+    /// assert_eq!(rotated.uv(1.0, 0.0, (64, 64)), (0.25, 0.5));
+    /// ```
+    pub fn uv(&self, u: f32, v: f32, texture_size: (u32,u32)) -> (f32,f32){
+        let (width, height) = (texture_size.0.max(1) as f32, texture_size.1.max(1) as f32);
+        let (x, y) = (self.position.0 as f32, self.position.1 as f32);
+        let (w, h) = (self.size.0 as f32, self.size.1 as f32);
+        if self.rotated{
+            // Region in texture is h wide and w tall, frame is turned clockwise
+            ((x + (1.0 - v) * h) / width, (y + u * w) / height)
+        }
+        else{
+            ((x + u * w) / width, (y + v * h) / height)
+        }
+    }
+}
+
+/// ## Overview
+/// Texture with frames, frames are selected by index or by name.
+/// Atlas is created from grid of equally sized frames or from JSON of texture packer
+///
+/// ## Example
+///
+/// ```ignore
+/// let sheet = Sprite::new(include_bytes!("player.png"), 0).texture;
+/// let atlas = TextureAtlas::from_grid(sheet, (32, 32), (0, 0), (0, 0));
+/// Stray::new()
+///     .push((Transform2D::ZERO, AtlasSprite::new(atlas, 3, 0)))
+///     .build()
+///     .run();
+/// ```
+#[derive(Clone)]
+pub struct TextureAtlas{
+    pub texture: StrayTexture,
+    pub frames: Vec<AtlasFrame>,
+    names: HashMap<String, usize>,
+}
+
+impl TextureAtlas{
+    /// Atlas without frames
+    pub fn new(texture: StrayTexture) -> Self{
+        Self { texture, frames: vec![], names: HashMap::new() }
+    }
+
+    /// Frames of size ``tile_size`` ordered row by row, ``padding`` is space between frames
+    /// and ``offset`` is space before first frame
+    pub fn from_grid(texture: StrayTexture, tile_size: (u32,u32), padding: (u32,u32), offset: (u32,u32)) -> Self{
+        let mut atlas = Self::new(texture);
+        let (width, height) = atlas.texture.dimensions;
+        let count = |size: u32, tile: u32, padding: u32, offset: u32| {
+            if tile == 0 || size < offset + tile { 0 } else { (size - offset - tile) / (tile + padding) + 1 }
+        };
+        let columns = count(width, tile_size.0, padding.0, offset.0);
+        let rows = count(height, tile_size.1, padding.1, offset.1);
+        for row in 0..rows{
+            for column in 0..columns{
+                let x = offset.0 + column * (tile_size.0 + padding.0);
+                let y = offset.1 + row * (tile_size.1 + padding.1);
+                atlas.frames.push(AtlasFrame::new(x, y, tile_size.0, tile_size.1));
+            }
+        }
+        atlas
+    }
+
+    /// ## Overview
+    /// Reads frames from JSON of TexturePacker in hash or array format,
+    /// ``texture`` is image which JSON describes. Frames in hash format are sorted by name
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use image::RgbaImage;
+    /// use stray_texture::*;
+    ///
+    /// let json = r#"{"frames": {
+    ///     "walk_1.png": {"frame": {"x": 0, "y": 0, "w": 16, "h": 32}, "rotated": false, "trimmed": true,
+    ///         "spriteSourceSize": {"x": 8, "y": 0, "w": 16, "h": 32}, "sourceSize": {"w": 32, "h": 32},
+    ///         "pivot": {"x": 0.5, "y": 1.0}}
+    /// }}"#;
+    /// let atlas = TextureAtlas::from_json(StrayTexture::with(RgbaImage::new(64, 64), (64, 64)), json).unwrap();
+    /// let frame = atlas.frame(atlas.index_of("walk_1.png").unwrap()).unwrap();
+    /// assert_eq!(frame.offset, (8, 0));
+    /// assert_eq!(frame.pivot, Some((0.5, 1.0)));
+    /// ```
+    pub fn from_json(texture: StrayTexture, json: &str) -> Result<Self, &'static str>{
+        let root: Value = serde_json::from_str(json).map_err(|_| "Atlas JSON can't be parsed")?;
+        let mut atlas = Self::new(texture);
+        match &root["frames"]{
+            Value::Object(frames) => {
+                for (name, frame) in frames.iter(){
+                    atlas.add_frame(Some(name), parse_frame(frame)?);
+                }
+            }
+            Value::Array(frames) => {
+                for frame in frames.iter(){
+                    atlas.add_frame(frame["filename"].as_str(), parse_frame(frame)?);
+                }
+            }
+            _ => return Err("Atlas JSON doesn't have frames")
+        }
+        Ok(atlas)
+    }
+
+    /// ## Overview
+    /// Packs images into one texture in shelves, transparent borders of images are trimmed
+    /// and ``padding`` is space around each frame. Index of frame is index of its image
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use image::{RgbaImage, Rgba};
+    /// use stray_texture::*;
+    ///
+    /// let mut image = RgbaImage::new(16, 16);
+    /// image.put_pixel(4, 6, Rgba([255, 0, 0, 255]));
+    /// let atlas = TextureAtlas::pack(&[image, RgbaImage::new(8, 8)], 1);
+    /// let frame = atlas.frame(0).unwrap();
+    /// assert_eq!((frame.size, frame.offset, frame.source_size), ((1, 1), (4, 6), (16, 16)));
+    /// ```
+    pub fn pack(images: &[RgbaImage], padding: u32) -> Self{
+        let bounds: Vec<(u32,u32,u32,u32)> = images.iter().map(opaque_bounds).collect();
+        // Tall images are placed first, so shelves waste less space
+        let mut order: Vec<usize> = (0..images.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(bounds[i].3));
+        let area: u64 = bounds.iter().map(|b| (b.2 + padding) as u64 * (b.3 + padding) as u64).sum();
+        let widest = bounds.iter().map(|b| b.2).max().unwrap_or(1);
+        let width = ((area as f64).sqrt().ceil() as u32).max(widest + padding * 2).next_power_of_two();
+
+        let mut positions = vec![(0, 0); images.len()];
+        let (mut x, mut y, mut shelf_height) = (padding, padding, 0);
+        for &i in order.iter(){
+            let (_, _, w, h) = bounds[i];
+            if x + w + padding > width{
+                x = padding;
+                y += shelf_height + padding;
+                shelf_height = 0;
+            }
+            positions[i] = (x, y);
+            x += w + padding;
+            shelf_height = shelf_height.max(h);
+        }
+        let height = y + shelf_height + padding;
+
+        let mut texture = RgbaImage::new(width, height);
+        let mut frames = Vec::with_capacity(images.len());
+        for (i, image) in images.iter().enumerate(){
+            let (left, top, w, h) = bounds[i];
+            let (x, y) = positions[i];
+            // Frames are trimmed to at least one pixel, so copy is always in bounds
+            let _ = texture.copy_from(&*image.view(left, top, w, h), x, y);
+            frames.push(AtlasFrame {
+                position: (x, y),
+                size: (w, h),
+                rotated: false,
+                source_size: image.dimensions(),
+                offset: (left, top),
+                pivot: None,
+            });
+        }
+        let mut atlas = Self::new(StrayTexture::with(texture, (width, height)));
+        atlas.frames = frames;
+        atlas
+    }
+
+    /// Adds frame and returns its index
+    pub fn add_frame(&mut self, name: Option<&str>, frame: AtlasFrame) -> usize{
+        let index = self.frames.len();
+        self.frames.push(frame);
+        if let Some(name) = name{
+            self.names.insert(name.to_string(), index);
+        }
+        index
+    }
+
+    pub fn frame(&self, index: usize) -> Option<&AtlasFrame>{
+        self.frames.get(index)
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize>{
+        self.names.get(name).copied()
+    }
+
+    /// Names of frames and their indices
+    pub fn names(&self) -> impl Iterator<Item = (&str, usize)>{
+        self.names.iter().map(|(name, index)| (name.as_str(), *index))
+    }
+
+    /// Indices of frames whose names start with prefix, sorted by name,
+    /// e.g. ``"run_"`` finds ``"run_01.png"``, ``"run_02.png"``
+    pub fn indices_with_prefix(&self, prefix: &str) -> Vec<usize>{
+        let mut frames: Vec<(&String, &usize)> = self.names.iter().filter(|(name, _)| name.starts_with(prefix)).collect();
+        frames.sort();
+        frames.into_iter().map(|(_, index)| *index).collect()
+    }
+
+    pub fn len(&self) -> usize{
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.frames.is_empty()
+    }
+}
+
+/// Rectangle of image which contains all pixels which aren't transparent,
+/// transparent image is trimmed to its top left pixel
+fn opaque_bounds(image: &RgbaImage) -> (u32,u32,u32,u32){
+    let (mut left, mut top, mut right, mut bottom) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, pixel) in image.enumerate_pixels(){
+        if pixel.0[3] != 0{
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x + 1);
+            bottom = bottom.max(y + 1);
+        }
+    }
+    if left == u32::MAX{
+        return (0, 0, image.width().min(1), image.height().min(1));
+    }
+    (left, top, right - left, bottom - top)
+}
+
+fn rect(value: &Value) -> Result<(u32,u32,u32,u32), &'static str>{
+    let field = |name: &str| value[name].as_u64().map(|number| number as u32).ok_or("Atlas JSON has invalid rectangle");
+    Ok((field("x").unwrap_or(0), field("y").unwrap_or(0), field("w")?, field("h")?))
+}
+
+fn parse_frame(value: &Value) -> Result<AtlasFrame, &'static str>{
+    let (x, y, width, height) = rect(&value["frame"])?;
+    let mut frame = AtlasFrame::new(x, y, width, height);
+    frame.rotated = value["rotated"].as_bool().unwrap_or(false);
+    if value["spriteSourceSize"].is_object() && value["sourceSize"].is_object(){
+        let (offset_x, offset_y, _, _) = rect(&value["spriteSourceSize"])?;
+        let (_, _, source_width, source_height) = rect(&value["sourceSize"])?;
+        frame.offset = (offset_x, offset_y);
+        frame.source_size = (source_width, source_height);
+    }
+    if let (Some(pivot_x), Some(pivot_y)) = (value["pivot"]["x"].as_f64(), value["pivot"]["y"].as_f64()){
+        frame.pivot = Some((pivot_x as f32, pivot_y as f32));
+    }
+    Ok(frame)
+}
@@ -1,2 +1,12 @@
 mod texture;
+mod target;
+mod atlas;
+mod animation;
+mod aseprite;
+mod animated;
 pub use texture::*;
+pub use target::*;
+pub use atlas::*;
+pub use animation::*;
+pub use aseprite::*;
+pub use animated::*;
\ No newline at end of file
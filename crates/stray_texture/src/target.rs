@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use wgpu::*;
+
+static NEXT_TARGET_ID: AtomicU32 = AtomicU32::new(0);
+static NEXT_ALLOCATION: AtomicU32 = AtomicU32::new(0);
+
+/// ## Overview
+/// Handle of offscreen texture which camera can render into.
+/// Copy of handle can be used as texture of ``Sprite`` or ``ScreenDraw``,
+/// so it's possible to make minimaps, mirrors, portals etc.
+///
+/// ## Example
+///
+/// ```ignore
+/// let target = RenderTarget::new(200, 200);
+/// Stray::new()
+///     .push((Transform2D::ZERO, Camera2D::with_target(target)))
+///     .push((Transform2D::new(200.0, 200.0, 0.0, 1.0), Sprite::from_target(&target, 0)))
+///     .build()
+///     .run();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderTarget{
+    id: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RenderTarget{
+    pub fn new(width: u32, height: u32) -> Self{
+        Self{id: NEXT_TARGET_ID.fetch_add(1, Ordering::Relaxed), width, height}
+    }
+
+    pub fn id(&self) -> u32{
+        self.id
+    }
+
+    /// Changes size of target, gpu texture is recreated in next frame
+    pub fn resize(&mut self, width: u32, height: u32){
+        self.width = width;
+        self.height = height;
+    }
+}
+
+/// Gpu side of ``RenderTarget``
+pub struct GpuRenderTarget{
+    pub texture: Texture,
+    pub view: TextureView,
+    pub sampler: Sampler,
+    pub size: (u32,u32),
+    // Changes every time gpu texture is created, so cached bind groups know when they're stale
+    pub allocation: u32,
+}
+
+impl GpuRenderTarget{
+    pub fn new(device: &Device, format: TextureFormat, size: (u32,u32)) -> Self{
+        let texture = device.create_texture(
+            &TextureDescriptor {
+                size: Extent3d {
+                    width: size.0.max(1),
+                    height: size.1.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+                label: Some("render_target_texture"),
+            }
+        );
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            address_mode_w: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            ..Default::default()
+        });
+        Self{texture, view, sampler, size, allocation: NEXT_ALLOCATION.fetch_add(1, Ordering::Relaxed)}
+    }
+
+    pub fn bind_group(&self, device: &Device, layout: &BindGroupLayout) -> BindGroup{
+        device.create_bind_group(
+            &BindGroupDescriptor {
+                layout,
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&self.view),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&self.sampler),
+                    }
+                ],
+                label: Some("render_target_bind_group"),
+            }
+        )
+    }
+}
+
+/// All render targets which are allocated on gpu, stored by id of ``RenderTarget``
+#[derive(Default)]
+pub struct RenderTargets(pub HashMap<u32, GpuRenderTarget>);
+
+impl RenderTargets{
+    /// Creates gpu texture of target or recreates it when size of target has changed
+    pub fn prepare(&mut self, device: &Device, format: TextureFormat, target: &RenderTarget){
+        let size = (target.width, target.height);
+        match self.0.get(&target.id){
+            Some(gpu_target) if gpu_target.size == size => {}
+            _ => {
+                self.0.insert(target.id, GpuRenderTarget::new(device, format, size));
+            }
+        }
+    }
+
+    pub fn get(&self, id: u32) -> Option<&GpuRenderTarget>{
+        self.0.get(&id)
+    }
+
+    /// Frees gpu texture of target, it's created again if camera renders into target
+    pub fn remove(&mut self, id: u32) -> Option<GpuRenderTarget>{
+        self.0.remove(&id)
+    }
+
+    /// Frees gpu textures of all targets which aren't in ``used``
+    pub fn retain(&mut self, used: &[u32]){
+        self.0.retain(|id, _| used.contains(id));
+    }
+}
@@ -1,17 +1,127 @@
 use image::*;
 use wgpu::*;
 
+use crate::{RenderTarget, RenderTargets};
+
+/// How texture is filtered and what happens outside of ``[0,1]`` texture coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureSampling{
+    pub mag_filter: FilterMode,
+    pub min_filter: FilterMode,
+    pub address_mode: AddressMode,
+}
+
+impl TextureSampling{
+    /// Crisp pixels without blending, used for pixel art
+    pub const NEAREST: Self = Self{
+        mag_filter: FilterMode::Nearest,
+        min_filter: FilterMode::Nearest,
+        address_mode: AddressMode::ClampToEdge,
+    };
+
+    pub fn with_address_mode(mut self, address_mode: AddressMode) -> Self{
+        self.address_mode = address_mode;
+        self
+    }
+}
+
+impl Default for TextureSampling{
+    fn default() -> Self {
+        Self { 
+            mag_filter: FilterMode::Linear, 
+            min_filter: FilterMode::Nearest, 
+            address_mode: AddressMode::ClampToEdge 
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct StrayTexture{
     rgba: RgbaImage,
-    pub dimensions: (u32,u32)
+    target: Option<u32>,
+    // Data like normal maps isn't color, so it's not converted from sRGB
+    linear: bool,
+    pub dimensions: (u32,u32),
+    pub sampling: TextureSampling,
 }
 
 impl StrayTexture{
     pub fn with(rgba: RgbaImage, dimensions: (u32,u32)) -> Self{
-        Self{rgba,dimensions}
+        Self{rgba, target: None, linear: false, dimensions, sampling: TextureSampling::default()}
+    }
+
+    /// Creates texture which samples content of render target instead of image
+    pub fn from_target(target: &RenderTarget) -> Self{
+        Self{rgba: RgbaImage::new(0, 0), target: Some(target.id()), linear: false, dimensions: (target.width, target.height), sampling: TextureSampling::default()}
+    }
+
+    /// 1x1 white texture, it's tinted by vertex color when drawn
+    pub fn white() -> Self{
+        Self::from_pixel([255, 255, 255, 255])
+    }
+
+    /// 1x1 texture with given 8-bit channels
+    pub fn from_pixel(rgba: [u8;4]) -> Self{
+        Self::with(RgbaImage::from_pixel(1, 1, Rgba(rgba)), (1, 1))
+    }
+
+    /// Marks texture as non-color data, e.g. normal map, which is sampled without sRGB conversion
+    pub fn as_linear(mut self) -> Self{
+        self.linear = true;
+        self
+    }
+
+    pub fn with_sampling(mut self, sampling: TextureSampling) -> Self{
+        self.sampling = sampling;
+        self
+    }
+
+    /// Id of render target if texture is created from it
+    pub fn target(&self) -> Option<u32>{
+        self.target
+    }
+
+    /// Size of texture, for render targets it's size of gpu texture
+    pub fn size(&self, targets: &RenderTargets) -> (u32,u32){
+        match self.target.and_then(|id| targets.get(id)){
+            Some(gpu_target) => gpu_target.size,
+            None => self.dimensions
+        }
+    }
+
+    /// Creates bind group of texture, returns ``None`` if render target isn't allocated yet
+    pub fn bind_group(&self, device: &wgpu::Device, queue: &wgpu::Queue, layout: &wgpu::BindGroupLayout, targets: &RenderTargets) -> Option<wgpu::BindGroup>{
+        match self.target{
+            Some(id) => targets.get(id).map(|gpu_target| gpu_target.bind_group(device, layout)),
+            None => Some(self.write_texture(device, queue, layout))
+        }
     }
 
     pub fn write_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue, layout: &wgpu::BindGroupLayout) -> wgpu::BindGroup{
+        let (texture_view, sampler) = self.upload(device, queue);
+
+        let bind_group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&texture_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    }
+                ],
+                label: Some("bind_group"),
+            }
+        );
+
+        bind_group
+    }
+
+    /// Writes image into new gpu texture, used when texture is bound in custom layout
+    pub fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> (wgpu::TextureView, wgpu::Sampler){
         let texture_size = wgpu::Extent3d {
             width: self.dimensions.0,
             height: self.dimensions.1,
@@ -23,9 +133,9 @@ impl StrayTexture{
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                format: if self.linear { TextureFormat::Rgba8Unorm } else { TextureFormat::Rgba8UnormSrgb },
                 usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[TextureFormat::Rgba8Unorm],
+                view_formats: &[],
                 label: Some("diffuse_texture"),
             }
         );
@@ -47,34 +157,15 @@ impl StrayTexture{
 
         let texture_view = diffuse_texture.create_view(&wgpu::TextureViewDescriptor::default());
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Nearest,
+            address_mode_u: self.sampling.address_mode,
+            address_mode_v: self.sampling.address_mode,
+            address_mode_w: self.sampling.address_mode,
+            mag_filter: self.sampling.mag_filter,
+            min_filter: self.sampling.min_filter,
             mipmap_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
-
-
-        let bind_group = device.create_bind_group(
-            &wgpu::BindGroupDescriptor {
-                layout: &layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&texture_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&sampler),
-                    }
-                ],
-                label: Some("bind_group"),
-            }
-        );
-
-        bind_group
+        (texture_view, sampler)
     }
 }
 
@@ -0,0 +1,21 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    // Frames and their delays are read from GIF, animator plays them in loop
+    for i in 0..3{
+        let (sprite, mut animator) = AtlasSprite::animated(include_bytes!("fire.gif"), 0).unwrap();
+        animator.speed = 1.0 + i as f32 * 0.25;
+        let x = (i as f32 - 1.0) * 150.0;
+        commands.push((Transform2D::new(x, 0.0, 0.0, 4.0), sprite, animator));
+    }
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Animated GIF")
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
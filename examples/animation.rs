@@ -0,0 +1,55 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Space plays attack once, animator returns to idle when attack finishes
+#[system(for_each)]
+fn attack(animator: &mut SpriteAnimator, #[resource] input: &InputEvent){
+    if input.is_pressed(Key::Space){
+        animator.play("attack");
+    }
+    if animator.is_finished(){
+        animator.play("idle");
+    }
+}
+
+#[system]
+fn print_events(#[resource] events: &AnimationEvents){
+    for event in events.markers("hit"){
+        println!("{:?} hits", event.entity);
+    }
+    for event in events.finished(){
+        println!("{:?} finished {}", event.entity, event.clip);
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    let texture = Sprite::new(include_bytes!("sprite.png"), 0).texture;
+    let atlas = TextureAtlas::from_grid(texture, (100, 100), (0, 0), (0, 0));
+
+    let player = SpriteAnimator::new()
+        .with_clip(AnimationClip::from_fps("idle", vec![0, 1, 2, 3], 6.0))
+        .with_clip(
+            AnimationClip::new("attack", vec![4, 5, 6, 7], vec![0.1, 0.1, 0.3, 0.1])
+                .with_mode(AnimationMode::Once)
+                .with_marker(2, "hit")
+        )
+        .playing("idle");
+    commands.push((Transform2D::new(-150.0, 0.0, 0.0, 1.0), AtlasSprite::new(atlas.clone(), 0, 0), player));
+
+    let mut spinner = SpriteAnimator::new()
+        .with_clip(AnimationClip::from_fps("spin", (0..8).collect(), 12.0).with_mode(AnimationMode::PingPong))
+        .playing("spin");
+    spinner.speed = 0.5;
+    commands.push((Transform2D::new(150.0, 0.0, 0.0, 1.0), AtlasSprite::new(atlas, 0, 0), spinner));
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Animation")
+        .add_system(attack_system())
+        .add_system(print_events_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
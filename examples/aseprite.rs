@@ -0,0 +1,36 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Space plays "land" tag once, then slime bounces again
+#[system(for_each)]
+fn land(animator: &mut SpriteAnimator, #[resource] input: &InputEvent){
+    if input.is_pressed(Key::Space){
+        animator.play("land");
+    }
+    if animator.is_finished(){
+        animator.play("bounce");
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    // Layers are flattened and tags of file become clips
+    let slime = Aseprite::from_bytes(include_bytes!("slime.aseprite")).unwrap();
+    let animator = SpriteAnimator::new()
+        .with_clips(slime.clips.clone())
+        .playing("bounce");
+    commands.push((Transform2D::new(-100.0, 0.0, 0.0, 8.0), AtlasSprite::new(slime.atlas.clone(), 0, 0), animator));
+
+    // Slice of file is named frame of atlas
+    let feet = AtlasSprite::from_name(slime.atlas, "feet", 0).unwrap();
+    commands.push((Transform2D::new(100.0, 0.0, 0.0, 8.0), feet));
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Aseprite")
+        .add_system(land_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
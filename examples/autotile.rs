@@ -0,0 +1,67 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Blob tiles of grass are frames 0 to 46, Wang tiles of water are frames 48 to 63
+struct Terrains{
+    grass: AutotileSet,
+    water: AutotileSet,
+}
+
+// Arrows move brush, G paints grass, W paints water and E erases both
+#[system(for_each)]
+fn paint(tilemap: &mut Tilemap, #[resource] input: &InputEvent, #[resource] terrains: &Terrains, #[state] brush: &mut (u32, u32)){
+    let (width, height) = tilemap.size();
+    if input.is_pressed(Key::Left) { brush.0 = brush.0.saturating_sub(1); }
+    if input.is_pressed(Key::Right) { brush.0 = (brush.0 + 1).min(width - 1); }
+    if input.is_pressed(Key::Up) { brush.1 = brush.1.saturating_sub(1); }
+    if input.is_pressed(Key::Down) { brush.1 = (brush.1 + 1).min(height - 1); }
+    if input.is_pressed(Key::G){
+        tilemap.paint_autotile(1, brush.0, brush.1, &terrains.grass);
+    }
+    if input.is_pressed(Key::W){
+        tilemap.paint_autotile(0, brush.0, brush.1, &terrains.water);
+    }
+    if input.is_pressed(Key::E){
+        tilemap.erase_autotile(1, brush.0, brush.1, &terrains.grass);
+        tilemap.erase_autotile(0, brush.0, brush.1, &terrains.water);
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer, #[resource] terrains: &Terrains){
+    let texture = Sprite::new(include_bytes!("terrain.png"), 0).texture.with_sampling(TextureSampling::NEAREST);
+    let tileset = TextureAtlas::from_grid(texture, (16, 16), (0, 0), (0, 0));
+    let mut map = Tilemap::new(tileset, (30, 20), vec2(16.0, 16.0))
+        .with_layer("water", 0)
+        .with_layer("grass", 1);
+
+    // Tiles placed with set get their variants from apply_autotiles
+    for y in 0..20{
+        for x in 0..30{
+            if (x as i32 - 20).pow(2) + (y as i32 - 10).pow(2) < 30{
+                map.set(0, x, y, Some(Tile::new(48)));
+            }
+            if x < 12 && y > 3 && y < 16 && (x + y) % 9 != 0{
+                map.set(1, x, y, Some(Tile::new(0)));
+            }
+        }
+    }
+    map.apply_autotiles(0, &terrains.water);
+    map.apply_autotiles(1, &terrains.grass);
+
+    commands.push((Transform2D::new(-480.0, 320.0, 0.0, 2.0), map));
+}
+
+fn main(){
+    let terrains = Terrains {
+        grass: AutotileSet::new(AutotileScheme::Blob47, (0..47).collect()).unwrap(),
+        water: AutotileSet::new(AutotileScheme::Wang16, (48..64).collect()).unwrap(),
+    };
+    Stray::new()
+        .with_title("Autotiling")
+        .insert(terrains)
+        .add_system(paint_system((0, 0)))
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
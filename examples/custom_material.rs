@@ -0,0 +1,43 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Sprite which waves like a flag, see wave.wgsl
+struct Wave{
+    time: f32,
+    strength: f32,
+}
+
+impl Material2D for Wave{
+    fn fragment_shader() -> &'static str {
+        include_str!("wave.wgsl")
+    }
+
+    fn uniform(&self) -> Vec<u8> {
+        [self.time, self.strength, 0.0, 0.0].iter().flat_map(|x| x.to_ne_bytes()).collect()
+    }
+}
+
+#[system(for_each)]
+fn update_wave(material_sprite: &mut MaterialSprite<Wave>){
+    material_sprite.material.time += 1.0 / 60.0;
+}
+
+#[system]
+fn setup_sprite(commands: &mut CommandBuffer){
+    commands.push((
+        Transform2D::ZERO,
+        MaterialSprite::new(
+            Sprite::new(include_bytes!("sprite.png"), 0),
+            Wave{time: 0.0, strength: 0.02}
+        )
+    ));
+}
+
+fn main(){
+    Stray::new()
+        .add_material::<Wave>()
+        .add_system(update_wave_system())
+        .run_once(setup_sprite_system())
+        .build()
+        .run();
+}
@@ -0,0 +1,53 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+const LINES: [&str; 3] = [
+    "[color=#ffcc00]Merchant[/color]: Welcome, traveller! I sell [wave]shiny[/wave] things.",
+    "[color=#ffcc00]Merchant[/color]: This one costs [size=32]50[/size] [sprite=coin] and it's [shake]very[/shake] rare.",
+    "[color=#ffcc00]Merchant[/color]: [font=pixel]COME BACK SOON![/font]",
+];
+
+struct Dialogue(usize);
+
+// Space shows whole line when it's being typed, otherwise it starts next line
+#[system(for_each)]
+fn next_line(
+    dialogue: &mut Dialogue,
+    text: &mut RichText2D,
+    typewriter: &mut Typewriter,
+    #[resource] input: &InputEvent
+){
+    if !input.is_pressed(Key::Space){
+        return;
+    }
+    if !typewriter.is_finished(text){
+        typewriter.skip();
+        return;
+    }
+    dialogue.0 = (dialogue.0 + 1) % LINES.len();
+    text.set_markup(LINES[dialogue.0]).unwrap();
+    typewriter.restart();
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    let font = Font::new(include_bytes!("fonts/Cantarell-Regular.ttf")).unwrap();
+    let pixel_font = Font::from_bmfont(include_bytes!("fonts/pixel.fnt"), &[include_bytes!("fonts/pixel_0.png")]).unwrap();
+    let coin = Sprite::new(include_bytes!("sprite.png"), 0).texture;
+
+    let text = RichText2D::new(LINES[0], font, 24.0)
+        .unwrap()
+        .with_font("pixel", pixel_font)
+        .with_sprite("coin", coin)
+        .with_bounds(vec2(500.0, 120.0));
+    commands.push((Dialogue(0), Transform2D::new(0.0, -150.0, 0.0, 1.0), text, Typewriter::new(25.0)));
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Dialogue")
+        .add_system(next_line_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
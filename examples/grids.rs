@@ -0,0 +1,108 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+const FLOWER: usize = 5;
+
+// Hex of unit and target on hexagonal board, cell of unit on isometric map
+struct Board{
+    hexes: HexGrid,
+    unit: Hex,
+    target: Hex,
+    iso: IsoGrid,
+    cell: IVec2,
+}
+
+// Unit on isometric map
+struct Walker;
+
+// Arrows move target on hex board, WASD moves walker through edges of isometric cells
+#[system]
+fn move_cursors(#[resource] input: &InputEvent, #[resource] board: &mut Board){
+    let keys = [(Key::Right, 0), (Key::Up, 2), (Key::Left, 3), (Key::Down, 5)];
+    for (key, direction) in keys{
+        if input.is_pressed(key){
+            board.target = board.target.neighbour(direction);
+        }
+    }
+    let steps = [(Key::D, 1), (Key::S, 2), (Key::A, 3), (Key::W, 0)];
+    for (key, neighbour) in steps{
+        if input.is_pressed(key){
+            board.cell = board.iso.neighbours(board.cell)[neighbour];
+        }
+    }
+}
+
+// Hexes in range of unit are green, ring at distance 3 is yellow and line to target is red
+#[system(for_each)]
+fn draw_hexes(draw: &mut ScreenDraw, #[resource] board: &Board){
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    let line = board.unit.line_to(board.target);
+    let ring = board.unit.ring(3);
+    for hex in board.unit.spiral(5){
+        let color = if line.contains(&hex){
+            StrayColor::RED
+        }
+        else if ring.contains(&hex){
+            StrayColor::YELLOW
+        }
+        else{
+            StrayColor::hsv(120.0, 0.2 + 0.1 * hex.distance(board.unit) as f32, 0.7)
+        };
+        let first = vertices.len() as u16;
+        let center = board.hexes.hex_to_local(hex);
+        // Corners are pulled in a bit, so background shows between hexes
+        for corner in board.hexes.corners(hex){
+            let point = center + (corner - center) * 0.9;
+            let mut vertex = Vertex::new(point.x as i32, point.y as i32, 0);
+            vertex.material = StandardMaterial::new(color);
+            vertices.push(vertex);
+        }
+        for i in 1..5{
+            indices.extend_from_slice(&[first, first + i, first + i + 1]);
+        }
+    }
+    draw.set_vertices(vertices);
+    draw.set_indices(indices);
+}
+
+// Walker stands on bottom of its cell, so YSort draws it behind things in front of it
+#[system(for_each)]
+fn place_walker(transform: &mut Transform2D, _walker: &Walker, #[resource] board: &Board){
+    let center = board.iso.cell_to_local(board.cell);
+    transform.position = vec2(160.0, 200.0) + (center - vec2(0.0, board.iso.tile_size.y / 2.0)) * 2.0;
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer, #[resource] board: &Board){
+    let texture = Sprite::new(include_bytes!("tiles.png"), 0).texture.with_sampling(TextureSampling::NEAREST);
+    let tileset = TextureAtlas::from_grid(texture, (16, 16), (0, 0), (0, 0));
+    let mut map = Tilemap::new(tileset.clone(), (10, 10), board.iso.tile_size)
+        .with_orientation(TilemapOrientation::Isometric)
+        .with_frame_scale(Vec2::ONE)
+        .with_layer("ground", 0);
+    map.fill(0, Some(Tile::new(0)));
+    commands.push((Transform2D::new(160.0, 200.0, 0.0, 2.0), map));
+    commands.push((Transform2D::new(160.0, 200.0, 0.0, 2.0), Walker, AtlasSprite::new(tileset, FLOWER, 1).with_pivot(vec2(0.5, 1.0))));
+    commands.push((ScreenDraw::init(-300.0, 0.0, 0.0),));
+}
+
+fn main(){
+    let board = Board {
+        hexes: HexGrid::new(HexOrientation::Pointy, vec2(24.0, 24.0)),
+        unit: Hex::new(0, 0),
+        target: Hex::new(3, -1),
+        iso: IsoGrid::new(vec2(16.0, 8.0)),
+        cell: ivec2(4, 4),
+    };
+    Stray::new()
+        .with_title("Isometric and Hexagonal Grids")
+        .with_layer_sort(LayerSort::YSort)
+        .insert(board)
+        .add_system(move_cursors_system())
+        .add_system(draw_hexes_system())
+        .add_system(place_walker_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
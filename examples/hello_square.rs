@@ -5,7 +5,7 @@ use legion::system;
 #[system(for_each)]
 fn draw(draw: &mut ScreenDraw){ 
     let vertices = vec![
-        Vertex::new(-500, -500, 0), Vertex::new(500, -500, 0), Vertex::new(-500, 500, 0), Vertex::new(500, 500, 0)
+        Vertex::new(-250, -250, 0), Vertex::new(250, -250, 0), Vertex::new(-250, 250, 0), Vertex::new(250, 250, 0)
     ];
 
     let indices = vec![
@@ -13,12 +13,7 @@ fn draw(draw: &mut ScreenDraw){
         2, 1, 3
     ];
 
-    let color = StrayColor{
-        r: 255,
-        g: 255,
-        b: 100,
-        a: 0.5,
-    };
+    let color = StrayColor::srgb_u8(255, 255, 100).with_alpha(0.5);
     let material = StandardMaterial::new(color);
     draw.set_vertices(vertices);
     draw.set_indices(indices);
@@ -28,7 +23,7 @@ fn draw(draw: &mut ScreenDraw){
 fn main(){
     Stray::new()
         .with_title("Stray App")
-        .push((ScreenDraw::init(0,0,0),))
+        .push((ScreenDraw::init(0.0, 0.0, 0.0),))
         .add_system(draw_system())
         .build()
         .run();
@@ -5,14 +5,9 @@ use legion::*;
 #[system(for_each)]
 fn draw(draw: &mut ScreenDraw){
     let vertices = vec![
-        Vertex::new(-500, -500,0), Vertex::new(500, -500,0), Vertex::new(-500, 500,0), 
+        Vertex::new(-250, -250,0), Vertex::new(250, -250,0), Vertex::new(-250, 250,0), 
     ];
-    let color = StrayColor{
-        r: 255,
-        g: 255,
-        b: 100,
-        a: 1.0,
-    };
+    let color = StrayColor::srgb_u8(255, 255, 100);
     let material = StandardMaterial::new(color);
     draw.set_vertices(vertices);
     draw.set_material(material);
@@ -21,7 +16,7 @@ fn draw(draw: &mut ScreenDraw){
 fn main(){
     Stray::new()
         .with_title("Stray App")
-        .push((ScreenDraw::init(0, 0, 0),))
+        .push((ScreenDraw::init(0.0, 0.0, 0.0),))
         .add_system(draw_system())
         .build()
         .run();
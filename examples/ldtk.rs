@@ -0,0 +1,54 @@
+use stray::prelude::*;
+use legion::{*, world::SubWorld};
+
+fn files(path: &str) -> Option<Vec<u8>>{
+    match path{
+        "tiles.png" => Some(include_bytes!("tiles.png").to_vec()),
+        _ => None
+    }
+}
+
+// Arrows switch to neighbouring level and world is moved so current level is in the middle of window
+#[system(for_each)]
+fn switch_level(transform: &mut Transform2D, world: &mut LdtkWorld, #[resource] input: &InputEvent){
+    let direction = if input.is_pressed(Key::Right) { "e" } else if input.is_pressed(Key::Left) { "w" } else { "" };
+    let next = match world.current_level().and_then(|level| level.neighbour(direction)){
+        Some(next) => next.to_string(),
+        None => return
+    };
+    world.switch_to(&next);
+    if let Some(level) = world.project().level(&next){
+        let center = level.world_position + vec2(level.size.x, -level.size.y) / 2.0;
+        transform.position = -center * transform.scale;
+        println!("{} plays {:?}", level.identifier, level.fields.get_str("music"));
+    }
+}
+
+// Player falls until IntGrid of collisions has wall under it
+#[system(for_each)]
+#[read_component(LdtkIntGrid)]
+fn fall(transform: &mut Transform2D, entity: &LdtkEntity, world: &SubWorld, #[resource] time: &Time){
+    if entity.identifier != "Player"{
+        return;
+    }
+    let speed = entity.fields.get_float("speed").unwrap_or(60.0) as f32;
+    let step = vec2(0.0, -speed * time.delta);
+    let feet = transform.position + step;
+    let blocked = <&LdtkIntGrid>::query().iter(world)
+        .filter(|grid| grid.name == "Collisions")
+        .any(|grid| grid.collides(feet - vec2(6.0, 0.0), feet + vec2(6.0, 1.0)));
+    if !blocked{
+        transform.translate(step);
+    }
+}
+
+fn main(){
+    let project = LdtkProject::from_json(include_str!("world.ldtk"), files).unwrap();
+    Stray::new()
+        .with_title("LDtk Levels")
+        .push((Transform2D::new(-256.0, 160.0, 0.0, 2.0), LdtkWorld::new(project).with_level("Meadow")))
+        .add_system(switch_level_system())
+        .add_system(fall_system())
+        .build()
+        .run();
+}
@@ -0,0 +1,59 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+struct Player;
+
+#[system(for_each)]
+fn move_light(
+    _player: &Player,
+    transform: &mut Transform2D,
+    #[resource] input: &InputEvent,
+){
+    if input.is_pressed(Key::A){
+        transform.position.x -= 10.0;
+    }
+    if input.is_pressed(Key::D){
+        transform.position.x += 10.0;
+    }
+    if input.is_pressed(Key::W){
+        transform.position.y += 10.0;
+    }
+    if input.is_pressed(Key::S){
+        transform.position.y -= 10.0;
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    // Floor of sprites which is lit by lights
+    for x in -3..=3{
+        for y in -3..=3{
+            commands.push((
+                Transform2D::new(x as f32 * 100.0, y as f32 * 100.0, 0.0, 1.0),
+                Sprite::new(include_bytes!("sprite.png"), 0)
+            ));
+        }
+    }
+    // Walls which cast shadows
+    commands.push((Transform2D::new(-150.0, 50.0, 0.0, 1.0), LightOccluder2D::rectangle(40.0, 200.0)));
+    commands.push((Transform2D::new(150.0, -100.0, deg2rad(45.0), 1.0), LightOccluder2D::rectangle(150.0, 30.0)));
+
+    commands.push((
+        Player,
+        Transform2D::ZERO,
+        PointLight2D::new(StrayColor::ORANGE, 400.0).with_intensity(1.5).with_shadows(true)
+    ));
+    commands.push((
+        Transform2D::new(-250.0, 250.0, deg2rad(-45.0), 1.0),
+        SpotLight2D::new(StrayColor::CYAN, 500.0, deg2rad(15.0), deg2rad(25.0)).with_shadows(true)
+    ));
+}
+
+fn main(){
+    Stray::new()
+        .insert(AmbientLight::new(StrayColor::hex("#202040").unwrap(), 1.0))
+        .add_system(move_light_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
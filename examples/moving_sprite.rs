@@ -26,7 +26,7 @@ fn rotating_sprite(
 #[system]
 fn setup_sprite(commands: &mut CommandBuffer){
     commands.push((
-        Transform2D::new(-500, -250, 0, 1.0),
+        Transform2D::new(-500.0, -250.0, 0.0, 1.0),
         Sprite::new(include_bytes!("sprite.png"), 0)
     ));
 }
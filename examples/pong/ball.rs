@@ -15,9 +15,9 @@ pub fn init_ball(cmd: &mut CommandBuffer){
             velocity: vec2(2.0,2.0)
         },
         Transform2D::new(
-            0, 
-            0, 
-            0, 
+            0.0, 
+            0.0, 
+            0.0, 
             0.5
         ),
         Sprite::new(include_bytes!("textures/ball.png"), 0)
@@ -37,7 +37,7 @@ pub fn ball_logic(
     let immutable_world = world.clone();
     let mutable_world = world;
 
-    for (ball_transform, ball, sprite) in ball_query.iter_mut(mutable_world){
+    for (ball_transform, ball, _sprite) in ball_query.iter_mut(mutable_world){
         ball_transform.position += ball.velocity;
         if ball_transform.position.y >= ((window.inner_size().height as i32)/2) as f32 ||  // TODO: add method for getting window edges
            ball_transform.position.y <= -((window.inner_size().height as i32)/2) as f32
@@ -50,7 +50,7 @@ pub fn ball_logic(
         {
             panic!("you lose") // joke
         }
-        for (paddle_transform, paddle) in right_paddle.iter(&immutable_world){ // Iterating over right paddle components
+        for (paddle_transform, _paddle) in right_paddle.iter(&immutable_world){ // Iterating over right paddle components
             if crate::check_aabb(
                 crate::ball::BALL_AABB,  // Check collision of ball aabb and paddle aabb
                 ball_transform, 
@@ -61,7 +61,7 @@ pub fn ball_logic(
             }
         }
 
-        for (paddle_transform, paddle) in left_paddle.iter(&immutable_world){ // Iterating over left paddle components
+        for (paddle_transform, _paddle) in left_paddle.iter(&immutable_world){ // Iterating over left paddle components
             if crate::check_aabb(
                 crate::ball::BALL_AABB, // Check collision of ball aabb and paddle aabb
                 ball_transform, 
@@ -1,8 +1,4 @@
 use stray::prelude::*;
-use legion::{
-    *,
-    world::SubWorld
-};
 
 mod ball;
 mod paddles;
@@ -10,9 +10,9 @@ pub fn init_paddles(cmd: &mut CommandBuffer, #[resource] window: &Window){
     cmd.push((
         LeftPaddle,
         Transform2D::new(
-            -((window.inner_size().width as i32)/2), // TODO: Create method for getting window edge
-            0,
-            0,
+            -((window.inner_size().width as i32)/2) as f32, // TODO: Create method for getting window edge
+            0.0,
+            0.0,
             0.5
         ),
         Sprite::new(include_bytes!("textures/paddle.png"), 0)
@@ -21,9 +21,9 @@ pub fn init_paddles(cmd: &mut CommandBuffer, #[resource] window: &Window){
     cmd.push((
         RightPaddle,
         Transform2D::new(
-            (window.inner_size().width as i32)/2, // TODO: Create method for getting window edge
-            0,
-            0,
+            ((window.inner_size().width as i32)/2) as f32, // TODO: Create method for getting window edge
+            0.0,
+            0.0,
             0.5
         ),
         Sprite::new(include_bytes!("textures/paddle.png"), 0)
@@ -32,7 +32,7 @@ pub fn init_paddles(cmd: &mut CommandBuffer, #[resource] window: &Window){
 
 #[system(for_each)]
 pub fn left_paddle_movement(
-    left_paddle: &LeftPaddle,
+    _left_paddle: &LeftPaddle,
     texture: &Sprite,
     transform: &mut Transform2D,
 
@@ -57,7 +57,7 @@ pub fn left_paddle_movement(
 
 #[system(for_each)]
 pub fn right_paddle_movement(
-    left_paddle: &RightPaddle,
+    _right_paddle: &RightPaddle,
     texture: &Sprite,
     transform: &mut Transform2D,
 
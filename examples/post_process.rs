@@ -0,0 +1,45 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Number keys switch between effects, 0 disables them
+#[system]
+fn switch_effects(
+    #[resource] input: &InputEvent,
+    #[resource] post_process: &mut PostProcess,
+){
+    let effect = if input.is_pressed(Key::Key1){
+        PostEffect::Bloom{ threshold: 0.6, intensity: 1.5, radius: 16.0 }
+    } else if input.is_pressed(Key::Key2){
+        PostEffect::Vignette{ color: StrayColor::BLACK, intensity: 1.0, radius: 0.4, smoothness: 0.6 }
+    } else if input.is_pressed(Key::Key3){
+        PostEffect::ChromaticAberration{ offset: 6.0 }
+    } else if input.is_pressed(Key::Key4){
+        PostEffect::Crt{ curvature: 0.08, scanline_intensity: 0.4, scanline_size: 2.0 }
+    } else if input.is_pressed(Key::Key5){
+        PostEffect::Pixelate{ pixel_size: 8.0 }
+    } else if input.is_pressed(Key::Key0){
+        post_process.0.clear();
+        return;
+    } else {
+        return;
+    };
+    post_process.0 = vec![effect];
+}
+
+#[system]
+fn setup_sprite(commands: &mut CommandBuffer){
+    commands.push((
+        Transform2D::ZERO,
+        Sprite::new(include_bytes!("sprite.png"), 0)
+    ));
+}
+
+fn main(){
+    Stray::new()
+        .with_background(Background::Gradient{ top: StrayColor::hex("#203a43").unwrap(), bottom: StrayColor::hex("#0f2027").unwrap() })
+        .with_post_effect(PostEffect::Vignette{ color: StrayColor::BLACK, intensity: 1.0, radius: 0.4, smoothness: 0.6 })
+        .add_system(switch_effects_system())
+        .run_once(setup_sprite_system())
+        .build()
+        .run();
+}
@@ -0,0 +1,55 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+struct Player;
+
+#[system(for_each)]
+fn player_movement(
+    _player: &Player,
+    transform: &mut Transform2D, 
+    #[resource] input: &InputEvent,
+){
+    if input.is_pressed(Key::A){
+        transform.position.x -= 20.0;
+    }
+    if input.is_pressed(Key::D){
+        transform.position.x += 20.0;
+    }
+    if input.is_pressed(Key::W){
+        transform.position.y += 20.0;
+    }
+    if input.is_pressed(Key::S){
+        transform.position.y -= 20.0;
+    }
+}
+
+#[system]
+fn setup(commands: &mut CommandBuffer){
+    let minimap = RenderTarget::new(300, 150);
+    commands.push((
+        Player,
+        Transform2D::new(0.0, 0.0, 0.0, 1.0),
+        Sprite::new(include_bytes!("sprite.png"), 0)
+    ));
+
+    // Camera which sees whole level in small scale
+    let mut camera = Camera2D::with_target(minimap);
+    camera.zoom = 0.25;
+    commands.push((Transform2D::ZERO, camera));
+
+    // Minimap in the corner of window
+    commands.push((
+        Transform2D::new(300.0, 150.0, 0.0, 0.5),
+        Sprite::from_target(&minimap, 0)
+    ));
+}
+
+fn main(){
+    Stray::new()
+        .with_size(1000, 500)
+        .with_title("Render Target")
+        .run_once(setup_system())
+        .add_system(player_movement_system())
+        .build()
+        .run();
+}
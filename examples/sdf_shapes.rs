@@ -0,0 +1,68 @@
+use stray::prelude::*;
+use legion::system;
+
+// Health bar whose fill shrinks and grows
+struct Health;
+
+// Camera zooms in and out to show that edges stay sharp
+#[system(for_each)]
+fn zoom(camera: &mut Camera2D, #[resource] time: &Time){
+    camera.zoom = 1.0 + 0.8 * (time.elapsed * 0.5).sin();
+}
+
+// Capsule is anchored at its left end, so it's moved by half of change of length
+#[system(for_each)]
+fn drain(shape: &mut SdfShape, transform: &mut Transform2D, _health: &Health, #[resource] time: &Time){
+    let length = 120.0 + 100.0 * time.elapsed.sin();
+    shape.kind = SdfShapeKind::Capsule { length, radius: 10.0 };
+    transform.position.x = -160.0 + length / 2.0;
+}
+
+fn main(){
+    let shadow = StrayColor::BLACK.with_alpha(0.6);
+    Stray::new()
+        .with_title("SDF Shapes")
+        .push((Transform2D::ZERO, Camera2D::new()))
+        // Panel with buttons
+        .push((
+            Transform2D::new(0.0, 40.0, 0.0, 1.0),
+            SdfShape::rounded_box(vec2(400.0, 260.0), 24.0)
+                .with_color(StrayColor::srgb_u8(40, 44, 52))
+                .with_outline(StrayColor::srgb_u8(90, 96, 110), 2.0)
+                .with_shadow(shadow, vec2(0.0, -8.0), 16.0)
+        ))
+        .push((
+            Transform2D::new(-90.0, 80.0, 0.0, 1.0),
+            SdfShape::rounded_box(vec2(140.0, 48.0), 10.0)
+                .with_color(StrayColor::srgb_u8(70, 130, 220))
+                .with_shadow(shadow, vec2(0.0, -3.0), 4.0)
+                .with_layer(1)
+        ))
+        .push((
+            Transform2D::new(90.0, 80.0, 0.0, 1.0),
+            SdfShape::rounded_box(vec2(140.0, 48.0), 24.0)
+                .with_color(StrayColor::TRANSPARENT)
+                .with_outline(StrayColor::srgb_u8(220, 90, 90), 3.0)
+                .with_layer(1)
+        ))
+        // Health bar
+        .push((
+            Transform2D::new(0.0, 0.0, 0.0, 1.0),
+            SdfShape::capsule(300.0, 12.0).with_color(StrayColor::srgb_u8(20, 20, 24)).with_layer(1)
+        ))
+        .push((Transform2D::new(0.0, 0.0, 0.0, 1.0), SdfShape::capsule(120.0, 10.0).with_color(StrayColor::GREEN).with_layer(2), Health))
+        // Debug markers
+        .push((
+            Transform2D::new(-100.0, -60.0, 0.0, 1.0),
+            SdfShape::circle(20.0).with_color(StrayColor::ORANGE).with_outline(StrayColor::WHITE, 2.0).with_layer(1)
+        ))
+        .push((Transform2D::new(0.0, -60.0, 0.0, 1.0), SdfShape::ring(18.0, 4.0).with_color(StrayColor::CYAN).with_layer(1)))
+        .push((
+            Transform2D::new(100.0, -60.0, 0.8, 1.0),
+            SdfShape::capsule(30.0, 8.0).with_color(StrayColor::YELLOW).with_shadow(shadow, vec2(2.0, -2.0), 3.0).with_layer(1)
+        ))
+        .add_system(zoom_system())
+        .add_system(drain_system())
+        .build()
+        .run();
+}
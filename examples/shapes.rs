@@ -0,0 +1,72 @@
+use stray::prelude::*;
+use legion::system;
+
+// Star whose points grow and shrink, it's tessellated again every frame
+struct Pulse;
+
+#[system(for_each)]
+fn pulse(shape: &mut Shape2D, _pulse: &Pulse, #[resource] time: &Time){
+    let inner = 30.0 + 15.0 * time.elapsed.sin();
+    let points = regular_polygon_points(10, 1.0).iter().enumerate()
+        .map(|(index, point)| *point * if index % 2 == 0 { 70.0 } else { inner })
+        .collect();
+    shape.kind = ShapeKind::Polygon { points };
+}
+
+// Only transform changes, so triangles of shape are kept
+struct Spin;
+
+#[system(for_each)]
+fn spin(transform: &mut Transform2D, _spin: &Spin, #[resource] time: &Time){
+    transform.rotate(0.5 * time.delta);
+}
+
+fn main(){
+    let wave = (0..=40).map(|step| vec2(step as f32 * 10.0 - 200.0, (step as f32 * 0.5).sin() * 30.0)).collect();
+    let heart = ShapePath::new()
+        .move_to(vec2(0.0, -40.0))
+        .cubic_to(vec2(-70.0, 10.0), vec2(-30.0, 60.0), vec2(0.0, 25.0))
+        .cubic_to(vec2(30.0, 60.0), vec2(70.0, 10.0), vec2(0.0, -40.0))
+        .close();
+    let arc = ShapePath::new()
+        .move_to(vec2(-60.0, 0.0))
+        .quadratic_to(vec2(0.0, 80.0), vec2(60.0, 0.0));
+
+    Stray::new()
+        .with_title("Shapes")
+        .push((Transform2D::new(-300.0, 150.0, 0.0, 1.0), Shape2D::circle(60.0).with_fill(StrayColor::ORANGE)))
+        .push((
+            Transform2D::new(-100.0, 150.0, 0.0, 1.0),
+            Shape2D::ellipse(vec2(80.0, 40.0)).without_fill().with_stroke(Stroke::new(StrayColor::WHITE, 3.0))
+        ))
+        .push((
+            Transform2D::new(100.0, 150.0, 0.0, 1.0),
+            Shape2D::rounded_rectangle(vec2(140.0, 90.0), 20.0)
+                .with_fill(StrayColor::BLUE)
+                .with_stroke(Stroke::new(StrayColor::WHITE, 6.0))
+        ))
+        .push((
+            Transform2D::new(300.0, 150.0, 0.0, 1.0),
+            Shape2D::regular_polygon(6, 60.0)
+                .with_fill(StrayColor::GREEN)
+                .with_stroke(Stroke::new(StrayColor::BLACK, 8.0).with_join(LineJoin::Bevel)),
+            Spin
+        ))
+        .push((Transform2D::new(-300.0, -50.0, 0.0, 1.0), Shape2D::polygon(vec![]).with_fill(StrayColor::YELLOW), Pulse))
+        .push((
+            Transform2D::new(0.0, -50.0, 0.0, 1.0),
+            Shape2D::path(heart).with_fill(StrayColor::RED).with_stroke(Stroke::new(StrayColor::WHITE, 2.0).with_join(LineJoin::Round))
+        ))
+        .push((
+            Transform2D::new(300.0, -50.0, 0.0, 1.0),
+            Shape2D::path(arc).without_fill().with_stroke(Stroke::new(StrayColor::CYAN, 10.0).with_cap(LineCap::Round))
+        ))
+        .push((
+            Transform2D::new(0.0, -220.0, 0.0, 1.0),
+            Shape2D::polyline(wave).with_stroke(Stroke::new(StrayColor::WHITE, 4.0).with_join(LineJoin::Round).with_cap(LineCap::Square))
+        ))
+        .add_system(pulse_system())
+        .add_system(spin_system())
+        .build()
+        .run();
+}
@@ -0,0 +1,39 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Switches frames of all atlas sprites with arrow keys
+#[system(for_each)]
+fn select_frame(sprite: &mut AtlasSprite, #[resource] input: &InputEvent){
+    if input.is_pressed(Key::Right){
+        sprite.index = (sprite.index + 1) % sprite.atlas.len();
+    }
+    if input.is_pressed(Key::Left){
+        sprite.index = (sprite.index + sprite.atlas.len() - 1) % sprite.atlas.len();
+    }
+    if input.is_pressed(Key::F){
+        sprite.flip_x = !sprite.flip_x;
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    // Texture is cut into grid of 100x100 frames
+    let texture = Sprite::new(include_bytes!("sprite.png"), 0).texture;
+    let atlas = TextureAtlas::from_grid(texture, (100, 100), (0, 0), (0, 0));
+
+    commands.push((Transform2D::new(-150.0, 0.0, 0.0, 1.0), AtlasSprite::new(atlas.clone(), 0, 0)));
+    // Sprite stands on its bottom edge
+    commands.push((
+        Transform2D::new(150.0, 0.0, 0.0, 2.0),
+        AtlasSprite::new(atlas, 5, 0).with_pivot(vec2(0.5, 1.0))
+    ));
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Sprite Sheet")
+        .add_system(select_frame_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
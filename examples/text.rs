@@ -0,0 +1,49 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+struct Counter(u32);
+
+#[system(for_each)]
+fn count(counter: &mut Counter, text: &mut Text2D){
+    counter.0 += 1;
+    text.set_text(&format!("Frames: {}", counter.0));
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    let font = Font::new(include_bytes!("fonts/Cantarell-Regular.ttf")).unwrap();
+
+    commands.push((
+        Transform2D::new(0.0, 150.0, 0.0, 1.0),
+        Text2D::new("Hello Stray!", font.clone(), 64.0).with_color(StrayColor::ORANGE)
+    ));
+    // Long text is wrapped to width of bounds
+    let paragraph = "Text is rasterized into glyph atlas once and all glyphs of one text are drawn at once. \
+        Lines are wrapped to width of bounds and aligned inside of them.";
+    commands.push((
+        Transform2D::ZERO,
+        Text2D::new(paragraph, font.clone(), 24.0)
+            .with_bounds(vec2(400.0, 200.0))
+            .with_align(TextAlign::Center)
+    ));
+    // Bitmap font is drawn with nearest filtering, its size is multiple of native size so pixels stay crisp
+    let pixel_font = Font::from_bmfont(include_bytes!("fonts/pixel.fnt"), &[include_bytes!("fonts/pixel_0.png")]).unwrap();
+    commands.push((
+        Transform2D::new(0.0, -180.0, 0.0, 1.0),
+        Text2D::new("PRESS START", pixel_font, 36.0).with_color(StrayColor::CYAN)
+    ));
+    commands.push((
+        Counter(0),
+        Transform2D::new(-380.0, -280.0, 0.0, 1.0),
+        Text2D::new("Frames: 0", font, 20.0).with_pivot(vec2(0.0, 1.0)).with_color(StrayColor::GRAY)
+    ));
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Text")
+        .add_system(count_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
@@ -0,0 +1,50 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+// Added to objects of class "spinner" from their properties
+struct Spinner(f32);
+
+#[system(for_each)]
+fn spin(transform: &mut Transform2D, spinner: &Spinner, #[resource] time: &Time){
+    transform.rotate(spinner.0 * time.delta);
+}
+
+// Maps read tileset and its image through callback, paths are relative to map
+fn files(path: &str) -> Option<Vec<u8>>{
+    match path{
+        "tiles.tsx" => Some(include_bytes!("tiles.tsx").to_vec()),
+        "tiles.png" => Some(include_bytes!("tiles.png").to_vec()),
+        _ => None
+    }
+}
+
+fn add_components(object: &TiledObject, entity: Entity, commands: &mut CommandBuffer){
+    match object.class.as_str(){
+        "spinner" => {
+            let speed = object.properties.get_float("speed").unwrap_or(1.0);
+            commands.add_component(entity, Spinner(speed as f32));
+        }
+        _ => println!("{} {:?} at {}", object.name, object.shape, object.position)
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    // Orthogonal map with external tileset, animated water and objects
+    let level = TiledMap::from_tmx(include_str!("level.tmx"), files).unwrap();
+    println!("{:?}", level.properties.get_str("title"));
+    level.spawn_with(commands, Transform2D::new(-480.0, 200.0, 0.0, 2.0), add_components);
+
+    // Isometric map in JSON format
+    let island = TiledMap::from_tmj(include_str!("island.tmj"), files).unwrap();
+    island.spawn_with(commands, Transform2D::new(300.0, 200.0, 0.0, 3.0), add_components);
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Tiled Maps")
+        .add_system(spin_system())
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
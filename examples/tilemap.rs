@@ -0,0 +1,62 @@
+use stray::prelude::*;
+use legion::{*, systems::CommandBuffer};
+
+const GRASS: usize = 0;
+const DIRT: usize = 1;
+const WATER: usize = 2;
+const ARROW: usize = 4;
+const FLOWER: usize = 5;
+const WALL: usize = 6;
+
+// Space plants flower in next cell, only chunk with that cell is built again
+#[system(for_each)]
+fn plant_flowers(tilemap: &mut Tilemap, #[resource] input: &InputEvent, #[state] planted: &mut u32){
+    if input.is_pressed(Key::Space){
+        let (width, height) = tilemap.size();
+        let cell = planted.wrapping_mul(7919) % (width * height);
+        let tint = StrayColor::hsv((*planted * 37 % 360) as f32, 0.6, 1.0);
+        tilemap.set(1, cell % width, cell / width, Some(Tile::new(FLOWER).with_tint(tint)));
+        *planted += 1;
+    }
+}
+
+#[system]
+fn setup_scene(commands: &mut CommandBuffer){
+    let texture = Sprite::new(include_bytes!("tiles.png"), 0).texture.with_sampling(TextureSampling::NEAREST);
+    let tileset = TextureAtlas::from_grid(texture, (16, 16), (0, 0), (0, 0));
+
+    // 80x60 tiles are split into 6 chunks on every layer
+    let mut map = Tilemap::new(tileset, (80, 60), vec2(16.0, 16.0))
+        .with_layer("ground", 0)
+        .with_layer("decor", 1);
+    map.fill(0, Some(Tile::new(GRASS)));
+    for y in 0..60{
+        for x in 0..80{
+            if (x as i32 - 40).pow(2) + (y as i32 - 30).pow(2) < 60{
+                map.set(0, x, y, Some(Tile::new(WATER)));
+            }
+            else if x == 20 || y == 15{
+                map.set(0, x, y, Some(Tile::new(DIRT)));
+            }
+            if y == 5 && x > 50{
+                map.set(1, x, y, Some(Tile::new(WALL)));
+            }
+        }
+    }
+    // Arrows show rotation and flipping of tiles
+    for quarter_turns in 0..4{
+        map.set(1, 22 + quarter_turns * 2, 10, Some(Tile::new(ARROW).with_rotation(quarter_turns)));
+    }
+    map.set(1, 30, 10, Some(Tile::new(ARROW).with_flip(false, true).with_tint(StrayColor::YELLOW)));
+
+    commands.push((Transform2D::new(-640.0, 480.0, 0.0, 1.0), map));
+}
+
+fn main(){
+    Stray::new()
+        .with_title("Tilemap")
+        .add_system(plant_flowers_system(0))
+        .run_once(setup_scene_system())
+        .build()
+        .run();
+}
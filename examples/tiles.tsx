@@ -0,0 +1,10 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="tiles" tilewidth="16" tileheight="16" tilecount="8" columns="4">
+ <image source="tiles.png" width="64" height="32"/>
+ <tile id="2">
+  <animation>
+   <frame tileid="2" duration="600"/>
+   <frame tileid="7" duration="100"/>
+  </animation>
+ </tile>
+</tileset>
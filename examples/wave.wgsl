@@ -0,0 +1,26 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) world_position: vec2<f32>,
+}
+
+struct Wave {
+    time: f32,
+    strength: f32,
+}
+
+@group(0) @binding(0)
+var t_diffuse: texture_2d<f32>;
+@group(0) @binding(1)
+var s_diffuse: sampler;
+
+@group(2) @binding(0)
+var<uniform> wave: Wave;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = sin(in.tex_coords.y * 20.0 + wave.time * 4.0) * wave.strength;
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords + vec2<f32>(offset, 0.0));
+    return color * in.color;
+}